@@ -0,0 +1,69 @@
+//! Minimal IPFS HTTP API client for manifest publishing
+//!
+//! Talks to a Kubo-compatible `/api/v0` endpoint, e.g.
+//! `http://127.0.0.1:5001`.
+
+use crate::BlockchainError;
+use protrace_merkle_tree::Manifest;
+use serde::Deserialize;
+
+const MULTIPART_BOUNDARY: &str = "protrace-manifest-boundary";
+
+#[derive(Deserialize)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+fn api_url(ipfs_api: &str, path: &str) -> String {
+    format!("{}/api/v0/{}", ipfs_api.trim_end_matches('/'), path)
+}
+
+/// Upload and pin `manifest` as JSON, returning its CID (v1)
+pub async fn upload_manifest(
+    ipfs_api: &str,
+    manifest: &Manifest,
+) -> Result<String, BlockchainError> {
+    let json = serde_json::to_string(manifest)
+        .map_err(|e| BlockchainError::IpfsError(format!("Failed to serialize manifest: {}", e)))?;
+
+    let body = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"manifest.json\"\r\n\
+         Content-Type: application/json\r\n\r\n{json}\r\n--{b}--\r\n",
+        b = MULTIPART_BOUNDARY,
+        json = json
+    );
+
+    let response = reqwest::Client::new()
+        .post(api_url(ipfs_api, "add?cid-version=1&pin=true"))
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+        )
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| BlockchainError::IpfsError(format!("Upload failed: {}", e)))?;
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| BlockchainError::IpfsError(format!("Failed to read response: {}", e)))?;
+    let added: AddResponse = serde_json::from_str(&text)
+        .map_err(|e| BlockchainError::IpfsError(format!("Unexpected add response: {}", e)))?;
+
+    Ok(added.hash)
+}
+
+/// Unpin `cid` so the node may garbage-collect it
+pub async fn unpin(ipfs_api: &str, cid: &str) -> Result<(), BlockchainError> {
+    reqwest::Client::new()
+        .post(api_url(ipfs_api, &format!("pin/rm?arg={}", cid)))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| BlockchainError::IpfsError(format!("Unpin failed: {}", e)))?;
+
+    Ok(())
+}
@@ -3,17 +3,23 @@
 //! Solana blockchain integration for Merkle root anchoring and edition management
 
 use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::ed25519_program;
+use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::{Keypair, Signature, Signer};
-use anchor_client::solana_sdk::system_program;
+use anchor_client::solana_sdk::{system_program, sysvar};
 use anchor_client::{Client, Cluster};
+use anchor_lang::AnchorDeserialize;
 use anyhow::Result;
-use protrace_merkle_tree::Manifest;
-use serde::{Deserialize, Serialize};
+use protrace_merkle_tree::leaf_encoding::hash_leaf;
+use protrace_merkle_tree::{proof_to_raw, verify_raw, Manifest, ProofElement};
+use std::future::Future;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+pub mod ipfs;
 pub mod types;
 pub use types::*;
 
@@ -29,14 +35,74 @@ pub enum BlockchainError {
     WalletError(String),
     #[error("RPC error: {0}")]
     RpcError(String),
+    #[error("IPFS error: {0}")]
+    IpfsError(String),
+    #[error("Unsupported account schema version: {0}")]
+    UnsupportedSchemaVersion(u8),
+    #[error("Edition registry uses the pre-schema (v0) layout; run migrate_edition_registry")]
+    LegacyEditionRegistry,
+    #[error("Oracle signature does not match the anchor intent")]
+    InvalidOracleSignature,
 }
 
 /// Program ID for ProTrace on devnet
 pub const PROTRACE_PROGRAM_ID: &str = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
 
+/// Maximum compute units a single Solana transaction may request
+pub const MAX_TRANSACTION_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Approximate fixed cost of a `batch_register_editions` instruction
+pub const BATCH_BASE_COMPUTE_UNITS: u32 = 20_000;
+
+/// Approximate cost of registering one edition within a batch
+pub const EDITION_COMPUTE_UNITS: u32 = 25_000;
+
+/// Edition cap enforced on-chain by `batch_register_editions`
+pub const MAX_BATCH_EDITIONS: usize = 50;
+
+/// Chunk transactions a pipelined batch registration keeps unconfirmed at once
+pub const PIPELINE_MAX_IN_FLIGHT: usize = 8;
+
+/// How long a pipelined batch waits for a chunk to confirm before failing it
+pub const PIPELINE_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay between signature status polls while awaiting confirmations
+const PIPELINE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Account layout version this client understands (program's `ACCOUNT_SCHEMA_VERSION`)
+pub const ACCOUNT_SCHEMA_VERSION: u8 = 1;
+
+/// Chains an edition registry keeps totals for (program's `MAX_TRACKED_CHAINS`)
+pub const MAX_TRACKED_CHAINS: usize = 8;
+
+/// Size of an edition registry account still in the pre-schema (v0) layout
+///
+/// v0 accounts have no schema byte; the program allocated them with exactly
+/// this many bytes, which no current-layout account has.
+pub const EDITION_REGISTRY_V0_SPACE: usize = 8 + 32 + 32 + (4 + 64) + 8 + (4 + 64) + 8 + 32 + 8;
+
+/// Registry ID used by single-platform deployments
+pub const DEFAULT_REGISTRY_ID: [u8; 16] = [0u8; 16];
+
+/// Domain tag of relayed anchor intents (program's `ANCHOR_INTENT_DOMAIN`)
+pub const ANCHOR_INTENT_DOMAIN: &[u8] = b"protrace:anchor-intent:v1";
+
+/// Derive the anchor account PDA of `oracle`'s registry
+///
+/// Registry IDs are scoped to the oracle, so two oracles using the same
+/// `registry_id` anchor to different accounts.
+pub fn anchor_account_address(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    registry_id: &[u8; 16],
+) -> Pubkey {
+    let seeds: [&[u8]; 3] = [b"protrace_anchor", oracle.as_ref(), registry_id];
+    Pubkey::find_program_address(&seeds, program_id).0
+}
+
 /// ProTrace blockchain client for Solana
 pub struct ProTraceClient {
-    client: Client,
+    client: Client<Rc<Keypair>>,
     program_id: Pubkey,
     payer: Rc<Keypair>,
 }
@@ -159,9 +225,31 @@ impl ProTraceClient {
         Ok(signature)
     }
 
+    /// Update Merkle root only if it differs from the stored one
+    ///
+    /// Costs one account read, and saves the transaction fee when the
+    /// root is unchanged. Returns `None` if no transaction was sent.
+    pub async fn update_merkle_root_if_changed(
+        &self,
+        new_root: [u8; 32],
+    ) -> Result<Option<Signature>> {
+        let current = self.get_merkle_account().await?;
+
+        if current.root == hex::encode(new_root) {
+            log::info!("Merkle root unchanged, skipping update");
+            return Ok(None);
+        }
+
+        Ok(Some(self.update_merkle_root(new_root).await?))
+    }
+
     /// Anchor Merkle root with oracle authority
+    ///
+    /// Each `registry_id` has its own anchor account on-chain, owned by the
+    /// payer as oracle.
     pub async fn anchor_merkle_root_oracle(
         &self,
+        registry_id: [u8; 16],
         merkle_root: [u8; 32],
         manifest_cid: String,
         asset_count: u64,
@@ -171,13 +259,12 @@ impl ProTraceClient {
         log::info!("  Root: {}", hex::encode(merkle_root));
         log::info!("  CID: {}", manifest_cid);
         log::info!("  Assets: {}", asset_count);
+        log::info!("  Registry: {}", hex::encode(registry_id));
 
         let program = self.client.program(self.program_id)?;
 
-        let (anchor_account, _bump) = Pubkey::find_program_address(
-            &[b"protrace_anchor"],
-            &self.program_id,
-        );
+        let anchor_account =
+            anchor_account_address(&self.program_id, &self.payer.pubkey(), &registry_id);
 
         let signature = program
             .request()
@@ -197,10 +284,77 @@ impl ProTraceClient {
                 is_writable: false,
             })
             .args(InstructionData::AnchorMerkleRootOracle {
+                registry_id,
+                merkle_root,
+                manifest_cid,
+                asset_count,
+                timestamp,
+            })
+            .send()?;
+
+        log::info!("Transaction signature: {}", signature);
+        Ok(signature)
+    }
+
+    /// Anchor a Merkle root signed off-chain by the oracle, paying as relayer
+    ///
+    /// `oracle_sig` must be the oracle's signature over
+    /// [`anchor_intent_message`] for the default registry. It is checked
+    /// locally before sending, then again on-chain through an Ed25519 verify
+    /// instruction placed ahead of the anchor instruction. The oracle must
+    /// already have anchored the registry itself; relaying can't create it.
+    pub async fn anchor_with_oracle_signature(
+        &self,
+        merkle_root: [u8; 32],
+        manifest_cid: String,
+        asset_count: u64,
+        timestamp: i64,
+        oracle_sig: Signature,
+        oracle_pubkey: Pubkey,
+    ) -> Result<Signature> {
+        let registry_id = DEFAULT_REGISTRY_ID;
+        log::info!("Relaying oracle-signed Merkle root");
+        log::info!("  Root: {}", hex::encode(merkle_root));
+        log::info!("  Oracle: {}", oracle_pubkey);
+
+        let message = anchor_intent_message(
+            &self.program_id,
+            registry_id,
+            merkle_root,
+            &manifest_cid,
+            asset_count,
+            timestamp,
+        );
+        let verify_ix = oracle_intent_instruction(&oracle_pubkey, &oracle_sig, &message)?;
+
+        let program = self.client.program(self.program_id)?;
+        let anchor_account = anchor_account_address(&self.program_id, &oracle_pubkey, &registry_id);
+
+        let signature = program
+            .request()
+            .instruction(verify_ix)
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: anchor_account,
+                is_signer: false,
+                is_writable: true,
+            })
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: self.payer.pubkey(),
+                is_signer: true,
+                is_writable: true,
+            })
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: sysvar::instructions::ID,
+                is_signer: false,
+                is_writable: false,
+            })
+            .args(InstructionData::AnchorMerkleRootRelayed {
+                registry_id,
                 merkle_root,
                 manifest_cid,
                 asset_count,
                 timestamp,
+                oracle: oracle_pubkey,
             })
             .send()?;
 
@@ -208,6 +362,30 @@ impl ProTraceClient {
         Ok(signature)
     }
 
+    /// Upload `manifest` to IPFS, then anchor its root under the returned CID
+    ///
+    /// Uploading first means an anchored root always has a retrievable
+    /// manifest. If anchoring fails the upload is unpinned again (failures
+    /// to unpin are only logged) and the anchoring error is returned.
+    pub async fn anchor_with_manifest(
+        &self,
+        manifest: &Manifest,
+        ipfs_api: &str,
+    ) -> Result<(String, Signature)> {
+        let (merkle_root, _, asset_count, timestamp) = manifest_to_anchor_params(manifest, None);
+
+        publish_and_anchor(manifest, ipfs_api, |cid| {
+            self.anchor_merkle_root_oracle(
+                DEFAULT_REGISTRY_ID,
+                merkle_root,
+                cid,
+                asset_count,
+                timestamp,
+            )
+        })
+        .await
+    }
+
     /// Initialize edition registry
     pub async fn initialize_edition_registry(&self, oracle_authority: Pubkey) -> Result<Signature> {
         log::info!("Initializing edition registry");
@@ -244,6 +422,44 @@ impl ProTraceClient {
         Ok(signature)
     }
 
+    /// Rewrite a pre-schema (v0) edition registry in the current layout
+    ///
+    /// Must be signed by the registry's oracle authority (the payer), which
+    /// also covers the extra rent. Fails on registries already migrated.
+    pub async fn migrate_edition_registry(&self) -> Result<Signature> {
+        log::info!("Migrating edition registry");
+
+        let program = self.client.program(self.program_id)?;
+
+        let (edition_registry, _bump) = Pubkey::find_program_address(
+            &[b"edition_registry"],
+            &self.program_id,
+        );
+
+        let signature = program
+            .request()
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: edition_registry,
+                is_signer: false,
+                is_writable: true,
+            })
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: self.payer.pubkey(),
+                is_signer: true,
+                is_writable: true,
+            })
+            .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                pubkey: system_program::ID,
+                is_signer: false,
+                is_writable: false,
+            })
+            .args(InstructionData::MigrateEditionRegistry)
+            .send()?;
+
+        log::info!("Transaction signature: {}", signature);
+        Ok(signature)
+    }
+
     /// Batch register editions
     pub async fn batch_register_editions(
         &self,
@@ -287,6 +503,166 @@ impl ProTraceClient {
         Ok(signature)
     }
 
+    /// Batch register editions in compute-budget-sized chunks, one at a time
+    ///
+    /// Chunk `i` is submitted with batch ID `<batch_id>-<i>`. Stops at the
+    /// first failing chunk; see [`BatchProgress`] for resuming.
+    pub async fn batch_register_editions_chunked(
+        &self,
+        updates: &[EditionUpdate],
+        batch_id: &str,
+        new_merkle_root: [u8; 32],
+        ipfs_cid: &str,
+    ) -> BatchProgress {
+        self.resume_from(updates, batch_id, new_merkle_root, ipfs_cid, 0)
+            .await
+    }
+
+    /// Continue a chunked registration from chunk `start_chunk`
+    ///
+    /// `updates` must be the same as in the original run, so they split into
+    /// the same chunks.
+    pub async fn resume_from(
+        &self,
+        updates: &[EditionUpdate],
+        batch_id: &str,
+        new_merkle_root: [u8; 32],
+        ipfs_cid: &str,
+        start_chunk: usize,
+    ) -> BatchProgress {
+        let chunks = chunk_by_compute_budget(updates, MAX_TRANSACTION_COMPUTE_UNITS);
+
+        register_chunks(&chunks, start_chunk, |i, chunk| {
+            self.batch_register_editions(
+                chunk.to_vec(),
+                format!("{}-{}", batch_id, i),
+                new_merkle_root,
+                ipfs_cid.to_string(),
+            )
+        })
+        .await
+    }
+
+    /// Batch register editions in several chunks, pipelining confirmations
+    ///
+    /// Up to [`PIPELINE_MAX_IN_FLIGHT`] chunks are submitted before their
+    /// confirmations are awaited together, so confirmation latency is paid
+    /// once per window instead of once per chunk. Chunks in a window may land
+    /// in any order, so each should carry the final `new_merkle_root`.
+    ///
+    /// Returns one result per chunk, in input order; a failed chunk does not
+    /// stop the others.
+    pub async fn batch_register_editions_pipelined(
+        &self,
+        chunks: Vec<EditionBatch>,
+    ) -> Result<Vec<Result<Signature, BlockchainError>>> {
+        log::info!("Pipelining {} edition batches", chunks.len());
+
+        let program = self.client.program(self.program_id)?;
+        let rpc_client = program.rpc();
+
+        let (edition_registry, _bump) = Pubkey::find_program_address(
+            &[b"edition_registry"],
+            &self.program_id,
+        );
+
+        let mut results = Vec::with_capacity(chunks.len());
+        let mut remaining = chunks.into_iter();
+
+        loop {
+            let window: Vec<EditionBatch> =
+                remaining.by_ref().take(PIPELINE_MAX_IN_FLIGHT).collect();
+            if window.is_empty() {
+                break;
+            }
+
+            // Submit the whole window without waiting for confirmation
+            let sent: Vec<Result<Signature, BlockchainError>> = window
+                .into_iter()
+                .map(|batch| {
+                    log::info!("  Submitting batch {}", batch.batch_id);
+                    let transaction = program
+                        .request()
+                        .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                            pubkey: edition_registry,
+                            is_signer: false,
+                            is_writable: true,
+                        })
+                        .accounts(anchor_client::solana_sdk::instruction::AccountMeta {
+                            pubkey: self.payer.pubkey(),
+                            is_signer: true,
+                            is_writable: false,
+                        })
+                        .args(InstructionData::BatchRegisterEditions {
+                            edition_updates: batch.edition_updates,
+                            batch_id: batch.batch_id,
+                            new_merkle_root: batch.new_merkle_root,
+                            ipfs_cid: batch.ipfs_cid,
+                        })
+                        .signed_transaction()
+                        .map_err(|e| BlockchainError::AnchorClientError(e.to_string()))?;
+
+                    rpc_client
+                        .send_transaction(&transaction)
+                        .map_err(|e| BlockchainError::TransactionFailed(e.to_string()))
+                })
+                .collect();
+
+            results.extend(await_confirmations(&rpc_client, sent).await);
+        }
+
+        Ok(results)
+    }
+
+    /// Fetch and decode the anchor account of `oracle`'s registry
+    pub async fn get_anchor_account(
+        &self,
+        oracle: &Pubkey,
+        registry_id: [u8; 16],
+    ) -> Result<AnchorAccount> {
+        let rpc_client = self.client.program(self.program_id)?.rpc();
+
+        let anchor_account = anchor_account_address(&self.program_id, oracle, &registry_id);
+
+        let data = rpc_client
+            .get_account_data(&anchor_account)
+            .map_err(|e| BlockchainError::RpcError(e.to_string()))?;
+
+        Ok(decode_anchor_account(&data)?)
+    }
+
+    /// Fetch and decode the Merkle root account seeded by `merkle_root`
+    pub async fn get_merkle_account(&self) -> Result<MerkleAccountView> {
+        let rpc_client = self.client.program(self.program_id)?.rpc();
+
+        let (merkle_account, _bump) = Pubkey::find_program_address(
+            &[b"merkle_root"],
+            &self.program_id,
+        );
+
+        let data = rpc_client
+            .get_account_data(&merkle_account)
+            .map_err(|e| BlockchainError::RpcError(e.to_string()))?;
+
+        Ok(decode_merkle_account(&data)?)
+    }
+
+    /// Fetch and decode the edition registry account
+    pub async fn get_edition_registry(&self) -> Result<EditionRegistryAccount> {
+        let rpc_client = self.client.program(self.program_id)?.rpc();
+
+        let (edition_registry, _bump) = Pubkey::find_program_address(
+            &[b"edition_registry"],
+            &self.program_id,
+        );
+
+        let data = rpc_client
+            .get_account_data(&edition_registry)
+            .map_err(|e| BlockchainError::RpcError(e.to_string()))?;
+
+        Ok(decode_edition_registry_account(&data)?)
+    }
+
     /// Get balance of payer account
     pub async fn get_balance(&self) -> Result<u64> {
         let rpc_client = self.client.program(self.program_id)?.rpc();
@@ -304,14 +680,282 @@ impl ProTraceClient {
     }
 }
 
+/// Run `register` on `chunks[start_chunk..]` in order, stopping at the first failure
+async fn register_chunks<F, Fut>(
+    chunks: &[&[EditionUpdate]],
+    start_chunk: usize,
+    mut register: F,
+) -> BatchProgress
+where
+    F: FnMut(usize, &[EditionUpdate]) -> Fut,
+    Fut: Future<Output = Result<Signature>>,
+{
+    let mut progress = BatchProgress {
+        completed_chunks: start_chunk.min(chunks.len()),
+        signatures: Vec::new(),
+        failed_at: None,
+    };
+
+    for (i, chunk) in chunks.iter().enumerate().skip(start_chunk) {
+        match register(i, chunk).await {
+            Ok(signature) => {
+                progress.completed_chunks += 1;
+                progress.signatures.push(signature);
+            }
+            Err(e) => {
+                log::warn!("Chunk {}/{} failed: {}", i + 1, chunks.len(), e);
+                progress.failed_at = Some(i);
+                break;
+            }
+        }
+    }
+
+    progress
+}
+
+/// Upload `manifest`, then run `anchor` with its CID, unpinning on failure
+async fn publish_and_anchor<F, Fut>(
+    manifest: &Manifest,
+    ipfs_api: &str,
+    anchor: F,
+) -> Result<(String, Signature)>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<Signature>>,
+{
+    let cid = ipfs::upload_manifest(ipfs_api, manifest).await?;
+    log::info!("Manifest uploaded: {}", cid);
+
+    match anchor(cid.clone()).await {
+        Ok(signature) => Ok((cid, signature)),
+        Err(e) => {
+            log::warn!("Anchoring failed, unpinning manifest {}: {}", cid, e);
+            if let Err(unpin_err) = ipfs::unpin(ipfs_api, &cid).await {
+                log::error!("Failed to unpin manifest {}: {}", cid, unpin_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Poll until every sent transaction confirms, fails, or times out
+async fn await_confirmations(
+    rpc_client: &anchor_client::solana_client::rpc_client::RpcClient,
+    sent: Vec<Result<Signature, BlockchainError>>,
+) -> Vec<Result<Signature, BlockchainError>> {
+    let deadline = Instant::now() + PIPELINE_CONFIRM_TIMEOUT;
+    let signatures: Vec<Option<Signature>> =
+        sent.iter().map(|r| r.as_ref().ok().copied()).collect();
+    // `None` while a sent transaction is still awaiting confirmation
+    let mut outcomes: Vec<Option<Result<Signature, BlockchainError>>> =
+        sent.into_iter().map(|r| r.err().map(Err)).collect();
+
+    loop {
+        let pending: Vec<usize> = (0..outcomes.len())
+            .filter(|&i| outcomes[i].is_none())
+            .collect();
+        if pending.is_empty() {
+            break;
+        }
+
+        if Instant::now() >= deadline {
+            for i in pending {
+                outcomes[i] = Some(Err(BlockchainError::TransactionFailed(format!(
+                    "{} not confirmed within {:?}",
+                    signatures[i].unwrap_or_default(),
+                    PIPELINE_CONFIRM_TIMEOUT
+                ))));
+            }
+            break;
+        }
+
+        let batch: Vec<Signature> = pending.iter().filter_map(|&i| signatures[i]).collect();
+        match rpc_client.get_signature_statuses(&batch) {
+            Ok(response) => {
+                for (&i, status) in pending.iter().zip(response.value) {
+                    let Some(status) = status else { continue };
+                    if let Some(err) = status.err {
+                        outcomes[i] =
+                            Some(Err(BlockchainError::TransactionFailed(err.to_string())));
+                    } else if status.satisfies_commitment(CommitmentConfig::confirmed()) {
+                        outcomes[i] = signatures[i].map(Ok);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Signature status poll failed: {}", e),
+        }
+
+        tokio::time::sleep(PIPELINE_POLL_INTERVAL).await;
+    }
+
+    outcomes.into_iter().flatten().collect()
+}
+
+/// Decode raw `MerkleAccount` data, including its 8-byte discriminator
+pub fn decode_merkle_account(data: &[u8]) -> Result<MerkleAccountView, BlockchainError> {
+    if data.len() < 8 {
+        return Err(BlockchainError::RpcError("Merkle account data too short".to_string()));
+    }
+    let account = MerkleAccount::deserialize(&mut &data[8..])
+        .map_err(|e| BlockchainError::RpcError(e.to_string()))?;
+
+    Ok(account.into())
+}
+
+/// Decode raw `AnchorAccount` data, including its 8-byte discriminator
+///
+/// The schema version byte that follows the discriminator selects the
+/// layout; versions this client doesn't know are rejected rather than
+/// misread.
+pub fn decode_anchor_account(data: &[u8]) -> Result<AnchorAccount, BlockchainError> {
+    let Some(&schema_version) = data.get(8) else {
+        return Err(BlockchainError::RpcError("Anchor account data too short".to_string()));
+    };
+
+    match schema_version {
+        ACCOUNT_SCHEMA_VERSION => AnchorDeserialize::deserialize(&mut &data[8..])
+            .map_err(|e| BlockchainError::RpcError(e.to_string())),
+        other => Err(BlockchainError::UnsupportedSchemaVersion(other)),
+    }
+}
+
+/// Decode raw `EditionRegistryAccount` data, including its 8-byte discriminator
+///
+/// Like [`decode_anchor_account`], the schema version byte selects the
+/// layout and unknown versions are rejected. Accounts still in the v0
+/// layout, which predates the schema byte, are recognized by their size
+/// and rejected with `LegacyEditionRegistry`.
+pub fn decode_edition_registry_account(
+    data: &[u8],
+) -> Result<EditionRegistryAccount, BlockchainError> {
+    if data.len() == EDITION_REGISTRY_V0_SPACE {
+        return Err(BlockchainError::LegacyEditionRegistry);
+    }
+    let Some(&schema_version) = data.get(8) else {
+        return Err(BlockchainError::RpcError("Edition registry data too short".to_string()));
+    };
+
+    match schema_version {
+        ACCOUNT_SCHEMA_VERSION => AnchorDeserialize::deserialize(&mut &data[8..])
+            .map_err(|e| BlockchainError::RpcError(e.to_string())),
+        other => Err(BlockchainError::UnsupportedSchemaVersion(other)),
+    }
+}
+
+/// Message an oracle signs to authorize a relayed anchor
+///
+/// Must match the program's `anchor_intent_message` byte for byte: domain
+/// tag, program ID, registry ID, root, asset count and timestamp
+/// (little-endian), then the CID bytes.
+pub fn anchor_intent_message(
+    program_id: &Pubkey,
+    registry_id: [u8; 16],
+    merkle_root: [u8; 32],
+    manifest_cid: &str,
+    asset_count: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ANCHOR_INTENT_DOMAIN.len() + 96 + manifest_cid.len());
+    message.extend_from_slice(ANCHOR_INTENT_DOMAIN);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(&registry_id);
+    message.extend_from_slice(&merkle_root);
+    message.extend_from_slice(&asset_count.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message.extend_from_slice(manifest_cid.as_bytes());
+    message
+}
+
+/// Build the Ed25519 verify instruction for an oracle's signed intent
+///
+/// Public key, signature and message are all carried inline, which is the
+/// only layout the program accepts. A signature that doesn't verify
+/// against `oracle_pubkey` is rejected here instead of failing on-chain.
+pub fn oracle_intent_instruction(
+    oracle_pubkey: &Pubkey,
+    oracle_sig: &Signature,
+    message: &[u8],
+) -> Result<Instruction, BlockchainError> {
+    if !oracle_sig.verify(oracle_pubkey.as_ref(), message) {
+        return Err(BlockchainError::InvalidOracleSignature);
+    }
+    let message_len =
+        u16::try_from(message.len()).map_err(|_| BlockchainError::InvalidOracleSignature)?;
+
+    // Header (count + padding), one offsets record, then the inline data
+    const PUBKEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBKEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    let mut data = vec![1u8, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBKEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message_len,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(oracle_pubkey.as_ref());
+    data.extend_from_slice(oracle_sig.as_ref());
+    data.extend_from_slice(message);
+
+    Ok(Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    })
+}
+
+/// Estimate compute units consumed by `batch_register_editions`
+///
+/// A linear model (base + per-edition cost); real usage varies with
+/// account sizes and logging, so leave headroom below the budget.
+pub fn estimate_batch_cost(updates: &[EditionUpdate]) -> u32 {
+    BATCH_BASE_COMPUTE_UNITS.saturating_add(
+        EDITION_COMPUTE_UNITS.saturating_mul(updates.len().min(u32::MAX as usize) as u32),
+    )
+}
+
+/// Split edition updates into batches whose estimated cost fits `budget`
+///
+/// Batches also respect the on-chain cap of [`MAX_BATCH_EDITIONS`].
+pub fn chunk_by_compute_budget(updates: &[EditionUpdate], budget: u32) -> Vec<&[EditionUpdate]> {
+    let per_batch = (budget.saturating_sub(BATCH_BASE_COMPUTE_UNITS) / EDITION_COMPUTE_UNITS) as usize;
+    let per_batch = per_batch.clamp(1, MAX_BATCH_EDITIONS);
+    updates.chunks(per_batch).collect()
+}
+
+/// Check that an edition is committed by a Merkle root before submitting it
+///
+/// Run this against the `new_merkle_root` passed to
+/// `batch_register_editions`; a malformed proof counts as not included.
+pub fn verify_edition_in_root(
+    update: &EditionUpdate,
+    proof: &[ProofElement],
+    root: [u8; 32],
+) -> bool {
+    let leaf_hash = hash_leaf(&update.leaf_bytes());
+    proof_to_raw(proof).is_ok_and(|raw| verify_raw(&leaf_hash, &raw, &root))
+}
+
 /// Helper to convert Manifest to blockchain format
-pub fn manifest_to_anchor_params(manifest: &Manifest) -> ([u8; 32], String, u64, i64) {
+///
+/// `timestamp` defaults to now; pass an explicit one to make re-anchoring
+/// the same manifest reproducible.
+pub fn manifest_to_anchor_params(
+    manifest: &Manifest,
+    timestamp: Option<i64>,
+) -> ([u8; 32], String, u64, i64) {
     let root_bytes = hex::decode(&manifest.root)
         .unwrap_or_else(|_| vec![0u8; 32]);
     let mut root = [0u8; 32];
     root.copy_from_slice(&root_bytes[..32.min(root_bytes.len())]);
 
-    let timestamp = chrono::Utc::now().timestamp();
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp());
 
     (root, manifest.root.clone(), manifest.total_leaves as u64, timestamp)
 }
@@ -325,4 +969,539 @@ mod tests {
         let program_id = Pubkey::from_str(PROTRACE_PROGRAM_ID);
         assert!(program_id.is_ok());
     }
+
+    #[test]
+    fn test_oracle_intent_instruction() {
+        let program_id = Pubkey::from_str(PROTRACE_PROGRAM_ID).unwrap();
+        let oracle = Keypair::new();
+        let intent = |root: [u8; 32]| {
+            anchor_intent_message(&program_id, DEFAULT_REGISTRY_ID, root, "bafyintent", 3, 100)
+        };
+        let message = intent([9u8; 32]);
+        let oracle_sig = oracle.sign_message(&message);
+
+        let ix = oracle_intent_instruction(&oracle.pubkey(), &oracle_sig, &message).unwrap();
+        assert_eq!(ix.program_id, ed25519_program::ID);
+        assert_eq!(ix.data[0], 1);
+        assert_eq!(&ix.data[16..48], oracle.pubkey().as_ref());
+        assert_eq!(&ix.data[48..112], oracle_sig.as_ref());
+        assert_eq!(&ix.data[112..], &message[..]);
+
+        // Forged: signed by a key other than the claimed oracle
+        let forged = Keypair::new().sign_message(&message);
+        assert!(matches!(
+            oracle_intent_instruction(&oracle.pubkey(), &forged, &message),
+            Err(BlockchainError::InvalidOracleSignature)
+        ));
+
+        // The oracle's signature doesn't carry over to a different root
+        let other = intent([8u8; 32]);
+        assert!(oracle_intent_instruction(&oracle.pubkey(), &oracle_sig, &other).is_err());
+    }
+
+    #[test]
+    fn test_instruction_data_uses_anchor_sighash() {
+        use anchor_lang::InstructionData as _;
+
+        let data = InstructionData::InitializeMerkleRoot { root: [0x11; 32] }.data();
+        assert_eq!(hex::encode(&data[..8]), "88512b71973e917b");
+        assert_eq!(&data[8..], &[0x11; 32]);
+
+        let update = EditionUpdate {
+            dna_hash: [1; 32],
+            chain: *b"ethereum\0\0",
+            contract: [2; 32],
+            token_id: [3; 32],
+            edition_no: 7,
+            edition_mode: EditionMode::Serial,
+            max_editions: Some(10),
+        };
+        let data = InstructionData::BatchRegisterEditions {
+            edition_updates: vec![update],
+            batch_id: "b1".to_string(),
+            new_merkle_root: [4; 32],
+            ipfs_cid: "cid".to_string(),
+        }
+        .data();
+        assert_eq!(hex::encode(&data[..8]), "2655e73697ecac08");
+        // Vec length prefix, then the update's fields in program order
+        assert_eq!(&data[8..12], &1u32.to_le_bytes());
+        let update_len = 32 + 10 + 32 + 32 + 4 + 1 + 5;
+        assert_eq!(data[12 + 32 + 10 + 32 + 32 + 4], 1, "EditionMode::Serial");
+        let tail = &data[12 + update_len..];
+        assert_eq!(&tail[..6], &[2, 0, 0, 0, b'b', b'1']);
+    }
+
+    #[test]
+    fn test_decode_merkle_account() {
+        use anchor_lang::AnchorSerialize;
+
+        let account = MerkleAccount {
+            root: [0xcd; 32],
+            authority: Pubkey::new_unique(),
+            bump: 254,
+            created_at: 1698765000,
+            updated_at: 1698765432,
+        };
+        let mut data = vec![0u8; 8];
+        account.serialize(&mut data).unwrap();
+
+        let view = decode_merkle_account(&data).unwrap();
+        assert_eq!(view.root, "cd".repeat(32));
+        assert_eq!(view.authority, account.authority);
+        assert_eq!(view.created_at, 1698765000);
+        assert_eq!(view.updated_at, 1698765432);
+
+        assert!(decode_merkle_account(&data[..4]).is_err());
+        assert!(decode_merkle_account(&data[..20]).is_err());
+    }
+
+    #[test]
+    fn test_decode_anchor_account_schema_version() {
+        use anchor_lang::AnchorSerialize;
+
+        let account = AnchorAccount {
+            schema_version: ACCOUNT_SCHEMA_VERSION,
+            oracle_authority: Pubkey::new_unique(),
+            merkle_root: [0xab; 32],
+            manifest_cid: "bafkmanifest".to_string(),
+            asset_count: 5,
+            timestamp: 1698765432,
+            oracle_signature: Pubkey::new_unique(),
+            version: 2,
+            registry_id: DEFAULT_REGISTRY_ID,
+        };
+        let mut data = vec![0u8; 8];
+        AnchorSerialize::serialize(&account, &mut data).unwrap();
+
+        let decoded = decode_anchor_account(&data).unwrap();
+        assert_eq!(decoded.schema_version, ACCOUNT_SCHEMA_VERSION);
+        assert_eq!(decoded.manifest_cid, "bafkmanifest");
+        assert_eq!(decoded.version, 2);
+
+        data[8] = ACCOUNT_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            decode_anchor_account(&data),
+            Err(BlockchainError::UnsupportedSchemaVersion(v)) if v == ACCOUNT_SCHEMA_VERSION + 1
+        ));
+        assert!(decode_anchor_account(&data[..8]).is_err());
+    }
+
+    /// `EditionRegistryAccount` as serialized by the program, discriminator included
+    fn edition_registry_fixture() -> Vec<u8> {
+        hex::decode(include_str!("../tests/fixtures/edition_registry_v1.hex").trim()).unwrap()
+    }
+
+    #[test]
+    fn test_edition_registry_decodes_program_layout() {
+        let data = edition_registry_fixture();
+        let mut body = &data[8..];
+        let registry: EditionRegistryAccount = AnchorDeserialize::deserialize(&mut body).unwrap();
+
+        // Every byte of the account is consumed by the layout
+        assert!(body.is_empty());
+
+        assert_eq!(registry.schema_version, ACCOUNT_SCHEMA_VERSION);
+        assert_eq!(registry.oracle_authority, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(registry.ipfs_cid, "bafyregistry");
+        assert_eq!(registry.last_batch_id, "batch-7");
+        assert_eq!(registry.last_oracle_signature, Pubkey::new_from_array([3u8; 32]));
+        assert_eq!(registry.version, 4);
+
+        let chain = |name: &str| {
+            let mut id = [0u8; 10];
+            id[..name.len()].copy_from_slice(name.as_bytes());
+            id
+        };
+        assert_eq!(registry.chain_count(chain("ethereum")), 2);
+        assert_eq!(registry.chain_count(chain("solana")), 3);
+        assert_eq!(registry.chain_count(chain("polygon")), 0);
+        assert_eq!(registry.total_editions, 5);
+    }
+
+    #[test]
+    fn test_decode_edition_registry_schema_version() {
+        let mut data = edition_registry_fixture();
+
+        let registry = decode_edition_registry_account(&data).unwrap();
+        assert_eq!(registry.schema_version, ACCOUNT_SCHEMA_VERSION);
+        assert_eq!(registry.version, 4);
+        assert_eq!(registry.chain_counts.iter().filter(|t| t.count > 0).count(), 2);
+
+        data[8] = ACCOUNT_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            decode_edition_registry_account(&data),
+            Err(BlockchainError::UnsupportedSchemaVersion(v)) if v == ACCOUNT_SCHEMA_VERSION + 1
+        ));
+        assert!(decode_edition_registry_account(&data[..8]).is_err());
+
+        // A truncated chain_counts array is an error, not a shorter list
+        data[8] = ACCOUNT_SCHEMA_VERSION;
+        assert!(decode_edition_registry_account(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_legacy_edition_registry() {
+        use anchor_lang::AnchorSerialize;
+
+        // v0 fields as the program wrote them, before schema_version and chain_counts.
+        // The oracle key starts with the current schema byte, so only the size
+        // tells this account apart from a current one.
+        let mut data = edition_registry_fixture()[..8].to_vec();
+        Pubkey::new_from_array([ACCOUNT_SCHEMA_VERSION; 32]).serialize(&mut data).unwrap();
+        [2u8; 32].serialize(&mut data).unwrap();
+        "bafyregistry".serialize(&mut data).unwrap();
+        5u64.serialize(&mut data).unwrap();
+        "batch-7".serialize(&mut data).unwrap();
+        1698765432i64.serialize(&mut data).unwrap();
+        Pubkey::new_from_array([3u8; 32]).serialize(&mut data).unwrap();
+        4u64.serialize(&mut data).unwrap();
+        data.resize(EDITION_REGISTRY_V0_SPACE, 0);
+
+        let err = decode_edition_registry_account(&data).unwrap_err();
+        assert!(matches!(err, BlockchainError::LegacyEditionRegistry));
+        assert!(err.to_string().contains("migrate_edition_registry"));
+
+        // The current layout, padded to its allocated size, still decodes
+        let mut current = edition_registry_fixture();
+        current.resize(EDITION_REGISTRY_V0_SPACE + 1 + 18 * MAX_TRACKED_CHAINS, 0);
+        assert_eq!(decode_edition_registry_account(&current).unwrap().version, 4);
+    }
+
+    /// Devnet wallet from the Solana CLI's default keypair path, if there is one
+    fn devnet_keypair() -> Option<Keypair> {
+        let home = std::env::var("HOME").ok()?;
+        let path = format!("{}/.config/solana/id.json", home);
+        anchor_client::solana_sdk::signature::read_keypair_file(path).ok()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a funded devnet wallet and deployed program"]
+    async fn test_merkle_account_read_back() {
+        let payer = devnet_keypair().expect("no keypair at ~/.config/solana/id.json");
+        let client = ProTraceClient::new_devnet(payer).unwrap();
+
+        // Initialization fails harmlessly if the singleton already exists
+        let _ = client.initialize_merkle_root([0x01; 32]).await;
+        let before = client.get_merkle_account().await.unwrap();
+
+        let new_root = [0x5a; 32];
+        client.update_merkle_root(new_root).await.unwrap();
+
+        let after = client.get_merkle_account().await.unwrap();
+        assert_eq!(after.root, hex::encode(new_root));
+        assert_eq!(after.authority, client.payer_pubkey());
+        assert!(after.updated_at >= before.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_register_chunks_resumes_after_failure() {
+        let updates = edition_updates(8);
+        let chunks: Vec<&[EditionUpdate]> = updates.chunks(2).collect();
+        assert_eq!(chunks.len(), 4);
+
+        // Mock RPC: chunk 2 fails once, then succeeds on retry
+        let mut submitted = Vec::new();
+        let mut chunk_2_failed = false;
+        let mut mock_rpc = |i: usize, chunk: &[EditionUpdate]| {
+            submitted.push((i, chunk.len()));
+            let result = if i == 2 && !chunk_2_failed {
+                chunk_2_failed = true;
+                Err(BlockchainError::TransactionFailed("blockhash expired".into()).into())
+            } else {
+                Ok(Signature::new_unique())
+            };
+            async move { result }
+        };
+
+        let first = register_chunks(&chunks, 0, &mut mock_rpc).await;
+        assert_eq!(first.completed_chunks, 2);
+        assert_eq!(first.signatures.len(), 2);
+        assert_eq!(first.failed_at, Some(2));
+
+        let resumed = register_chunks(&chunks, first.failed_at.unwrap(), &mut mock_rpc).await;
+        assert_eq!(resumed.completed_chunks, 4);
+        assert_eq!(resumed.signatures.len(), 2);
+        assert_eq!(resumed.failed_at, None);
+
+        // Committed chunks were not resubmitted
+        let indices: Vec<usize> = submitted.iter().map(|&(i, _)| i).collect();
+        assert_eq!(indices, [0, 1, 2, 2, 3]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a funded devnet wallet and deployed program"]
+    async fn test_batch_register_editions_pipelined() {
+        let payer = devnet_keypair().expect("no keypair at ~/.config/solana/id.json");
+        let client = ProTraceClient::new_devnet(payer).unwrap();
+
+        // Initialization fails harmlessly if the registry already exists
+        let _ = client.initialize_edition_registry(client.payer_pubkey()).await;
+
+        let updates = edition_updates(9);
+        let chunks: Vec<EditionBatch> = updates
+            .chunks(3)
+            .enumerate()
+            .map(|(i, chunk)| EditionBatch {
+                edition_updates: chunk.to_vec(),
+                batch_id: format!("pipelined-{}", i),
+                new_merkle_root: [0x7e; 32],
+                ipfs_cid: "bafkreipipelined".to_string(),
+            })
+            .collect();
+
+        let results = client
+            .batch_register_editions_pipelined(chunks)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        for result in &results {
+            assert!(result.is_ok(), "chunk failed: {:?}", result);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a funded devnet wallet and deployed program"]
+    async fn test_update_merkle_root_if_changed() {
+        let payer = devnet_keypair().expect("no keypair at ~/.config/solana/id.json");
+        let client = ProTraceClient::new_devnet(payer).unwrap();
+
+        // Initialization fails harmlessly if the singleton already exists
+        let _ = client.initialize_merkle_root([0x01; 32]).await;
+
+        // Make sure the first update has something to change
+        let current = client.get_merkle_account().await.unwrap();
+        let new_root = if current.root == hex::encode([0x6b; 32]) {
+            [0x6c; 32]
+        } else {
+            [0x6b; 32]
+        };
+
+        let first = client.update_merkle_root_if_changed(new_root).await.unwrap();
+        assert!(first.is_some());
+
+        // Same root again is a no-op
+        let second = client.update_merkle_root_if_changed(new_root).await.unwrap();
+        assert!(second.is_none());
+    }
+
+    /// Answer `responses.len()` HTTP requests in order, returning their request lines
+    fn mock_ipfs(responses: Vec<String>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let mut request_lines = Vec::new();
+            for body in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                // Drain headers and body
+                let mut content_length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    line.clear();
+                }
+                reader.read_exact(&mut vec![0u8; content_length]).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                request_lines.push(request_line);
+            }
+            request_lines
+        });
+
+        (addr, handle)
+    }
+
+    fn two_leaf_manifest() -> Manifest {
+        let mut tree = protrace_merkle_tree::MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.build_tree().unwrap();
+        tree.export_manifest().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_anchor_anchors_uploaded_cid() {
+        let (api, handle) = mock_ipfs(vec![
+            r#"{"Name":"manifest.json","Hash":"bafkmanifest","Size":"42"}"#.into(),
+        ]);
+        let signature = Signature::new_unique();
+
+        let mut anchored_cid = None;
+        let (cid, sig) = publish_and_anchor(&two_leaf_manifest(), &api, |cid| {
+            anchored_cid = Some(cid);
+            async move { Ok(signature) }
+        })
+        .await
+        .unwrap();
+
+        let request_lines = handle.join().unwrap();
+        assert!(request_lines[0].starts_with("POST /api/v0/add?"));
+        assert_eq!(cid, "bafkmanifest");
+        assert_eq!(anchored_cid.as_deref(), Some("bafkmanifest"));
+        assert_eq!(sig, signature);
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_anchor_unpins_on_failure() {
+        let (api, handle) = mock_ipfs(vec![
+            r#"{"Hash":"bafkmanifest"}"#.into(),
+            r#"{"Pins":["bafkmanifest"]}"#.into(),
+        ]);
+
+        let err = publish_and_anchor(&two_leaf_manifest(), &api, |_| async {
+            Err(BlockchainError::TransactionFailed("simulated".into()).into())
+        })
+        .await
+        .unwrap_err();
+
+        let request_lines = handle.join().unwrap();
+        assert!(err.to_string().contains("simulated"));
+        assert!(request_lines[1].starts_with("POST /api/v0/pin/rm?arg=bafkmanifest "));
+    }
+
+    #[test]
+    fn test_manifest_to_anchor_params_fixed_timestamp() {
+        let mut tree = protrace_merkle_tree::MerkleTree::new();
+        for i in 0..3 {
+            let (dna, pointer) = (format!("dna{}", i), format!("ptr{}", i));
+            tree.add_leaf(&dna, &pointer, "platform", Some(1000 + i));
+        }
+        tree.build_tree().unwrap();
+        let manifest = tree.export_manifest().unwrap();
+
+        let first = manifest_to_anchor_params(&manifest, Some(1698765432));
+        let second = manifest_to_anchor_params(&manifest, Some(1698765432));
+        assert_eq!(first, second);
+        assert_eq!(first.0.to_vec(), hex::decode(&manifest.root).unwrap());
+        assert_eq!(first.3, 1698765432);
+    }
+
+    #[test]
+    fn test_anchor_account_per_registry() {
+        let program_id = Pubkey::from_str(PROTRACE_PROGRAM_ID).unwrap();
+
+        let oracle = Pubkey::new_unique();
+
+        let default = anchor_account_address(&program_id, &oracle, &DEFAULT_REGISTRY_ID);
+        let other = anchor_account_address(&program_id, &oracle, &[0x42; 16]);
+        assert_ne!(default, other);
+        assert_eq!(default, anchor_account_address(&program_id, &oracle, &DEFAULT_REGISTRY_ID));
+
+        // Another oracle can't reach this oracle's registry
+        let squatter = Pubkey::new_unique();
+        assert_ne!(default, anchor_account_address(&program_id, &squatter, &DEFAULT_REGISTRY_ID));
+    }
+
+    fn edition_updates(count: u32) -> Vec<EditionUpdate> {
+        (0..count)
+            .map(|i| {
+                EditionUpdate::new(
+                    [i as u8; 32],
+                    "solana",
+                    [0u8; 32],
+                    &i.to_string(),
+                    i,
+                    EditionMode::Serial,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_edition_update_string_fields() {
+        let update = EditionUpdate::new(
+            [0u8; 32],
+            "ethereum",
+            [0u8; 32],
+            "12345",
+            1,
+            EditionMode::Serial,
+            None,
+        );
+        assert_eq!(update.chain_str(), "ethereum");
+        assert_eq!(update.token_id_str(), "12345");
+
+        // Values longer than the field are truncated by `new`
+        let update = EditionUpdate::new(
+            [0u8; 32],
+            "arbitrum-nova",
+            [0u8; 32],
+            "",
+            1,
+            EditionMode::Serial,
+            None,
+        );
+        assert_eq!(update.chain_str(), "arbitrum-n");
+        assert_eq!(update.token_id_str(), "");
+    }
+
+    #[test]
+    fn test_verify_edition_in_root() {
+        let updates = edition_updates(5);
+        let mut tree = protrace_merkle_tree::MerkleTree::new();
+        for update in &updates {
+            tree.add_edition_leaf(
+                &hex::encode(update.dna_hash),
+                &update.chain_str(),
+                &hex::encode(update.contract),
+                &update.token_id_str(),
+                update.edition_no,
+            );
+        }
+        let mut root = [0u8; 32];
+        hex::decode_to_slice(tree.build_tree().unwrap(), &mut root).unwrap();
+
+        let proof = tree.get_proof(3).unwrap();
+        assert!(verify_edition_in_root(&updates[3], &proof, root));
+        assert!(!verify_edition_in_root(&updates[2], &proof, root));
+
+        let mut tampered = updates[3].clone();
+        tampered.edition_no += 1;
+        assert!(!verify_edition_in_root(&tampered, &proof, root));
+    }
+
+    #[test]
+    fn test_estimate_batch_cost_is_linear() {
+        let one = estimate_batch_cost(&edition_updates(1));
+        let two = estimate_batch_cost(&edition_updates(2));
+        let ten = estimate_batch_cost(&edition_updates(10));
+
+        assert_eq!(estimate_batch_cost(&[]), BATCH_BASE_COMPUTE_UNITS);
+        assert_eq!(two - one, EDITION_COMPUTE_UNITS);
+        assert_eq!(ten - one, 9 * EDITION_COMPUTE_UNITS);
+    }
+
+    #[test]
+    fn test_over_budget_batch_is_split() {
+        let updates = edition_updates(60);
+        assert!(estimate_batch_cost(&updates) > MAX_TRANSACTION_COMPUTE_UNITS);
+
+        let batches = chunk_by_compute_budget(&updates, MAX_TRANSACTION_COMPUTE_UNITS);
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|batch| batch.len()).sum::<usize>(), 60);
+        for batch in &batches {
+            assert!(estimate_batch_cost(batch) <= MAX_TRANSACTION_COMPUTE_UNITS);
+            assert!(batch.len() <= MAX_BATCH_EDITIONS);
+        }
+
+        // A tighter budget yields smaller batches
+        let small = chunk_by_compute_budget(&updates, 200_000);
+        assert!(small.iter().all(|batch| estimate_batch_cost(batch) <= 200_000));
+    }
 }
@@ -1,10 +1,12 @@
 //! Type definitions for blockchain operations
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
 use serde::{Deserialize, Serialize};
 
 /// Edition mode enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub enum EditionMode {
     Strict1To1,
     Serial,
@@ -12,7 +14,7 @@ pub enum EditionMode {
 }
 
 /// Edition update structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, AnchorSerialize, AnchorDeserialize)]
 pub struct EditionUpdate {
     pub dna_hash: [u8; 32],
     pub chain: [u8; 10],
@@ -23,8 +25,35 @@ pub struct EditionUpdate {
     pub max_editions: Option<u32>,
 }
 
-/// Instruction data enum for Anchor program calls
+/// One `batch_register_editions` call's worth of arguments
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditionBatch {
+    pub edition_updates: Vec<EditionUpdate>,
+    pub batch_id: String,
+    pub new_merkle_root: [u8; 32],
+    pub ipfs_cid: String,
+}
+
+/// Outcome of a chunked edition registration
+///
+/// Chunks before `completed_chunks` are committed on-chain. If `failed_at`
+/// is set, pass it as `start_chunk` to `ProTraceClient::resume_from` with
+/// the same updates to continue where the run stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchProgress {
+    /// Chunks committed so far, counting any skipped by a resume
+    pub completed_chunks: usize,
+    /// Signatures of the chunks committed during this run, in order
+    pub signatures: Vec<Signature>,
+    /// Index of the chunk that failed, if the run stopped early
+    pub failed_at: Option<usize>,
+}
+
+/// Instruction data enum for Anchor program calls
+///
+/// Each variant serializes as the program instruction it names: the
+/// 8-byte Anchor sighash followed by the borsh-encoded arguments.
+#[derive(Debug, Clone, Serialize, Deserialize, AnchorSerialize)]
 pub enum InstructionData {
     InitializeMerkleRoot {
         root: [u8; 32],
@@ -33,14 +62,24 @@ pub enum InstructionData {
         new_root: [u8; 32],
     },
     AnchorMerkleRootOracle {
+        registry_id: [u8; 16],
+        merkle_root: [u8; 32],
+        manifest_cid: String,
+        asset_count: u64,
+        timestamp: i64,
+    },
+    AnchorMerkleRootRelayed {
+        registry_id: [u8; 16],
         merkle_root: [u8; 32],
         manifest_cid: String,
         asset_count: u64,
         timestamp: i64,
+        oracle: Pubkey,
     },
     InitializeEditionRegistry {
         oracle_authority: Pubkey,
     },
+    MigrateEditionRegistry,
     BatchRegisterEditions {
         edition_updates: Vec<EditionUpdate>,
         batch_id: String,
@@ -49,9 +88,51 @@ pub enum InstructionData {
     },
 }
 
+impl InstructionData {
+    /// Program instruction name, as hashed into the Anchor sighash
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstructionData::InitializeMerkleRoot { .. } => "initialize_merkle_root",
+            InstructionData::UpdateMerkleRoot { .. } => "update_merkle_root",
+            InstructionData::AnchorMerkleRootOracle { .. } => "anchor_merkle_root_oracle",
+            InstructionData::AnchorMerkleRootRelayed { .. } => "anchor_merkle_root_relayed",
+            InstructionData::InitializeEditionRegistry { .. } => "initialize_edition_registry",
+            InstructionData::MigrateEditionRegistry => "migrate_edition_registry",
+            InstructionData::BatchRegisterEditions { .. } => "batch_register_editions",
+        }
+    }
+
+    /// First 8 bytes of `sha256("global:<name>")`
+    pub fn sighash(&self) -> [u8; 8] {
+        let preimage = format!("global:{}", self.name());
+        let hash = anchor_lang::solana_program::hash::hash(preimage.as_bytes());
+        let mut sighash = [0u8; 8];
+        sighash.copy_from_slice(&hash.to_bytes()[..8]);
+        sighash
+    }
+}
+
+/// The discriminator differs per variant, so [`anchor_lang::InstructionData::data`]
+/// is overridden below and this constant is never written.
+impl anchor_lang::Discriminator for InstructionData {
+    const DISCRIMINATOR: [u8; 8] = [0; 8];
+}
+
+impl anchor_lang::InstructionData for InstructionData {
+    fn data(&self) -> Vec<u8> {
+        let encoded = self.try_to_vec().expect("Should always serialize");
+        // Borsh leads with the variant index; Anchor wants the sighash there
+        let mut data = self.sighash().to_vec();
+        data.extend_from_slice(&encoded[1..]);
+        data
+    }
+}
+
 /// Account data for Merkle anchor
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, AnchorSerialize, AnchorDeserialize)]
 pub struct AnchorAccount {
+    /// Layout version; see [`crate::ACCOUNT_SCHEMA_VERSION`]
+    pub schema_version: u8,
     pub oracle_authority: Pubkey,
     pub merkle_root: [u8; 32],
     pub manifest_cid: String,
@@ -59,11 +140,53 @@ pub struct AnchorAccount {
     pub timestamp: i64,
     pub oracle_signature: Pubkey,
     pub version: u64,
+    pub registry_id: [u8; 16],
+}
+
+/// Account data for the authority-managed Merkle root
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MerkleAccount {
+    pub root: [u8; 32],
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Read-side view of a [`MerkleAccount`] with the root hex-encoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MerkleAccountView {
+    pub root: String,
+    pub authority: Pubkey,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<MerkleAccount> for MerkleAccountView {
+    fn from(account: MerkleAccount) -> Self {
+        Self {
+            root: hex::encode(account.root),
+            authority: account.authority,
+            created_at: account.created_at,
+            updated_at: account.updated_at,
+        }
+    }
+}
+
+/// Editions registered on one chain (program's `ChainTotal`)
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, AnchorSerialize, AnchorDeserialize,
+)]
+pub struct ChainTotal {
+    pub chain: [u8; 10],
+    pub count: u64,
 }
 
 /// Account data for edition registry
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, AnchorSerialize, AnchorDeserialize)]
 pub struct EditionRegistryAccount {
+    /// Layout version; see [`crate::ACCOUNT_SCHEMA_VERSION`]
+    pub schema_version: u8,
     pub oracle_authority: Pubkey,
     pub merkle_root: [u8; 32],
     pub ipfs_cid: String,
@@ -72,6 +195,18 @@ pub struct EditionRegistryAccount {
     pub last_batch_timestamp: i64,
     pub last_oracle_signature: Pubkey,
     pub version: u64,
+    /// Per-chain edition totals; count 0 marks an unused slot
+    pub chain_counts: [ChainTotal; crate::MAX_TRACKED_CHAINS],
+}
+
+impl EditionRegistryAccount {
+    /// Editions registered on `chain`, or 0 if it isn't tracked
+    pub fn chain_count(&self, chain: [u8; 10]) -> u64 {
+        self.chain_counts
+            .iter()
+            .find(|t| t.count > 0 && t.chain == chain)
+            .map_or(0, |t| t.count)
+    }
 }
 
 impl EditionUpdate {
@@ -105,4 +240,31 @@ impl EditionUpdate {
             max_editions,
         }
     }
+
+    /// Chain name with null padding removed
+    pub fn chain_str(&self) -> String {
+        unpad(&self.chain)
+    }
+
+    /// Token ID with null padding removed
+    pub fn token_id_str(&self) -> String {
+        unpad(&self.token_id)
+    }
+
+    /// Leaf bytes committing this edition in an edition Merkle tree
+    pub fn leaf_bytes(&self) -> Vec<u8> {
+        protrace_merkle_tree::leaf_encoding::encode_edition_leaf(
+            &hex::encode(self.dna_hash),
+            &self.chain_str(),
+            &hex::encode(self.contract),
+            &self.token_id_str(),
+            self.edition_no,
+        )
+    }
+}
+
+/// Decode a null-padded fixed-size field, replacing invalid UTF-8
+fn unpad(bytes: &[u8]) -> String {
+    let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
 }
@@ -2,7 +2,10 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use protrace_blockchain::{manifest_to_anchor_params, ProTraceClient};
+use protrace_blockchain::{
+    manifest_to_anchor_params, AnchorAccount, ProTraceClient, DEFAULT_REGISTRY_ID,
+};
+use protrace_merkle_tree::Manifest;
 use protrace_wallet::WalletManager;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
@@ -13,14 +16,22 @@ use std::str::FromStr;
 pub async fn handle_blockchain_command(
     action: crate::BlockchainCommands,
     wallet_path: &str,
+    json: bool,
 ) -> Result<()> {
     match action {
         crate::BlockchainCommands::InitRoot { root } => init_merkle_root(wallet_path, root).await,
         crate::BlockchainCommands::UpdateRoot { root } => {
             update_merkle_root(wallet_path, root).await
         }
-        crate::BlockchainCommands::Anchor { manifest } => {
-            anchor_merkle_root(wallet_path, manifest).await
+        crate::BlockchainCommands::Anchor { manifest, timestamp } => {
+            anchor_merkle_root(wallet_path, manifest, timestamp).await
+        }
+        crate::BlockchainCommands::AnchorRoot { root, asset_count } => {
+            anchor_raw_root(wallet_path, root, asset_count).await
+        }
+        crate::BlockchainCommands::Status => show_anchor_status(wallet_path, json).await,
+        crate::BlockchainCommands::Audit { cid, gateway } => {
+            audit_manifest(wallet_path, cid, gateway, json).await
         }
         crate::BlockchainCommands::InitRegistry { oracle } => {
             init_edition_registry(wallet_path, oracle).await
@@ -28,21 +39,27 @@ pub async fn handle_blockchain_command(
     }
 }
 
+/// Parse a hex-encoded 32-byte root hash
+fn parse_root_hex(root: &str) -> Result<[u8; 32]> {
+    let root_bytes = hex::decode(root).context("Invalid root hash format")?;
+    if root_bytes.len() != 32 {
+        anyhow::bail!("Root hash must be 32 bytes");
+    }
+    let mut root_array = [0u8; 32];
+    root_array.copy_from_slice(&root_bytes);
+    Ok(root_array)
+}
+
 async fn init_merkle_root(wallet_path: &str, root: String) -> Result<()> {
     println!("{}", "Initializing Merkle root on blockchain...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path).context("Failed to load wallet")?;
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
         .context("Failed to create blockchain client")?;
 
     // Parse root hash
-    let root_bytes = hex::decode(&root).context("Invalid root hash format")?;
-    if root_bytes.len() != 32 {
-        anyhow::bail!("Root hash must be 32 bytes");
-    }
-    let mut root_array = [0u8; 32];
-    root_array.copy_from_slice(&root_bytes);
+    let root_array = parse_root_hex(&root)?;
 
     let signature = client
         .initialize_merkle_root(root_array)
@@ -67,18 +84,13 @@ async fn init_merkle_root(wallet_path: &str, root: String) -> Result<()> {
 async fn update_merkle_root(wallet_path: &str, root: String) -> Result<()> {
     println!("{}", "Updating Merkle root on blockchain...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path).context("Failed to load wallet")?;
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
         .context("Failed to create blockchain client")?;
 
     // Parse root hash
-    let root_bytes = hex::decode(&root).context("Invalid root hash format")?;
-    if root_bytes.len() != 32 {
-        anyhow::bail!("Root hash must be 32 bytes");
-    }
-    let mut root_array = [0u8; 32];
-    root_array.copy_from_slice(&root_bytes);
+    let root_array = parse_root_hex(&root)?;
 
     let signature = client
         .update_merkle_root(root_array)
@@ -100,17 +112,21 @@ async fn update_merkle_root(wallet_path: &str, root: String) -> Result<()> {
     Ok(())
 }
 
-async fn anchor_merkle_root(wallet_path: &str, manifest: PathBuf) -> Result<()> {
+async fn anchor_merkle_root(
+    wallet_path: &str,
+    manifest: PathBuf,
+    timestamp: Option<i64>,
+) -> Result<()> {
     println!("{}", "Anchoring Merkle root via oracle...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path).context("Failed to load wallet")?;
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
         .context("Failed to create blockchain client")?;
 
     // Load manifest
     let manifest_data = fs::read_to_string(&manifest).context("Failed to read manifest")?;
-    let manifest: protrace_merkle_tree::Manifest =
+    let manifest: Manifest =
         serde_json::from_str(&manifest_data).context("Failed to parse manifest")?;
 
     println!("  📊 Manifest loaded:");
@@ -118,10 +134,16 @@ async fn anchor_merkle_root(wallet_path: &str, manifest: PathBuf) -> Result<()>
     println!("    Root: {}", manifest.root.bright_white());
 
     // Convert manifest to anchor params
-    let (root, _cid, asset_count, timestamp) = manifest_to_anchor_params(&manifest);
+    let (root, _cid, asset_count, timestamp) = manifest_to_anchor_params(&manifest, timestamp);
 
     let signature = client
-        .anchor_merkle_root_oracle(root, manifest.root.clone(), asset_count, timestamp)
+        .anchor_merkle_root_oracle(
+            DEFAULT_REGISTRY_ID,
+            root,
+            manifest.root.clone(),
+            asset_count,
+            timestamp,
+        )
         .await
         .context("Failed to anchor Merkle root")?;
 
@@ -142,10 +164,165 @@ async fn anchor_merkle_root(wallet_path: &str, manifest: PathBuf) -> Result<()>
     Ok(())
 }
 
+async fn anchor_raw_root(wallet_path: &str, root: String, asset_count: u64) -> Result<()> {
+    println!("{}", "Anchoring raw Merkle root via oracle...".yellow());
+
+    // Validate before touching the wallet or network
+    let root_array = parse_root_hex(&root)?;
+
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
+
+    let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
+        .context("Failed to create blockchain client")?;
+
+    let timestamp = chrono::Utc::now().timestamp();
+
+    let signature = client
+        .anchor_merkle_root_oracle(
+            DEFAULT_REGISTRY_ID,
+            root_array,
+            String::new(),
+            asset_count,
+            timestamp,
+        )
+        .await
+        .context("Failed to anchor Merkle root")?;
+
+    println!();
+    println!("{}", "✅ Merkle Root Anchored".bright_green().bold());
+    println!("  🔐 Root: {}", root.bright_white());
+    println!("  📦 Assets: {}", asset_count);
+    println!("  📝 Transaction: {}", signature);
+    println!(
+        "  🔗 Explorer: {}",
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster=devnet",
+            signature
+        )
+        .bright_blue()
+    );
+
+    Ok(())
+}
+
+async fn show_anchor_status(wallet_path: &str, json: bool) -> Result<()> {
+    if !json {
+        println!("{}", "Fetching anchor account...".yellow());
+    }
+
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
+
+    let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
+        .context("Failed to create blockchain client")?;
+
+    let account = client
+        .get_anchor_account(&client.payer_pubkey(), DEFAULT_REGISTRY_ID)
+        .await
+        .context("Failed to fetch anchor account")?;
+
+    println!("{}", format_anchor_status(&account, json)?);
+
+    Ok(())
+}
+
+/// Render an anchor account for `blockchain status`
+fn format_anchor_status(account: &AnchorAccount, json: bool) -> Result<String> {
+    if json {
+        let value = serde_json::json!({
+            "oracle_authority": account.oracle_authority.to_string(),
+            "merkle_root": hex::encode(account.merkle_root),
+            "manifest_cid": account.manifest_cid,
+            "asset_count": account.asset_count,
+            "timestamp": account.timestamp,
+            "version": account.version,
+            "registry_id": hex::encode(account.registry_id),
+        });
+        return serde_json::to_string_pretty(&value).context("Failed to serialize status");
+    }
+
+    Ok(format!(
+        "{}\n  🗂️ Registry: {}\n  👤 Oracle: {}\n  🔐 Root: {}\n  📁 Manifest CID: {}\n  📦 Assets: {}\n  🕒 Timestamp: {}\n  🔢 Version: {}",
+        "📋 Anchor Account".bright_cyan().bold(),
+        hex::encode(account.registry_id),
+        account.oracle_authority,
+        hex::encode(account.merkle_root).bright_white(),
+        account.manifest_cid,
+        account.asset_count,
+        account.timestamp,
+        account.version,
+    ))
+}
+
+async fn audit_manifest(wallet_path: &str, cid: String, gateway: String, json: bool) -> Result<()> {
+    if !json {
+        println!("{}", "Auditing manifest against on-chain root...".yellow());
+    }
+
+    let manifest = fetch_manifest(&gateway, &cid).await?;
+
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
+
+    let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
+        .context("Failed to create blockchain client")?;
+
+    let account = client
+        .get_anchor_account(&client.payer_pubkey(), DEFAULT_REGISTRY_ID)
+        .await
+        .context("Failed to fetch anchor account")?;
+
+    let onchain_root = hex::encode(account.merkle_root);
+    let matches = manifest.root == onchain_root;
+
+    if json {
+        let value = serde_json::json!({
+            "cid": cid,
+            "manifest_root": manifest.root,
+            "onchain_root": onchain_root,
+            "matches": matches,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).context("Failed to serialize audit")?
+        );
+    } else {
+        println!("  📁 CID: {}", cid);
+        println!("  📊 Manifest root: {}", manifest.root.bright_white());
+        println!("  ⛓️  On-chain root: {}", onchain_root.bright_white());
+        println!();
+        if matches {
+            println!("{}", "✅ Manifest matches anchored root".bright_green().bold());
+        } else {
+            println!("{}", "❌ Manifest does not match anchored root".bright_red().bold());
+        }
+    }
+
+    if !matches {
+        anyhow::bail!("Manifest root does not match on-chain root");
+    }
+
+    Ok(())
+}
+
+/// Fetch and parse a manifest from `<gateway>/ipfs/<cid>`
+async fn fetch_manifest(gateway: &str, cid: &str) -> Result<Manifest> {
+    let url = format!("{}/ipfs/{}", gateway.trim_end_matches('/'), cid);
+
+    let body = reqwest::get(&url)
+        .await
+        .with_context(|| format!("Failed to fetch {}", url))?
+        .error_for_status()
+        .with_context(|| format!("Gateway returned an error for {}", url))?
+        .text()
+        .await
+        .context("Failed to read manifest body")?;
+
+    serde_json::from_str(&body).context("Failed to parse manifest")
+}
+
 async fn init_edition_registry(wallet_path: &str, oracle: Option<String>) -> Result<()> {
     println!("{}", "Initializing edition registry...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path).context("Failed to load wallet")?;
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
         .context("Failed to create blockchain client")?;
@@ -175,3 +352,149 @@ async fn init_edition_registry(wallet_path: &str, oracle: Option<String>) -> Res
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_root_hex() {
+        let root = "ab".repeat(32);
+        assert_eq!(parse_root_hex(&root).unwrap(), [0xab; 32]);
+
+        assert!(parse_root_hex("abcd").is_err());
+        assert!(parse_root_hex(&"zz".repeat(32)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_anchor_raw_root_validation() {
+        // Invalid root is rejected before the wallet is loaded
+        let err = anchor_raw_root("/nonexistent/id.json", "abcd".to_string(), 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("32 bytes"));
+
+        // Valid root passes validation and fails only on the missing wallet
+        let err = anchor_raw_root("/nonexistent/id.json", "ab".repeat(32), 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to load wallet"));
+    }
+
+    #[test]
+    fn test_format_anchor_status_json() {
+        let account = AnchorAccount {
+            schema_version: protrace_blockchain::ACCOUNT_SCHEMA_VERSION,
+            oracle_authority: Pubkey::new_unique(),
+            merkle_root: [0xab; 32],
+            manifest_cid: String::new(),
+            asset_count: 5,
+            timestamp: 1698765432,
+            oracle_signature: Pubkey::new_unique(),
+            version: 3,
+            registry_id: DEFAULT_REGISTRY_ID,
+        };
+
+        let out = format_anchor_status(&account, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["merkle_root"], "ab".repeat(32));
+        assert_eq!(value["asset_count"], 5);
+        assert_eq!(value["version"], 3);
+        assert_eq!(value["registry_id"], "00".repeat(16));
+    }
+
+    /// Serve a single HTTP response and return the request line
+    fn serve_once(body: String) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            // Drain headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request_line
+        });
+
+        (addr, handle)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_manifest_from_gateway() {
+        let mut tree = protrace_merkle_tree::MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.build_tree().unwrap();
+        let manifest = tree.export_manifest().unwrap();
+
+        let (gateway, handle) = serve_once(serde_json::to_string(&manifest).unwrap());
+
+        let fetched = fetch_manifest(&format!("{}/", gateway), "QmTestCid")
+            .await
+            .unwrap();
+        let request_line = handle.join().unwrap();
+
+        assert!(request_line.starts_with("GET /ipfs/QmTestCid "));
+        assert_eq!(fetched.root, manifest.root);
+        assert_eq!(fetched.total_leaves, 2);
+        assert_eq!(parse_root_hex(&fetched.root).unwrap().len(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_manifest_rejects_invalid_json() {
+        let (gateway, handle) = serve_once("not a manifest".to_string());
+
+        let err = fetch_manifest(&gateway, "QmTestCid").await.unwrap_err();
+        handle.join().unwrap();
+        assert!(err.to_string().contains("Failed to parse manifest"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a funded devnet wallet and deployed program"]
+    async fn test_status_reports_last_anchored_version() {
+        let wallet = WalletManager::from_file(protrace_wallet::get_default_keypair_path()).unwrap();
+        let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone()).unwrap();
+
+        let before = client
+            .get_anchor_account(&client.payer_pubkey(), DEFAULT_REGISTRY_ID)
+            .await
+            .map(|a| a.version)
+            .unwrap_or(0);
+        client
+            .anchor_merkle_root_oracle(
+                DEFAULT_REGISTRY_ID,
+                [0x11; 32],
+                String::new(),
+                1,
+                chrono::Utc::now().timestamp(),
+            )
+            .await
+            .unwrap();
+
+        let account = client
+            .get_anchor_account(&client.payer_pubkey(), DEFAULT_REGISTRY_ID)
+            .await
+            .unwrap();
+        assert_eq!(account.version, before + 1);
+
+        let out = format_anchor_status(&account, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["version"], before + 1);
+    }
+}
@@ -2,14 +2,28 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use protrace_image_dna::{compute_dna, hamming_distance, is_duplicate};
-use std::path::PathBuf;
+use protrace_image_dna::{
+    cluster_by_threshold, compare_dna, compute_dna, extract_dna_features,
+    extract_dna_features_batch, hamming_distance, DnaResult,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-pub async fn handle_dna_command(action: crate::DnaCommands) -> Result<()> {
+pub async fn handle_dna_command(action: crate::DnaCommands, json: bool) -> Result<()> {
     match action {
         crate::DnaCommands::Compute { image } => compute_dna_hash(image).await,
         crate::DnaCommands::Compare { image1, image2 } => compare_images(image1, image2).await,
         crate::DnaCommands::Batch { images } => batch_compute_dna(images).await,
+        crate::DnaCommands::VerifyHash { hex } => verify_dna_hash(&hex),
+        crate::DnaCommands::Histogram { images } => dna_histogram(images, json),
+        crate::DnaCommands::Report {
+            dir,
+            threshold,
+            output,
+        } => dna_report(dir, threshold, output),
+        crate::DnaCommands::Bench { count, size } => dna_bench(count, size, json),
     }
 }
 
@@ -33,27 +47,56 @@ async fn compute_dna_hash(image: PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Validate a 256-bit DNA hash and split it into (dHash, grid) components
+fn parse_dna_hash(hex: &str) -> Result<(&str, &str)> {
+    if hex.len() != 64 {
+        anyhow::bail!("DNA hash must be 64 hex chars, got {}", hex.len());
+    }
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!("DNA hash contains non-hex characters");
+    }
+    Ok(hex.split_at(16))
+}
+
+fn verify_dna_hash(hex: &str) -> Result<()> {
+    let hex = hex.trim().to_lowercase();
+    let (dhash, grid_hash) = parse_dna_hash(&hex)?;
+
+    // Same signature as extract_dna_features
+    let signature = blake3::hash(hex.as_bytes()).to_hex();
+
+    println!("{}", "🧬 DNA Hash".bright_cyan().bold());
+    println!("  {} Well-formed (256-bit)", "✓".bright_green());
+    println!();
+    println!("  Component Hashes:");
+    println!("    dHash (64-bit): {}", dhash.bright_yellow());
+    println!("    Grid (192-bit): {}", grid_hash.bright_blue());
+    println!();
+    println!("  🔐 BLAKE3 Signature:");
+    println!("    {}", signature.as_str().bright_white());
+
+    Ok(())
+}
+
 async fn compare_images(image1: PathBuf, image2: PathBuf) -> Result<()> {
     println!("{}", "Comparing images...".yellow());
 
     let dna1 = compute_dna(&image1).context("Failed to compute DNA for image 1")?;
     let dna2 = compute_dna(&image2).context("Failed to compute DNA for image 2")?;
 
-    let distance = hamming_distance(&dna1.dna_hex, &dna2.dna_hex)
+    let comparison = compare_dna(&dna1.dna_hex, &dna2.dna_hex, 26)
         .context("Failed to calculate distance")?;
-    let similarity = 1.0 - (distance as f64 / 256.0);
-    let duplicate = is_duplicate(&dna1.dna_hex, &dna2.dna_hex, 26)?;
 
     println!("{}", "🔍 Image Comparison".bright_cyan().bold());
     println!("  📁 Image 1: {}", image1.display());
     println!("  📁 Image 2: {}", image2.display());
     println!();
     println!("  📊 Analysis:");
-    println!("    Hamming Distance: {}", distance);
-    println!("    Similarity: {:.2}%", similarity * 100.0);
+    println!("    Hamming Distance: {}", comparison.distance);
+    println!("    Similarity: {:.2}%", comparison.similarity * 100.0);
     println!(
         "    Duplicate: {}",
-        if duplicate {
+        if comparison.is_duplicate {
             "YES ⚠️".bright_red().bold()
         } else {
             "NO ✓".bright_green()
@@ -105,9 +148,9 @@ async fn batch_compute_dna(images: Vec<PathBuf>) -> Result<()> {
             let (img1, dna1) = &results[i];
             let (img2, dna2) = &results[j];
 
-            if is_duplicate(&dna1.dna_hex, &dna2.dna_hex, 26)? {
+            let comparison = compare_dna(&dna1.dna_hex, &dna2.dna_hex, 26)?;
+            if comparison.is_duplicate {
                 found_duplicates = true;
-                let distance = hamming_distance(&dna1.dna_hex, &dna2.dna_hex)?;
                 println!(
                     "  {} ⚠️",
                     "DUPLICATE FOUND".bright_red().bold()
@@ -116,7 +159,7 @@ async fn batch_compute_dna(images: Vec<PathBuf>) -> Result<()> {
                     img1.file_name().unwrap().to_string_lossy(),
                     img2.file_name().unwrap().to_string_lossy()
                 );
-                println!("    Distance: {}", distance);
+                println!("    Distance: {}", comparison.distance);
             }
         }
     }
@@ -127,3 +170,371 @@ async fn batch_compute_dna(images: Vec<PathBuf>) -> Result<()> {
 
     Ok(())
 }
+
+/// Fraction of hashes with each of the 256 DNA bits set, MSB first
+fn bit_histogram(results: &[DnaResult]) -> Vec<f64> {
+    let mut counts = vec![0usize; 256];
+    for dna in results {
+        for (count, bit) in counts.iter_mut().zip(dna.dna_binary.bytes()) {
+            if bit == b'1' {
+                *count += 1;
+            }
+        }
+    }
+
+    let total = results.len().max(1) as f64;
+    counts
+        .into_iter()
+        .map(|count| count as f64 / total)
+        .collect()
+}
+
+/// Print how often each DNA bit is set across `images`
+///
+/// Bits that are (almost) always 0 or 1 carry no information and point at a
+/// weakness in the hash for this kind of content.
+fn dna_histogram(images: Vec<PathBuf>, json: bool) -> Result<()> {
+    let results = images
+        .iter()
+        .map(|image| {
+            compute_dna(image)
+                .with_context(|| format!("Failed to compute DNA for {}", image.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let histogram = bit_histogram(&results);
+
+    if json {
+        let value = serde_json::json!({
+            "images": results.len(),
+            "histogram": histogram,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("{}", "📊 DNA Bit Histogram".bright_cyan().bold());
+    println!("  Images: {}", results.len());
+    println!();
+
+    let mut stuck = 0;
+    for (bit, fraction) in histogram.iter().enumerate() {
+        let line = format!("    bit {:>3}: {:.2}", bit, fraction);
+        if *fraction == 0.0 || *fraction == 1.0 {
+            stuck += 1;
+            println!("{}", line.bright_red());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    println!();
+    println!("  Stuck bits: {}", stuck);
+
+    Ok(())
+}
+
+/// Image extensions `dna report` picks up from a folder
+const REPORT_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Near-duplicate clusters found in a folder
+#[derive(Debug, Serialize)]
+struct DnaReport {
+    threshold: u32,
+    total_images: usize,
+    clusters: Vec<ReportCluster>,
+}
+
+/// One group of near-duplicates; the representative is its first member
+#[derive(Debug, Serialize)]
+struct ReportCluster {
+    representative: String,
+    members: Vec<ReportMember>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportMember {
+    path: PathBuf,
+    dna_hex: String,
+    /// Hamming distance to the cluster representative
+    distance: u32,
+}
+
+/// Image files directly inside `dir`, sorted by path
+fn list_images(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut images: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| REPORT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+/// Compute DNA for every image in `dir` and cluster near-duplicates
+fn build_report(dir: &Path, threshold: u32) -> Result<DnaReport> {
+    let images = list_images(dir)?;
+    let hashes = images
+        .iter()
+        .map(|image| {
+            compute_dna(image)
+                .map(|dna| dna.dna_hex)
+                .with_context(|| format!("Failed to compute DNA for {}", image.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let clusters = cluster_by_threshold(&hashes, threshold)?
+        .into_iter()
+        .map(|indices| {
+            let representative = hashes[indices[0]].clone();
+            let members = indices
+                .into_iter()
+                .map(|i| {
+                    Ok(ReportMember {
+                        path: images[i].clone(),
+                        dna_hex: hashes[i].clone(),
+                        distance: hamming_distance(&representative, &hashes[i])?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ReportCluster {
+                representative,
+                members,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(DnaReport {
+        threshold,
+        total_images: images.len(),
+        clusters,
+    })
+}
+
+/// Report near-duplicate clusters in `dir` without registering anything
+///
+/// The JSON report goes to `output` if given, otherwise to stdout.
+fn dna_report(dir: PathBuf, threshold: u32, output: Option<PathBuf>) -> Result<()> {
+    let report = build_report(&dir, threshold)?;
+    let json = serde_json::to_string_pretty(&report).context("Failed to serialize report")?;
+
+    let Some(output) = output else {
+        println!("{}", json);
+        return Ok(());
+    };
+
+    fs::write(&output, json).with_context(|| format!("Failed to write {}", output.display()))?;
+
+    println!("{}", "🔍 Near-Duplicate Report".bright_cyan().bold());
+    println!("  📁 Folder: {}", dir.display());
+    println!("  Images: {}", report.total_images);
+    println!("  Clusters: {}", report.clusters.len());
+    for cluster in &report.clusters {
+        println!(
+            "    {} ({} images)",
+            cluster.representative.bright_yellow(),
+            cluster.members.len()
+        );
+    }
+    println!("  💾 Report: {}", output.display());
+
+    Ok(())
+}
+
+/// DNA extraction timings over a set of synthetic images
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    count: usize,
+    size: u32,
+    /// Throughput of the batch (parallel) extraction
+    images_per_sec: f64,
+    /// Mean time to extract one image on its own
+    mean_latency_ms: f64,
+}
+
+/// Write `count` distinct `size`×`size` PNGs into `dir`
+fn generate_bench_images(dir: &Path, count: usize, size: u32) -> Result<Vec<PathBuf>> {
+    (0..count)
+        .map(|i| {
+            let seed = i as u32;
+            let img = image::RgbImage::from_fn(size, size, |x, y| {
+                let v = (x.wrapping_mul(seed + 1) ^ y.wrapping_mul(seed + 7)) as u8;
+                image::Rgb([v, v.wrapping_add(seed as u8), 255 - v])
+            });
+            let path = dir.join(format!("bench_{}.png", i));
+            img.save(&path)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Time DNA extraction over `count` generated images
+///
+/// Throughput comes from one batch run, which uses every core; latency is
+/// measured separately by extracting each image sequentially.
+fn run_bench(count: usize, size: u32) -> Result<BenchResult> {
+    if count == 0 || size == 0 {
+        anyhow::bail!("count and size must be positive");
+    }
+
+    let dir = tempfile::tempdir().context("Failed to create temp dir")?;
+    let images = generate_bench_images(dir.path(), count, size)?;
+
+    let start = Instant::now();
+    for result in extract_dna_features_batch(&images, || {}) {
+        result.context("DNA extraction failed")?;
+    }
+    let batch_elapsed = start.elapsed();
+
+    let mut total_latency = Duration::ZERO;
+    for image in &images {
+        let start = Instant::now();
+        extract_dna_features(image).context("DNA extraction failed")?;
+        total_latency += start.elapsed();
+    }
+
+    Ok(BenchResult {
+        count,
+        size,
+        images_per_sec: count as f64 / batch_elapsed.as_secs_f64(),
+        mean_latency_ms: total_latency.as_secs_f64() * 1000.0 / count as f64,
+    })
+}
+
+/// Report DNA extraction throughput and latency on this machine
+fn dna_bench(count: usize, size: u32, json: bool) -> Result<()> {
+    if !json {
+        println!(
+            "{}",
+            format!(
+                "Benchmarking DNA on {} images of {}×{}...",
+                count, size, size
+            )
+            .yellow()
+        );
+    }
+
+    let result = run_bench(count, size)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("{}", "⏱️  DNA Benchmark".bright_cyan().bold());
+    println!(
+        "  Images: {} ({}×{})",
+        result.count, result.size, result.size
+    );
+    println!(
+        "  Throughput: {} images/s",
+        format!("{:.1}", result.images_per_sec).bright_green()
+    );
+    println!("  Mean latency: {:.2} ms", result.mean_latency_ms);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dna_hash() {
+        let hex = format!("{}{}", "ab".repeat(8), "cd".repeat(24));
+        let (dhash, grid_hash) = parse_dna_hash(&hex).unwrap();
+        assert_eq!(dhash, "ab".repeat(8));
+        assert_eq!(grid_hash, "cd".repeat(24));
+        assert!(verify_dna_hash(&hex.to_uppercase()).is_ok());
+
+        assert!(parse_dna_hash(&hex[..62]).is_err());
+        assert!(parse_dna_hash(&"zz".repeat(32)).is_err());
+        assert!(verify_dna_hash("not-a-hash").is_err());
+    }
+
+    #[test]
+    fn test_bit_histogram() {
+        let dir = tempfile::tempdir().unwrap();
+        let results: Vec<DnaResult> = (0..4u32)
+            .map(|i| {
+                let img = image::RgbImage::from_fn(64, 64, |x, y| {
+                    let v = ((x * (i + 1) + y * (4 - i)) % 256) as u8;
+                    image::Rgb([v, v.wrapping_mul(3), 255 - v])
+                });
+                let path = dir.path().join(format!("img_{}.png", i));
+                img.save(&path).unwrap();
+                compute_dna(&path).unwrap()
+            })
+            .collect();
+
+        let histogram = bit_histogram(&results);
+        assert_eq!(histogram.len(), 256);
+        assert!(histogram.iter().all(|f| (0.0..=1.0).contains(f)));
+
+        // Each entry is the share of hashes with that bit set
+        let set: usize = results
+            .iter()
+            .map(|dna| dna.dna_binary.bytes().filter(|b| *b == b'1').count())
+            .sum();
+        let total: f64 = histogram.iter().sum();
+        assert!((total * results.len() as f64 - set as f64).abs() < 1e-9);
+
+        assert!(dna_histogram(vec![dir.path().join("img_0.png")], true).is_ok());
+    }
+
+    #[test]
+    fn test_bench_reports_positive_throughput() {
+        let result = run_bench(3, 32).unwrap();
+        assert_eq!(result.count, 3);
+        assert!(result.images_per_sec > 0.0);
+        assert!(result.mean_latency_ms > 0.0);
+
+        assert!(run_bench(0, 32).is_err());
+        assert!(dna_bench(2, 16, true).is_ok());
+    }
+
+    #[test]
+    fn test_report_clusters_near_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let gradient = |offset: u8| {
+            image::RgbImage::from_fn(64, 64, move |x, y| {
+                let v = ((x + y) * 2) as u8;
+                image::Rgb([v.saturating_add(offset), v, 255 - v])
+            })
+        };
+        gradient(0).save(dir.path().join("a.png")).unwrap();
+        gradient(3).save(dir.path().join("b.png")).unwrap();
+        image::RgbImage::from_fn(64, 64, |x, y| {
+            let v = if (x / 8 + y / 8) % 2 == 0 { 0 } else { 255 };
+            image::Rgb([v, v, v])
+        })
+        .save(dir.path().join("c.png"))
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+        let report = build_report(dir.path(), 26).unwrap();
+        assert_eq!(report.total_images, 3);
+        assert_eq!(report.clusters.len(), 1);
+
+        let cluster = &report.clusters[0];
+        let names: Vec<_> = cluster
+            .members
+            .iter()
+            .map(|m| m.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["a.png", "b.png"]);
+        assert_eq!(cluster.representative, cluster.members[0].dna_hex);
+        assert_eq!(cluster.members[0].distance, 0);
+        assert!(cluster.members[1].distance <= 26);
+
+        let output = dir.path().join("report.json");
+        dna_report(dir.path().to_path_buf(), 26, Some(output.clone())).unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(output).unwrap()).unwrap();
+        assert_eq!(value["clusters"][0]["members"].as_array().unwrap().len(), 2);
+    }
+}
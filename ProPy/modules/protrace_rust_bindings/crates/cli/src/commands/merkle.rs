@@ -3,32 +3,188 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use protrace_image_dna::extract_dna_features;
-use protrace_merkle_tree::MerkleTree;
+use protrace_merkle_tree::{FileStore, ManifestStore, MerkleTree, TREE_MAGIC};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub async fn handle_merkle_command(action: crate::MerkleCommands) -> Result<()> {
+/// On-disk format written by `merkle build`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum TreeFormat {
+    /// Pretty-printed JSON manifest
+    Json,
+    /// Binary tree from `MerkleTree::to_bytes`
+    Bin,
+}
+
+pub async fn handle_merkle_command(action: crate::MerkleCommands, json: bool) -> Result<()> {
     match action {
         crate::MerkleCommands::Build {
             images,
             platform,
             output,
-        } => build_merkle_tree(images, platform, output).await,
+            format,
+            store,
+            deterministic_pointers,
+        } => {
+            build_merkle_tree(
+                images,
+                platform,
+                output,
+                format,
+                store,
+                deterministic_pointers,
+            )
+            .await
+        }
         crate::MerkleCommands::Proof { manifest, index } => {
-            generate_proof(manifest, index).await
+            generate_proof(manifest, index, json).await
         }
         crate::MerkleCommands::Verify {
             manifest,
             proof,
             index,
         } => verify_proof(manifest, proof, index).await,
+        crate::MerkleCommands::ExportProofs {
+            manifest,
+            dir,
+            indices,
+        } => export_proofs(manifest, dir, indices).await,
+        crate::MerkleCommands::RootFromHashes { file } => root_from_hashes(file, json),
     }
 }
 
+async fn export_proofs(manifest: PathBuf, dir: PathBuf, indices: Option<Vec<usize>>) -> Result<()> {
+    println!("{}", "Exporting proof bundles...".yellow());
+
+    let manifest_data = fs::read_to_string(&manifest).context("Failed to read manifest")?;
+    let manifest: protrace_merkle_tree::Manifest =
+        serde_json::from_str(&manifest_data).context("Failed to parse manifest")?;
+
+    let mut tree = MerkleTree::new();
+    tree.import_manifest(&manifest)
+        .context("Failed to import manifest")?;
+
+    let indices = indices.unwrap_or_else(|| (0..tree.leaf_count()).collect());
+    fs::create_dir_all(&dir).context("Failed to create output directory")?;
+
+    for &index in &indices {
+        let bundle = tree
+            .get_proof_bundle(index, true)
+            .with_context(|| format!("Failed to generate proof for leaf {}", index))?;
+        let path = dir.join(format!("proof_{}.json", index));
+        let json = serde_json::to_string_pretty(&bundle).context("Failed to serialize proof")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    println!("{}", "✅ Proofs Exported".bright_cyan().bold());
+    println!("  📊 Bundles: {}", indices.len());
+    println!("  📁 Directory: {}", dir.display());
+
+    Ok(())
+}
+
+/// Open the manifest store named by a `--store` spec
+fn open_store(spec: &str) -> Result<Box<dyn ManifestStore>> {
+    match spec.split_once(':') {
+        Some(("file", dir)) if !dir.is_empty() => Ok(Box::new(FileStore::new(dir))),
+        _ => anyhow::bail!("Unsupported manifest store {:?} (expected file:<dir>)", spec),
+    }
+}
+
+/// Load a built tree from a JSON manifest or a binary tree file
+///
+/// Binary files are recognised by their leading `TREE_MAGIC`.
+fn load_tree(path: &Path) -> Result<MerkleTree> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    if data.starts_with(TREE_MAGIC) {
+        return MerkleTree::from_bytes(&data).context("Failed to decode binary tree");
+    }
+
+    let manifest: protrace_merkle_tree::Manifest =
+        serde_json::from_slice(&data).context("Failed to parse manifest")?;
+    let mut tree = MerkleTree::new();
+    tree.import_manifest(&manifest)
+        .context("Failed to import manifest")?;
+    Ok(tree)
+}
+
+/// Build a tree from a DNA hash list (see `RootFromHashes`) and return it
+fn tree_from_hash_list(contents: &str) -> Result<MerkleTree> {
+    let mut tree = MerkleTree::new();
+
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut columns = line.split_whitespace();
+        let dna_hex = columns.next().unwrap_or_default();
+        if hex::decode(dna_hex).is_err() {
+            anyhow::bail!("Line {}: invalid DNA hex {:?}", line_no + 1, dna_hex);
+        }
+        let pointer = columns.next().unwrap_or_default();
+        let platform_id = columns.next().unwrap_or_default();
+        let timestamp = match columns.next() {
+            Some(ts) => ts
+                .parse::<i64>()
+                .with_context(|| format!("Line {}: invalid timestamp {:?}", line_no + 1, ts))?,
+            None => 0,
+        };
+
+        tree.add_leaf(dna_hex, pointer, platform_id, Some(timestamp));
+    }
+
+    Ok(tree)
+}
+
+/// Pointer derived from a leaf's DNA and platform
+///
+/// The same inputs always give the same pointer, so manifests built from
+/// the same images are reproducible (apart from leaf timestamps).
+pub fn deterministic_pointer(dna_hex: &str, platform: &str) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(dna_hex.as_bytes());
+    hasher.update(b"|");
+    hasher.update(platform.as_bytes());
+    format!("blake3:{}", &hasher.finalize().to_hex()[..32])
+}
+
+/// Pointer for a new leaf: deterministic, or a random UUID
+pub fn leaf_pointer(dna_hex: &str, platform: &str, deterministic: bool) -> String {
+    if deterministic {
+        deterministic_pointer(dna_hex, platform)
+    } else {
+        format!("uuid:{}", uuid::Uuid::new_v4())
+    }
+}
+
+fn root_from_hashes(file: PathBuf, json: bool) -> Result<()> {
+    let contents = fs::read_to_string(&file).context("Failed to read hash list")?;
+    let mut tree = tree_from_hash_list(&contents)?;
+    let root = tree.build_tree().context("Failed to build tree")?;
+
+    if json {
+        let value = serde_json::json!({
+            "root": root,
+            "leaves": tree.leaf_count(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("{}", root);
+    }
+
+    Ok(())
+}
+
 async fn build_merkle_tree(
     images: Vec<PathBuf>,
     platform: String,
     output: Option<PathBuf>,
+    format: TreeFormat,
+    store: Option<String>,
+    deterministic_pointers: bool,
 ) -> Result<()> {
     println!(
         "{}",
@@ -42,7 +198,7 @@ async fn build_merkle_tree(
 
         match extract_dna_features(image) {
             Ok(features) => {
-                let pointer = format!("uuid:{}", uuid::Uuid::new_v4());
+                let pointer = leaf_pointer(&features.dna_hex, &platform, deterministic_pointers);
                 tree.add_leaf(&features.dna_hex, &pointer, &platform, None);
                 println!("{}", "✓".bright_green());
             }
@@ -61,8 +217,36 @@ async fn build_merkle_tree(
     println!("  🔐 Root hash:");
     println!("    {}", root.bright_green());
 
+    if format == TreeFormat::Bin {
+        if store.is_some() {
+            anyhow::bail!("--store only accepts JSON manifests");
+        }
+        let output_path = output.unwrap_or_else(|| PathBuf::from("merkle_tree.bin"));
+        let bytes = tree.to_bytes().context("Failed to encode tree")?;
+        fs::write(&output_path, &bytes).context("Failed to write tree file")?;
+
+        println!();
+        println!(
+            "  📁 Tree saved: {} ({} bytes)",
+            output_path.display(),
+            bytes.len()
+        );
+        return Ok(());
+    }
+
     // Export manifest
     let manifest = tree.export_manifest().context("Failed to export manifest")?;
+
+    if let Some(spec) = store {
+        let key = open_store(&spec)?
+            .put(&manifest)
+            .context("Failed to store manifest")?;
+
+        println!();
+        println!("  📁 Manifest stored: {} ({})", key, spec);
+        return Ok(());
+    }
+
     let output_path = output.unwrap_or_else(|| PathBuf::from("merkle_manifest.json"));
 
     let json = serde_json::to_string_pretty(&manifest).context("Failed to serialize manifest")?;
@@ -74,16 +258,18 @@ async fn build_merkle_tree(
     Ok(())
 }
 
-async fn generate_proof(manifest: PathBuf, index: usize) -> Result<()> {
-    println!("{}", "Generating Merkle proof...".yellow());
+async fn generate_proof(manifest: PathBuf, index: usize, json: bool) -> Result<()> {
+    let tree = load_tree(&manifest)?;
 
-    let manifest_data = fs::read_to_string(&manifest).context("Failed to read manifest")?;
-    let manifest: protrace_merkle_tree::Manifest =
-        serde_json::from_str(&manifest_data).context("Failed to parse manifest")?;
+    if json {
+        let bundle = tree
+            .get_proof_bundle(index, true)
+            .context("Failed to generate proof")?;
+        println!("{}", serde_json::to_string_pretty(&bundle)?);
+        return Ok(());
+    }
 
-    let mut tree = MerkleTree::new();
-    tree.import_manifest(&manifest)
-        .context("Failed to import manifest")?;
+    println!("{}", "Generating Merkle proof...".yellow());
 
     let proof = tree.get_proof(index).context("Failed to generate proof")?;
 
@@ -140,3 +326,104 @@ async fn verify_proof(manifest: PathBuf, proof_file: PathBuf, index: usize) -> R
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_pointer() {
+        let dna = "ab".repeat(32);
+        let pointer = deterministic_pointer(&dna, "platform");
+
+        assert_eq!(pointer, deterministic_pointer(&dna, "platform"));
+        assert_eq!(pointer.len(), "blake3:".len() + 32);
+        assert_ne!(pointer, deterministic_pointer(&dna, "other"));
+        assert_ne!(pointer, deterministic_pointer(&"cd".repeat(32), "platform"));
+        assert_ne!(leaf_pointer(&dna, "platform", false), pointer);
+    }
+
+    #[test]
+    fn test_root_from_hash_list() {
+        let contents = [
+            "ab".repeat(32),
+            format!("{} ipfs://Qm1", "cd".repeat(32)),
+            String::new(),
+            "# comment".to_string(),
+            format!("{} ipfs://Qm2 opensea", "ef".repeat(32)),
+            format!("{} ipfs://Qm3 opensea 1698765432", "01".repeat(32)),
+        ]
+        .join("\n");
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), &contents).unwrap();
+
+        let mut tree = tree_from_hash_list(&fs::read_to_string(file.path()).unwrap()).unwrap();
+        assert_eq!(tree.leaf_count(), 4);
+        let root = tree.build_tree().unwrap();
+        assert_eq!(root.len(), 64);
+        assert_eq!(
+            root,
+            "f688f34e97259ff829c3a2b80943469271d71d17e4472ff36c7c50728efd3289"
+        );
+        assert!(root_from_hashes(file.path().to_path_buf(), true).is_ok());
+
+        assert!(tree_from_hash_list("zz ptr").is_err());
+        assert!(tree_from_hash_list(&format!("{} p x notanumber", "ab".repeat(32))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_proofs() {
+        let mut tree = MerkleTree::new();
+        for i in 0..3 {
+            tree.add_leaf(
+                &format!("dna{}", i),
+                &format!("ptr{}", i),
+                "platform",
+                Some(1000 + i),
+            );
+        }
+        tree.build_tree().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("manifest.json");
+        fs::write(&manifest, serde_json::to_string(&tree.export_manifest().unwrap()).unwrap())
+            .unwrap();
+
+        let out = dir.path().join("proofs");
+        export_proofs(manifest.clone(), out.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(fs::read_dir(&out).unwrap().count(), 3);
+        for i in 0..3 {
+            let data = fs::read_to_string(out.join(format!("proof_{}.json", i))).unwrap();
+            let bundle: protrace_merkle_tree::ProofBundle = serde_json::from_str(&data).unwrap();
+            assert!(bundle.verify().unwrap());
+        }
+
+        let subset = dir.path().join("subset");
+        export_proofs(manifest.clone(), subset.clone(), Some(vec![2]))
+            .await
+            .unwrap();
+        assert_eq!(fs::read_dir(&subset).unwrap().count(), 1);
+        assert!(subset.join("proof_2.json").exists());
+
+        assert!(export_proofs(manifest, subset, Some(vec![3]))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_open_store() {
+        let mut tree = tree_from_hash_list(&"ab".repeat(32)).unwrap();
+        tree.build_tree().unwrap();
+        let manifest = tree.export_manifest().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = open_store(&format!("file:{}", dir.path().display())).unwrap();
+        let key = store.put(&manifest).unwrap();
+        assert_eq!(store.get(&key).unwrap().root, manifest.root);
+
+        assert!(open_store("s3:bucket").is_err());
+        assert!(open_store("file:").is_err());
+    }
+}
@@ -3,8 +3,8 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use protrace_blockchain::{manifest_to_anchor_params, ProTraceClient};
-use protrace_image_dna::{compute_dna, extract_dna_features, is_duplicate};
+use protrace_blockchain::{manifest_to_anchor_params, ProTraceClient, DEFAULT_REGISTRY_ID};
+use protrace_image_dna::{cluster_by_threshold, extract_dna_features_batch, DnaFeatures};
 use protrace_merkle_tree::MerkleTree;
 use protrace_wallet::WalletManager;
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
@@ -12,7 +12,13 @@ use solana_sdk::signature::Signer;
 use std::path::PathBuf;
 use std::time::Instant;
 
-pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Result<()> {
+use super::merkle::leaf_pointer;
+
+pub async fn run_end_to_end_test(
+    images: Vec<PathBuf>,
+    wallet_path: &str,
+    deterministic_pointers: bool,
+) -> Result<()> {
     println!("{}", "🧪 Running End-to-End Test".bright_cyan().bold());
     println!("{}", "═".repeat(50).bright_black());
     println!();
@@ -21,7 +27,7 @@ pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Res
 
     // Step 1: Load wallet
     println!("{}", "Step 1: Loading wallet...".bright_yellow());
-    let wallet = WalletManager::from_file(wallet_path).context("Failed to load wallet")?;
+    let wallet = WalletManager::load(wallet_path).context("Failed to load wallet")?;
     println!("  ✓ Wallet loaded: {}", wallet.pubkey_string().bright_white());
     println!();
 
@@ -51,43 +57,27 @@ pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Res
             .progress_chars("=>-"),
     );
 
-    let mut dna_results = Vec::new();
-    for image in &images {
-        pb.set_message(format!("{}", image.file_name().unwrap().to_string_lossy()));
-        match extract_dna_features(image) {
-            Ok(features) => {
-                dna_results.push((image.clone(), features));
-            }
-            Err(e) => {
-                println!("  ✗ Failed to process {}: {}", image.display(), e);
-            }
-        }
-        pb.inc(1);
-    }
+    let dna_results = compute_all_dna(&images, &pb);
     pb.finish_with_message("Done");
     println!("  ✓ Processed {} images", dna_results.len());
     println!();
 
     // Step 4: Check for duplicates
     println!("{}", "Step 4: Checking for duplicates...".bright_yellow());
-    let mut found_duplicates = false;
-    for i in 0..dna_results.len() {
-        for j in (i + 1)..dna_results.len() {
-            let (img1, dna1) = &dna_results[i];
-            let (img2, dna2) = &dna_results[j];
-            
-            if is_duplicate(&dna1.dna_hex, &dna2.dna_hex, 26)? {
-                found_duplicates = true;
-                println!(
-                    "  {} Duplicate found: {} ↔ {}",
-                    "⚠️".bright_red(),
-                    img1.file_name().unwrap().to_string_lossy(),
-                    img2.file_name().unwrap().to_string_lossy()
-                );
-            }
-        }
+    let clusters = duplicate_clusters(&dna_results, 26)?;
+    for cluster in &clusters {
+        let names: Vec<String> = cluster
+            .iter()
+            .map(|image| image.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        println!(
+            "  {} Duplicate cluster ({}): {}",
+            "⚠️".bright_red(),
+            cluster.len(),
+            names.join(" ↔ ")
+        );
     }
-    if !found_duplicates {
+    if clusters.is_empty() {
         println!("  ✓ No duplicates detected");
     }
     println!();
@@ -97,7 +87,7 @@ pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Res
     let mut tree = MerkleTree::new();
     
     for (image, features) in &dna_results {
-        let pointer = format!("uuid:{}", uuid::Uuid::new_v4());
+        let pointer = leaf_pointer(&features.dna_hex, "devnet-test", deterministic_pointers);
         tree.add_leaf(&features.dna_hex, &pointer, "devnet-test", None);
     }
     
@@ -116,10 +106,16 @@ pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Res
 
     // Step 7: Anchor to blockchain
     println!("{}", "Step 7: Anchoring Merkle root to blockchain...".bright_yellow());
-    let (root_array, _cid, asset_count, timestamp) = manifest_to_anchor_params(&manifest);
+    let (root_array, _cid, asset_count, timestamp) = manifest_to_anchor_params(&manifest, None);
     
     match client
-        .anchor_merkle_root_oracle(root_array, manifest.root.clone(), asset_count, timestamp)
+        .anchor_merkle_root_oracle(
+            DEFAULT_REGISTRY_ID,
+            root_array,
+            manifest.root.clone(),
+            asset_count,
+            timestamp,
+        )
         .await
     {
         Ok(signature) => {
@@ -174,10 +170,121 @@ pub async fn run_end_to_end_test(images: Vec<PathBuf>, wallet_path: &str) -> Res
     println!("  • Images processed: {}", dna_results.len());
     println!("  • Merkle tree leaves: {}", tree.leaf_count());
     println!("  • Root hash: {}", root.bright_white());
-    println!("  • Duplicates found: {}", if found_duplicates { "Yes" } else { "No" });
+    println!("  • Duplicate clusters: {}", clusters.len());
     println!("  • Time elapsed: {:.2}s", elapsed.as_secs_f64());
     println!();
     println!("{}", "🎉 ProTrace is ready for production!".bright_cyan().bold());
 
     Ok(())
 }
+
+/// Extract DNA for `images` in parallel, advancing `pb` as each one finishes
+///
+/// Images that fail are reported and left out of the result.
+fn compute_all_dna(images: &[PathBuf], pb: &ProgressBar) -> Vec<(PathBuf, DnaFeatures)> {
+    let results = extract_dna_features_batch(images, || pb.inc(1));
+
+    images
+        .iter()
+        .zip(results)
+        .filter_map(|(image, result)| match result {
+            Ok(features) => Some((image.clone(), features)),
+            Err(e) => {
+                pb.println(format!("  ✗ Failed to process {}: {}", image.display(), e));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Group processed images into clusters of transitive near-duplicates
+fn duplicate_clusters(
+    dna_results: &[(PathBuf, DnaFeatures)],
+    threshold: u32,
+) -> Result<Vec<Vec<PathBuf>>> {
+    let hashes: Vec<&str> = dna_results
+        .iter()
+        .map(|(_, features)| features.dna_hex.as_str())
+        .collect();
+
+    let clusters = cluster_by_threshold(&hashes, threshold)?;
+
+    Ok(clusters
+        .into_iter()
+        .map(|cluster| {
+            cluster
+                .into_iter()
+                .map(|i| dna_results[i].0.clone())
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(dna_hex: &str) -> DnaFeatures {
+        DnaFeatures {
+            dna_signature: String::new(),
+            dna_hex: dna_hex.to_string(),
+            dna_binary: String::new(),
+            dhash: String::new(),
+            grid_hash: String::new(),
+            algorithm: "dHash+Grid".to_string(),
+            perceptual_hash: dna_hex.to_string(),
+            bits: 256,
+        }
+    }
+
+    #[test]
+    fn test_compute_all_dna() {
+        let dir = tempfile::tempdir().unwrap();
+        let images: Vec<PathBuf> = (0..5u32)
+            .map(|i| {
+                let img = image::RgbImage::from_fn(48, 48, |x, y| {
+                    image::Rgb([(x * 5) as u8, (y * 5) as u8, (i * 50) as u8])
+                });
+                let path = dir.path().join(format!("img_{}.png", i));
+                img.save(&path).unwrap();
+                path
+            })
+            .collect();
+
+        let pb = ProgressBar::hidden();
+        let dna_results = compute_all_dna(&images, &pb);
+
+        assert_eq!(dna_results.len(), images.len());
+        assert_eq!(pb.position(), images.len() as u64);
+        for ((path, features), image) in dna_results.iter().zip(&images) {
+            assert_eq!(path, image);
+            assert_eq!(features.dna_hex.len(), 64);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_clusters_groups_similar_images() {
+        let base = "ab".repeat(32);
+        let near = format!("{}aa", &base[..62]);
+        let nearer = format!("{}a8", &base[..62]);
+        let distinct = "54".repeat(32);
+
+        let dna_results = vec![
+            (PathBuf::from("a.png"), features(&base)),
+            (PathBuf::from("b.png"), features(&near)),
+            (PathBuf::from("c.png"), features(&nearer)),
+            (PathBuf::from("d.png"), features(&distinct)),
+        ];
+
+        let clusters = duplicate_clusters(&dna_results, 26).unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(
+            clusters[0],
+            vec![
+                PathBuf::from("a.png"),
+                PathBuf::from("b.png"),
+                PathBuf::from("c.png")
+            ]
+        );
+    }
+}
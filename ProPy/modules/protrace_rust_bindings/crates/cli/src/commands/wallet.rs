@@ -45,7 +45,7 @@ async fn create_new_wallet(output: PathBuf) -> Result<()> {
 async fn show_wallet_info(wallet_path: &str) -> Result<()> {
     println!("{}", "Loading wallet info...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path)
+    let wallet = WalletManager::load(wallet_path)
         .context("Failed to load wallet")?;
 
     println!("{}", "📋 Wallet Information".bright_cyan().bold());
@@ -58,7 +58,7 @@ async fn show_wallet_info(wallet_path: &str) -> Result<()> {
 async fn request_airdrop(wallet_path: &str, amount: f64) -> Result<()> {
     println!("{}", "Requesting airdrop...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path)
+    let wallet = WalletManager::load(wallet_path)
         .context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
@@ -80,7 +80,7 @@ async fn request_airdrop(wallet_path: &str, amount: f64) -> Result<()> {
 async fn get_balance(wallet_path: &str) -> Result<()> {
     println!("{}", "Fetching balance...".yellow());
 
-    let wallet = WalletManager::from_file(wallet_path)
+    let wallet = WalletManager::load(wallet_path)
         .context("Failed to load wallet")?;
 
     let client = ProTraceClient::new_devnet(wallet.keypair().insecure_clone())
@@ -19,13 +19,17 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Wallet keypair file path
+    /// Wallet keypair file path, or `env:VAR` to read it from an environment variable
     #[arg(short, long, global = true, default_value = "~/.config/solana/id.json")]
     wallet: String,
 
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Emit machine-readable JSON output where supported
+    #[arg(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand)]
@@ -59,6 +63,9 @@ enum Commands {
         /// Image files to test
         #[arg(required = true)]
         images: Vec<PathBuf>,
+        /// Derive pointers from DNA and platform instead of random UUIDs
+        #[arg(long)]
+        deterministic_pointers: bool,
     },
 }
 
@@ -106,6 +113,41 @@ enum DnaCommands {
         /// Image files
         images: Vec<PathBuf>,
     },
+
+    /// Check that a DNA hash is well-formed
+    VerifyHash {
+        /// DNA hash (64 hex chars)
+        hex: String,
+    },
+
+    /// Show how often each DNA bit is set across a set of images
+    Histogram {
+        /// Image files
+        #[arg(required = true)]
+        images: Vec<PathBuf>,
+    },
+
+    /// Report near-duplicate clusters in a folder (dry run, nothing anchored)
+    Report {
+        /// Folder of images
+        dir: PathBuf,
+        /// Maximum Hamming distance for two images to count as duplicates
+        #[arg(long, default_value = "26")]
+        threshold: u32,
+        /// Write the JSON report here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Measure DNA extraction throughput on synthetic images
+    Bench {
+        /// Number of images to generate
+        #[arg(long, default_value = "100")]
+        count: usize,
+        /// Width and height of each image in pixels
+        #[arg(long, default_value = "512")]
+        size: u32,
+    },
 }
 
 #[derive(Subcommand)]
@@ -120,11 +162,20 @@ enum MerkleCommands {
         /// Output file
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Output format: a JSON manifest, or the compact binary tree
+        #[arg(long, value_enum, default_value = "json")]
+        format: commands::merkle::TreeFormat,
+        /// Manifest store instead of an output file (`file:<dir>`)
+        #[arg(long, conflicts_with = "output")]
+        store: Option<String>,
+        /// Derive pointers from DNA and platform instead of random UUIDs
+        #[arg(long)]
+        deterministic_pointers: bool,
     },
 
     /// Generate proof for specific image
     Proof {
-        /// Manifest file
+        /// Manifest file, or a binary tree from `build --format bin`
         manifest: PathBuf,
         /// Image index
         index: usize,
@@ -139,6 +190,28 @@ enum MerkleCommands {
         /// Leaf index
         index: usize,
     },
+
+    /// Write a proof bundle file for every leaf of a manifest
+    ExportProofs {
+        /// Manifest file
+        manifest: PathBuf,
+        /// Output directory
+        #[arg(long)]
+        dir: PathBuf,
+        /// Only export these leaf indices (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        indices: Option<Vec<usize>>,
+    },
+
+    /// Compute the root for a list of DNA hashes
+    ///
+    /// One leaf per line: `dna_hex [pointer [platform_id [timestamp]]]`,
+    /// whitespace-separated. Missing columns default to empty strings and
+    /// timestamp 0. Blank lines and lines starting with `#` are skipped.
+    RootFromHashes {
+        /// Input file
+        file: PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -159,6 +232,31 @@ enum BlockchainCommands {
     Anchor {
         /// Manifest file
         manifest: PathBuf,
+        /// Unix timestamp to anchor with (default: now)
+        #[arg(long)]
+        timestamp: Option<i64>,
+    },
+
+    /// Anchor a raw 32-byte root via oracle (no manifest CID)
+    AnchorRoot {
+        /// Root hash (hex)
+        root: String,
+        /// Number of assets committed by the root
+        #[arg(long)]
+        asset_count: u64,
+    },
+
+    /// Show the current on-chain anchor account
+    Status,
+
+    /// Fetch a manifest from an IPFS gateway and check it against the anchored root
+    Audit {
+        /// Manifest CID
+        #[arg(long)]
+        cid: String,
+        /// IPFS gateway base URL
+        #[arg(long, default_value = "https://ipfs.io")]
+        gateway: String,
     },
 
     /// Initialize edition registry
@@ -178,18 +276,27 @@ async fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
         .init();
 
-    println!("{}", "🔒 ProTrace - NFT Duplicate Prevention".bright_cyan().bold());
-    println!("{}", "─".repeat(50).bright_black());
+    if !cli.json {
+        println!("{}", "🔒 ProTrace - NFT Duplicate Prevention".bright_cyan().bold());
+        println!("{}", "─".repeat(50).bright_black());
+    }
 
     match cli.command {
         Commands::Wallet { action } => {
             commands::wallet::handle_wallet_command(action, &cli.wallet).await
         }
-        Commands::Dna { action } => commands::dna::handle_dna_command(action).await,
-        Commands::Merkle { action } => commands::merkle::handle_merkle_command(action).await,
+        Commands::Dna { action } => commands::dna::handle_dna_command(action, cli.json).await,
+        Commands::Merkle { action } => {
+            commands::merkle::handle_merkle_command(action, cli.json).await
+        }
         Commands::Blockchain { action } => {
-            commands::blockchain::handle_blockchain_command(action, &cli.wallet).await
+            commands::blockchain::handle_blockchain_command(action, &cli.wallet, cli.json).await
+        }
+        Commands::Test {
+            images,
+            deterministic_pointers,
+        } => {
+            commands::test::run_end_to_end_test(images, &cli.wallet, deterministic_pointers).await
         }
-        Commands::Test { images } => commands::test::run_end_to_end_test(images, &cli.wallet).await,
     }
 }
@@ -0,0 +1,49 @@
+//! End-to-end check of `merkle build --format bin` and `merkle proof`
+
+use protrace_merkle_tree::{MerkleTree, ProofBundle};
+use std::path::Path;
+use std::process::Command;
+
+fn protrace(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_protrace"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "protrace {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_binary_tree_proof() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut images = Vec::new();
+    for i in 0..3u32 {
+        let name = format!("image_{}.png", i);
+        image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * (i + 1)) as u8, (y * 3) as u8, ((x + y) * i) as u8])
+        })
+        .save(dir.path().join(&name))
+        .unwrap();
+        images.push(name);
+    }
+
+    let mut build = vec!["merkle", "build"];
+    build.extend(images.iter().map(String::as_str));
+    build.extend(["--format", "bin", "--output", "tree.bin"]);
+    protrace(dir.path(), &build);
+
+    let tree =
+        MerkleTree::from_bytes(&std::fs::read(dir.path().join("tree.bin")).unwrap()).unwrap();
+    assert_eq!(tree.leaf_count(), 3);
+
+    let stdout = protrace(dir.path(), &["--json", "merkle", "proof", "tree.bin", "1"]);
+    let bundle: ProofBundle = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(bundle.root, tree.get_root().unwrap());
+    assert!(bundle.verify().unwrap());
+}
@@ -3,8 +3,10 @@
 //! 256-bit DNA fingerprinting combining dHash (64-bit) + Grid (192-bit)
 //! Designed for cross-platform NFT duplicate prevention.
 
-use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Rgb};
+use image::error::{DecodingError, ImageFormatHint};
+use image::{DynamicImage, GenericImageView, ImageBuffer, ImageError, Luma, Rgb};
 use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
 use thiserror::Error;
 
@@ -16,8 +18,14 @@ pub enum DnaError {
     InvalidHashFormat(String),
     #[error("Hash length mismatch")]
     HashLengthMismatch,
+    #[error("Failed to download image: {0}")]
+    DownloadError(String),
 }
 
+/// Largest image body [`compute_dna_from_url`] will download
+#[cfg(feature = "reqwest")]
+pub const MAX_DOWNLOAD_BYTES: u64 = 20 * 1024 * 1024;
+
 /// DNA computation result containing all components
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnaResult {
@@ -29,6 +37,14 @@ pub struct DnaResult {
     pub bits: u32,
 }
 
+/// Distance, similarity and duplicate verdict for a pair of DNA hashes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DnaComparison {
+    pub distance: u32,
+    pub similarity: f64,
+    pub is_duplicate: bool,
+}
+
 /// DNA feature extraction result for compatibility
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnaFeatures {
@@ -44,10 +60,92 @@ pub struct DnaFeatures {
 
 /// Compute 256-bit DNA fingerprint (dHash + Grid)
 pub fn compute_dna<P: AsRef<Path>>(image_path: P) -> Result<DnaResult, DnaError> {
-    let img = image::open(image_path)?;
+    let img = load_image(image_path.as_ref())?;
+    compute_dna_from_image(&img)
+}
+
+/// Open and decode an image, turning decoder panics into load errors
+///
+/// Truncated or corrupt files (e.g. partial downloads) can panic inside the
+/// decoder instead of returning an error; such panics are caught and
+/// reported as `ImageLoadError`. Images with a zero dimension are rejected.
+fn load_image(path: &Path) -> Result<DynamicImage, DnaError> {
+    let reader = image::io::Reader::open(path)
+        .map_err(ImageError::IoError)?
+        .with_guessed_format()
+        .map_err(ImageError::IoError)?;
+    let format = reader
+        .format()
+        .map_or(ImageFormatHint::Unknown, ImageFormatHint::Exact);
+
+    let img = panic::catch_unwind(AssertUnwindSafe(|| reader.decode())).map_err(|_| {
+        ImageError::Decoding(DecodingError::new(
+            format.clone(),
+            format!("decoder panicked on truncated or corrupt file {}", path.display()),
+        ))
+    })??;
+
+    if img.width() == 0 || img.height() == 0 {
+        return Err(ImageError::Decoding(DecodingError::new(
+            format,
+            format!("image {} has zero width or height", path.display()),
+        ))
+        .into());
+    }
+
+    Ok(img)
+}
+
+/// Compute 256-bit DNA fingerprint for the image at `url`
+///
+/// The response must be a 2xx with an `image/*` content type and a body of
+/// at most [`MAX_DOWNLOAD_BYTES`]; anything else is a `DownloadError`.
+#[cfg(feature = "reqwest")]
+pub fn compute_dna_from_url(url: &str) -> Result<DnaResult, DnaError> {
+    let bytes = download_image(url, MAX_DOWNLOAD_BYTES)?;
+    let img = image::load_from_memory(&bytes)?;
     compute_dna_from_image(&img)
 }
 
+/// Download an image body, enforcing status, content type and size cap
+#[cfg(feature = "reqwest")]
+fn download_image(url: &str, max_bytes: u64) -> Result<Vec<u8>, DnaError> {
+    use std::io::Read;
+
+    let response = reqwest::blocking::get(url)
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| DnaError::DownloadError(e.to_string()))?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    if !content_type.starts_with("image/") {
+        return Err(DnaError::DownloadError(format!(
+            "unexpected content type {:?}",
+            content_type
+        )));
+    }
+
+    let oversize = || DnaError::DownloadError(format!("image exceeds {} bytes", max_bytes));
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(oversize());
+    }
+
+    // Content-Length may be absent or wrong, so cap the read as well
+    let mut bytes = Vec::new();
+    response
+        .take(max_bytes + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| DnaError::DownloadError(e.to_string()))?;
+    if bytes.len() as u64 > max_bytes {
+        return Err(oversize());
+    }
+
+    Ok(bytes)
+}
+
 /// Compute DNA from DynamicImage
 pub fn compute_dna_from_image(img: &DynamicImage) -> Result<DnaResult, DnaError> {
     // Compute dHash (64-bit)
@@ -64,20 +162,22 @@ pub fn compute_dna_from_image(img: &DynamicImage) -> Result<DnaResult, DnaError>
     let dna_int2 = u128::from_str_radix(&dna_hex[32..], 16).unwrap_or(0) as u128;
     let dna_binary = format!("{:0128b}{:0128b}", dna_int, dna_int2);
     
+    // Derived from the hex so it stays correct if components are added
+    let bits = (dna_hex.len() * 4) as u32;
+    
     Ok(DnaResult {
         dna_hex,
         dna_binary,
         dhash: dhash.hash_hex,
         grid_hash: grid_hash.hash_hex,
         algorithm: "dHash+Grid".to_string(),
-        bits: 256,
+        bits,
     })
 }
 
 #[derive(Debug)]
 struct HashResult {
     hash_hex: String,
-    bits: Vec<u8>,
 }
 
 /// Legacy dHash implementation (matches Python version)
@@ -119,7 +219,7 @@ fn compute_dhash_legacy(img: &DynamicImage) -> Result<HashResult, DnaError> {
                     }
                 }
             }
-            let avg = if count > 0 { (sum / count) as u8 } else { 0 };
+            let avg = sum.checked_div(count).unwrap_or(0) as u8;
             gray_128.put_pixel(x, y, Luma([avg]));
         }
     }
@@ -140,7 +240,7 @@ fn compute_dhash_legacy(img: &DynamicImage) -> Result<HashResult, DnaError> {
     // Convert to hex (16 chars for 64 bits)
     let hash_hex = bits_to_hex(&bits);
     
-    Ok(HashResult { hash_hex, bits })
+    Ok(HashResult { hash_hex })
 }
 
 /// Compute 192-bit Grid hash (matches Python version)
@@ -182,10 +282,7 @@ fn compute_grid_hash(img: &DynamicImage) -> Result<HashResult, DnaError> {
     // Convert to hex (48 chars for 192 bits)
     let hash_hex = bits_to_hex(&all_bits);
     
-    Ok(HashResult {
-        hash_hex,
-        bits: all_bits,
-    })
+    Ok(HashResult { hash_hex })
 }
 
 /// Pad image to target_size × target_size with black padding
@@ -329,6 +426,56 @@ pub fn is_duplicate(hash1: &str, hash2: &str, threshold: u32) -> Result<bool, Dn
     Ok(distance <= threshold)
 }
 
+/// Compare two DNA hashes, deriving all metrics from one distance
+pub fn compare_dna(hash1: &str, hash2: &str, threshold: u32) -> Result<DnaComparison, DnaError> {
+    let distance = hamming_distance(hash1, hash2)?;
+    Ok(DnaComparison {
+        distance,
+        similarity: 1.0 - (distance as f64 / 256.0),
+        is_duplicate: distance <= threshold,
+    })
+}
+
+/// Group DNA hashes into clusters of near-duplicates
+///
+/// Two hashes are linked when their Hamming distance is within `threshold`;
+/// links are transitive, so A~B and B~C place A, B and C in one cluster
+/// even if A and C are further apart. Returns clusters of two or more
+/// indices into `hashes`, each sorted, ordered by their smallest index.
+pub fn cluster_by_threshold<S: AsRef<str>>(
+    hashes: &[S],
+    threshold: u32,
+) -> Result<Vec<Vec<usize>>, DnaError> {
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+
+    for i in 0..hashes.len() {
+        for j in (i + 1)..hashes.len() {
+            if is_duplicate(hashes[i].as_ref(), hashes[j].as_ref(), threshold)? {
+                let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+                if root_i != root_j {
+                    parent[root_j.max(root_i)] = root_i.min(root_j);
+                }
+            }
+        }
+    }
+
+    let mut groups: std::collections::BTreeMap<usize, Vec<usize>> = Default::default();
+    for i in 0..hashes.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    Ok(groups.into_values().filter(|group| group.len() > 1).collect())
+}
+
 /// Extract DNA features with BLAKE3 signature
 pub fn extract_dna_features<P: AsRef<Path>>(image_path: P) -> Result<DnaFeatures, DnaError> {
     let dna_result = compute_dna(image_path)?;
@@ -344,10 +491,58 @@ pub fn extract_dna_features<P: AsRef<Path>>(image_path: P) -> Result<DnaFeatures
         grid_hash: dna_result.grid_hash,
         algorithm: dna_result.algorithm,
         perceptual_hash: dna_result.dna_hex,
-        bits: 256,
+        bits: dna_result.bits,
     })
 }
 
+/// Extract DNA features for many images, calling `on_progress` after each
+///
+/// Results are returned in input order. With the `parallel` feature the
+/// images are processed on rayon's global pool, and `on_progress` may be
+/// called from several threads at once.
+#[cfg(feature = "parallel")]
+pub fn extract_dna_features_batch<P, F>(
+    image_paths: &[P],
+    on_progress: F,
+) -> Vec<Result<DnaFeatures, DnaError>>
+where
+    P: AsRef<Path> + Sync,
+    F: Fn() + Sync,
+{
+    use rayon::prelude::*;
+
+    image_paths
+        .par_iter()
+        .map(|path| {
+            let features = extract_dna_features(path);
+            on_progress();
+            features
+        })
+        .collect()
+}
+
+/// Extract DNA features for many images, calling `on_progress` after each
+///
+/// Results are returned in input order.
+#[cfg(not(feature = "parallel"))]
+pub fn extract_dna_features_batch<P, F>(
+    image_paths: &[P],
+    on_progress: F,
+) -> Vec<Result<DnaFeatures, DnaError>>
+where
+    P: AsRef<Path> + Sync,
+    F: Fn() + Sync,
+{
+    image_paths
+        .iter()
+        .map(|path| {
+            let features = extract_dna_features(path);
+            on_progress();
+            features
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -374,4 +569,154 @@ mod tests {
         let hash2 = "0000000000000001";
         assert!(is_duplicate(hash1, hash2, 26).unwrap());
     }
+
+    #[test]
+    fn test_extract_dna_features_batch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths: Vec<_> = (0..6u32)
+            .map(|i| {
+                let img = image::RgbImage::from_fn(32, 32, |x, y| {
+                    image::Rgb([(x * 8) as u8, (y * 8) as u8, (i * 40) as u8])
+                });
+                let path = dir.path().join(format!("img_{}.png", i));
+                img.save(&path).unwrap();
+                path
+            })
+            .collect();
+        paths.push(dir.path().join("missing.png"));
+
+        let progress = AtomicUsize::new(0);
+        let results = extract_dna_features_batch(&paths, || {
+            progress.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(results.len(), paths.len());
+        assert_eq!(progress.load(Ordering::Relaxed), paths.len());
+        assert!(results[6].is_err());
+        for (path, result) in paths.iter().zip(&results).take(6) {
+            assert_eq!(result.as_ref().unwrap().dna_hex, compute_dna(path).unwrap().dna_hex);
+        }
+    }
+
+    #[test]
+    fn test_cluster_by_threshold() {
+        let hashes = [
+            "0000000000000000",
+            "0000000000000001",
+            "0000000000000003",
+            "ffffffffffffffff",
+        ];
+
+        // Three mutually-similar hashes form one cluster; the outlier is dropped
+        let clusters = cluster_by_threshold(&hashes, 2).unwrap();
+        assert_eq!(clusters, vec![vec![0, 1, 2]]);
+
+        // Transitive: 0~1 and 1~2 at distance 1, while 0 and 2 are 2 apart
+        let clusters = cluster_by_threshold(&hashes, 1).unwrap();
+        assert_eq!(clusters, vec![vec![0, 1, 2]]);
+
+        assert!(cluster_by_threshold(&hashes, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compute_dna_truncated_png() {
+        let img = DynamicImage::ImageRgb8(ImageBuffer::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        }));
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageOutputFormat::Png).unwrap();
+        let png = png.into_inner();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("partial.png");
+        std::fs::write(&path, &png[..png.len() / 2]).unwrap();
+
+        assert!(matches!(compute_dna(&path), Err(DnaError::ImageLoadError(_))));
+
+        // The intact file still decodes
+        std::fs::write(&path, &png).unwrap();
+        assert!(compute_dna(&path).is_ok());
+    }
+
+    /// Serve a single HTTP response on a local port and return its URL
+    #[cfg(feature = "reqwest")]
+    fn serve_once(content_type: &'static str, body: Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/image.png", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            // Drain request line and headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            write!(
+                stream,
+                concat!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n",
+                    "Connection: close\r\n\r\n"
+                ),
+                content_type,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+
+        url
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_compute_dna_from_url() {
+        let img = DynamicImage::ImageRgb8(ImageBuffer::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        }));
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageOutputFormat::Png).unwrap();
+        let png = png.into_inner();
+
+        let dna = compute_dna_from_url(&serve_once("image/png", png.clone())).unwrap();
+        assert_eq!(dna.dna_hex, compute_dna_from_image(&img).unwrap().dna_hex);
+
+        let err = compute_dna_from_url(&serve_once("text/html", png.clone())).unwrap_err();
+        assert!(matches!(err, DnaError::DownloadError(_)));
+
+        let err = download_image(&serve_once("image/png", png.clone()), 16).unwrap_err();
+        assert!(matches!(err, DnaError::DownloadError(msg) if msg.contains("exceeds")));
+    }
+
+    #[test]
+    fn test_compare_dna() {
+        let hash1 = "0000000000000000";
+        let hash2 = "000000000000000f";
+
+        let comparison = compare_dna(hash1, hash2, 26).unwrap();
+        assert_eq!(comparison.distance, hamming_distance(hash1, hash2).unwrap());
+        assert_eq!(comparison.distance, 4);
+        assert_eq!(comparison.similarity, dna_similarity(hash1, hash2).unwrap());
+        assert_eq!(comparison.is_duplicate, is_duplicate(hash1, hash2, 26).unwrap());
+        assert!(comparison.is_duplicate);
+
+        assert!(!compare_dna(hash1, hash2, 3).unwrap().is_duplicate);
+        assert!(compare_dna(hash1, "00", 26).is_err());
+    }
+
+    #[test]
+    fn test_bits_matches_hex_length() {
+        let img = DynamicImage::ImageRgb8(ImageBuffer::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        }));
+        let dna = compute_dna_from_image(&img).unwrap();
+        assert_eq!(dna.dna_hex.len(), 64);
+        assert_eq!(dna.bits as usize, dna.dna_hex.len() * 4);
+    }
 }
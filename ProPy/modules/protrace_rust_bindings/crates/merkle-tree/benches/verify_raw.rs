@@ -0,0 +1,79 @@
+//! Hex vs. pre-decoded proof verification
+//!
+//! Before the timing runs, prints the number of heap allocations made by
+//! 10k verifications of a depth-14 proof along each path.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use protrace_merkle_tree::leaf_encoding::{encode_leaf, hash_leaf};
+use protrace_merkle_tree::{proof_to_raw, verify_proof_standalone, verify_raw, MerkleTree};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that counts allocations
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const LEAVES: i64 = 1 << 14;
+const ITERATIONS: usize = 10_000;
+const INDEX: i64 = 12_345;
+
+fn count_allocations(f: impl Fn()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let mut tree = MerkleTree::new();
+    for i in 0..LEAVES {
+        tree.add_leaf(&format!("{:064x}", i), &format!("ptr{}", i), "platform", Some(i));
+    }
+    let root = tree.build_tree().unwrap();
+    assert_eq!(tree.depth(), 14);
+
+    let dna = format!("{:064x}", INDEX);
+    let pointer = format!("ptr{}", INDEX);
+    let proof = tree.get_proof(INDEX as usize).unwrap();
+
+    let raw = proof_to_raw(&proof).unwrap();
+    let leaf_hash = hash_leaf(&encode_leaf(&dna, &pointer, "platform", INDEX));
+    let root_bytes: [u8; 32] = hex::decode(&root).unwrap().try_into().unwrap();
+
+    let verify_hex = || {
+        let ok = verify_proof_standalone(&dna, &pointer, "platform", INDEX, &proof, &root);
+        assert!(black_box(ok.unwrap()));
+    };
+    let verify_decoded = || assert!(black_box(verify_raw(&leaf_hash, &raw, &root_bytes)));
+
+    println!(
+        "allocations for {} depth-14 verifications: hex {}, raw {}",
+        ITERATIONS,
+        count_allocations(verify_hex),
+        count_allocations(verify_decoded)
+    );
+
+    let mut group = c.benchmark_group("verify_depth_14");
+    group.bench_function("hex", |b| b.iter(verify_hex));
+    group.bench_function("raw", |b| b.iter(verify_decoded));
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Leaf encoding shared by tree construction and verification
+//!
+//! Leaf = BLAKE3(DNA_hex || pointer || platform_id || timestamp), with the
+//! fields joined by `|` before hashing.
+//!
+//! Edition leaves use the universal edition key instead:
+//! `dna_hex#chain#contract_hex#token_id#edition_no`.
+
+/// Encode registration fields into leaf bytes
+pub fn encode_leaf(dna_hex: &str, pointer: &str, platform_id: &str, timestamp: i64) -> Vec<u8> {
+    format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp).into_bytes()
+}
+
+/// Encode an edition's universal key into leaf bytes
+pub fn encode_edition_leaf(
+    dna_hex: &str,
+    chain: &str,
+    contract_hex: &str,
+    token_id: &str,
+    edition_no: u32,
+) -> Vec<u8> {
+    format!("{}#{}#{}#{}#{}", dna_hex, chain, contract_hex, token_id, edition_no).into_bytes()
+}
+
+/// BLAKE3 hash of encoded leaf bytes
+pub fn hash_leaf(leaf_data: &[u8]) -> [u8; 32] {
+    hash_leaf_namespaced(&[], leaf_data)
+}
+
+/// BLAKE3 hash of encoded leaf bytes prefixed with a registry namespace
+///
+/// An empty namespace is identical to [`hash_leaf`].
+pub fn hash_leaf_namespaced(namespace: &[u8], leaf_data: &[u8]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(namespace);
+    hasher.update(leaf_data);
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_leaf() {
+        assert_eq!(encode_leaf("abc123", "ptr1", "platform1", 1000), b"abc123|ptr1|platform1|1000");
+        assert_eq!(
+            hash_leaf(b"abc123|ptr1|platform1|1000"),
+            *blake3::hash(b"abc123|ptr1|platform1|1000").as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_encode_edition_leaf() {
+        assert_eq!(
+            encode_edition_leaf("abc123", "solana", "00ff", "42", 3),
+            b"abc123#solana#00ff#42#3"
+        );
+    }
+}
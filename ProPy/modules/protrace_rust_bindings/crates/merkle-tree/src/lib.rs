@@ -3,12 +3,32 @@
 //! BLAKE3-based Merkle tree for tamper-proof DNA registration commitments.
 //! Optimized for batch verification with O(log n) proof generation.
 
+use cid::Cid;
+use multihash::Multihash;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
 use thiserror::Error;
 
+pub mod leaf_encoding;
+pub mod store;
+
+pub use store::{FileStore, ManifestStore};
+
+use leaf_encoding::{encode_edition_leaf, encode_leaf, hash_leaf, hash_leaf_namespaced};
+
+/// Merkle tree errors
+///
+/// `EmptyTree`, `TreeNotBuilt`, `LeafIndexOutOfRange`, `InvalidProof`,
+/// `RootMismatch` and `InvalidHex` have the same names and meanings in
+/// ProRust's merkle crate, so a `match` on them works against either.
 #[derive(Error, Debug)]
 pub enum MerkleError {
+    #[error("Tree is empty")]
+    EmptyTree,
+    #[error("Trees use different namespaces")]
+    NamespaceMismatch,
     #[error("Tree not built")]
     TreeNotBuilt,
     #[error("Leaf index out of range: {0}")]
@@ -17,10 +37,48 @@ pub enum MerkleError {
     InvalidProof,
     #[error("Root mismatch")]
     RootMismatch,
+    #[error("Invalid hex encoding: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+    #[error("Manifest serialization failed: {0}")]
+    Serialization(String),
+    #[error("Timestamp out of range: {0}")]
+    InvalidTimestamp(i64),
+    #[error("Manifest storage failed: {0}")]
+    Storage(String),
+    #[error("Leaf {0} is not a text registration leaf")]
+    NonTextLeaf(usize),
+    #[error("Stored hash of leaf {0} does not match its fields")]
+    LeafHashMismatch(usize),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("DNA {0} is registered STRICT_1_1 and cannot have other editions")]
+    EditionPolicyViolation(String),
 }
 
+/// How far past the current time a strict tree accepts leaf timestamps
+pub const MAX_TIMESTAMP_SKEW_SECS: i64 = 24 * 60 * 60;
+
+/// DNA algorithm recorded in exported manifests unless overridden
+pub const DEFAULT_DNA_ALGORITHM: &str = "dHash+Grid/v1";
+
+/// `Manifest::algorithm` of manifests written before the field existed
+pub const UNKNOWN_DNA_ALGORITHM: &str = "unknown";
+
+/// Magic bytes that start every [`MerkleTree::to_bytes`] encoding
+pub const TREE_MAGIC: &[u8; 4] = b"PTMT";
+
+/// Layout version written after [`TREE_MAGIC`]
+const TREE_FORMAT_VERSION: u8 = 1;
+
+/// Multicodec code for raw binary blocks
+const RAW_CODEC: u64 = 0x55;
+
+/// Multihash code for SHA2-256
+const SHA2_256_CODE: u64 = 0x12;
+
 /// Merkle tree node
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct MerkleNode {
     hash: [u8; 32],
     left: Option<Box<MerkleNode>>,
@@ -30,8 +88,8 @@ struct MerkleNode {
 }
 
 impl MerkleNode {
-    fn new_leaf(data: Vec<u8>) -> Self {
-        let hash = blake3::hash(&data).into();
+    fn new_leaf(data: Vec<u8>, namespace: &[u8]) -> Self {
+        let hash = hash_leaf_namespaced(namespace, &data);
         Self {
             hash,
             left: None,
@@ -58,19 +116,200 @@ impl MerkleNode {
 }
 
 /// Proof element for Merkle proof
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `hash` is always held and serialized as hex, but may be deserialized from
+/// either a hex string or a 32-byte array, as emitted by some non-Rust tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProofElement {
+    #[serde(deserialize_with = "deserialize_hash_hex")]
     pub hash: String,
     pub position: Position,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Sibling hash as it may appear in proof JSON
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HashRepr {
+    Hex(String),
+    Bytes([u8; 32]),
+}
+
+fn deserialize_hash_hex<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match HashRepr::deserialize(deserializer)? {
+        HashRepr::Hex(hash) => hash,
+        HashRepr::Bytes(bytes) => hex::encode(bytes),
+    })
+}
+
+/// Edition policy of a DNA, mirroring the on-chain `edition_mode`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EditionMode {
+    /// Any number of editions (on-chain 0 = SERIAL)
+    Serial,
+    /// Exactly one edition (on-chain 1 = STRICT_1_1)
+    Strict1To1,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Position {
     Left,
     Right,
 }
 
+/// Proof element with the sibling hash already decoded
+///
+/// Converting a proof once with [`proof_to_raw`] lets [`verify_raw`] run
+/// without hex decoding or heap allocation per step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProofElementRaw {
+    pub hash: [u8; 32],
+    pub position: Position,
+}
+
+impl TryFrom<&ProofElement> for ProofElementRaw {
+    type Error = MerkleError;
+
+    fn try_from(element: &ProofElement) -> Result<Self, MerkleError> {
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(&element.hash, &mut hash)?;
+        Ok(Self {
+            hash,
+            position: element.position,
+        })
+    }
+}
+
+/// Merkle proof that records the leaf index it was generated for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proof {
+    pub index: usize,
+    pub elements: Vec<ProofElement>,
+}
+
+impl Proof {
+    /// Encode as `depth (1 byte) || position bitmap || 32-byte siblings`
+    ///
+    /// Bit `i` of the bitmap (LSB first) is set when the level-`i` sibling
+    /// is on the left. Fails with `InvalidProof` for proofs deeper than 255
+    /// levels, and with `InvalidHex` unless every element is a 32-byte hex
+    /// hash as produced by `get_proof`.
+    pub fn to_compact(&self) -> Result<Vec<u8>, MerkleError> {
+        let depth = self.elements.len();
+        if depth > u8::MAX as usize {
+            return Err(MerkleError::InvalidProof);
+        }
+        let bitmap_len = depth.div_ceil(8);
+        let mut out = vec![0u8; 1 + bitmap_len + depth * 32];
+        out[0] = depth as u8;
+
+        for (i, element) in self.elements.iter().enumerate() {
+            if element.position == Position::Left {
+                out[1 + i / 8] |= 1 << (i % 8);
+            }
+            let start = 1 + bitmap_len + i * 32;
+            hex::decode_to_slice(&element.hash, &mut out[start..start + 32])?;
+        }
+
+        Ok(out)
+    }
+
+    /// Decode a proof written by [`Proof::to_compact`]
+    ///
+    /// Fails with `InvalidProof` unless `bytes` holds exactly a `depth`-level
+    /// proof. The leaf index is recovered from the position bitmap.
+    pub fn from_compact(bytes: &[u8], depth: usize) -> Result<Proof, MerkleError> {
+        let bitmap_len = depth.div_ceil(8);
+        if depth > u8::MAX as usize
+            || bytes.len() != 1 + bitmap_len + depth * 32
+            || bytes[0] as usize != depth
+        {
+            return Err(MerkleError::InvalidProof);
+        }
+
+        let (bitmap, siblings) = bytes[1..].split_at(bitmap_len);
+        let mut index = 0;
+        let elements = siblings
+            .chunks_exact(32)
+            .enumerate()
+            .map(|(i, sibling)| {
+                let position = if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                    index |= 1 << i;
+                    Position::Left
+                } else {
+                    Position::Right
+                };
+                ProofElement {
+                    hash: hex::encode(sibling),
+                    position,
+                }
+            })
+            .collect();
+
+        Ok(Proof { index, elements })
+    }
+}
+
+/// Proof together with the leaf it proves, for verifiers without the tree
+///
+/// Carries the raw leaf `data`, the precomputed `leaf_hash`, or both. When
+/// both are present the hash is recomputed from the data and cross-checked.
+/// Bundles from a namespaced tree carry the hex-encoded `namespace` so the
+/// data can be rehashed the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofBundle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_hash: Option<String>,
+    pub proof: Vec<ProofElement>,
+    pub root: String,
+}
+
+impl ProofBundle {
+    /// Verify the bundle's proof against its root
+    ///
+    /// Fails with `InvalidProof` if the bundle carries neither data nor a
+    /// leaf hash, or a leaf hash that isn't 32 bytes; returns `false` if
+    /// they disagree. A hash-only bundle must carry a non-empty proof:
+    /// with no sibling to fold, it would only show that its leaf hash
+    /// equals the root, which any root satisfies. Only the data shows the
+    /// tree has a single leaf.
+    pub fn verify(&self) -> Result<bool, MerkleError> {
+        let namespace = match &self.namespace {
+            Some(namespace) => hex::decode(namespace)?,
+            None => Vec::new(),
+        };
+
+        let leaf_hash = match (&self.data, &self.leaf_hash) {
+            (Some(data), Some(leaf_hash)) => {
+                let computed = hash_leaf_namespaced(&namespace, data.as_bytes());
+                if hex::decode(leaf_hash)? != computed {
+                    return Ok(false);
+                }
+                computed.to_vec()
+            }
+            (Some(data), None) => hash_leaf_namespaced(&namespace, data.as_bytes()).to_vec(),
+            (None, Some(leaf_hash)) => {
+                let leaf_hash = hex::decode(leaf_hash)?;
+                if leaf_hash.len() != 32 || self.proof.is_empty() {
+                    return Err(MerkleError::InvalidProof);
+                }
+                leaf_hash
+            }
+            (None, None) => return Err(MerkleError::InvalidProof),
+        };
+
+        let computed = fold_proof(leaf_hash, &self.proof)?;
+        Ok(root_matches(&computed, &self.root))
+    }
+}
+
 /// Leaf information for manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeafInfo {
@@ -79,32 +318,173 @@ pub struct LeafInfo {
     pub pointer: String,
     pub platform_id: String,
     pub timestamp: i64,
+    /// Precomputed BLAKE3 leaf hash (hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_hash: Option<String>,
 }
 
 /// Manifest for IPFS storage
+///
+/// Manifests of a namespaced tree carry the hex-encoded `namespace`, which
+/// prefixes every leaf hash; it is absent for the default empty namespace.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     pub root: String,
     pub total_leaves: usize,
     pub leaves: Vec<LeafInfo>,
-    pub proofs: HashMap<String, Vec<ProofElement>>,
+    /// Proofs keyed by leaf index; ordered so serialization is byte-reproducible
+    pub proofs: BTreeMap<String, Vec<ProofElement>>,
+    /// DNA algorithm that produced the leaves' `dna_hex`, e.g. `"dHash+Grid/v1"`
+    #[serde(default = "unknown_dna_algorithm")]
+    pub algorithm: String,
+}
+
+fn unknown_dna_algorithm() -> String {
+    UNKNOWN_DNA_ALGORITHM.to_string()
+}
+
+impl Manifest {
+    /// Serialize the manifest as canonical JSON with object keys sorted
+    pub fn to_canonical_json(&self) -> Result<Vec<u8>, MerkleError> {
+        // serde_json::Value stores objects in a BTreeMap, so re-serializing
+        // through it sorts every key regardless of struct field order
+        let value =
+            serde_json::to_value(self).map_err(|e| MerkleError::Serialization(e.to_string()))?;
+        serde_json::to_vec(&value).map_err(|e| MerkleError::Serialization(e.to_string()))
+    }
+
+    /// Compute the manifest's CIDv1 locally, without an IPFS daemon
+    ///
+    /// The canonical JSON is addressed as a single raw block hashed with
+    /// SHA2-256, matching `ipfs add --cid-version=1 --raw-leaves` for
+    /// manifests under the default chunk size. Returned in base32.
+    pub fn compute_cid(&self) -> Result<String, MerkleError> {
+        let digest = Sha256::digest(self.to_canonical_json()?);
+        let multihash = Multihash::<64>::wrap(SHA2_256_CODE, &digest)
+            .map_err(|e| MerkleError::Serialization(e.to_string()))?;
+        Ok(Cid::new_v1(RAW_CODEC, multihash).to_string())
+    }
+
+    /// Decode the manifest's namespace; the empty namespace if none is set
+    fn namespace_bytes(&self) -> Result<Vec<u8>, MerkleError> {
+        Ok(match &self.namespace {
+            Some(namespace) => hex::decode(namespace)?,
+            None => Vec::new(),
+        })
+    }
+
+    /// Verify leaf `index`'s stored proof against the root and return the leaf
+    ///
+    /// The leaf hash is always recomputed from the leaf's fields under the
+    /// manifest's namespace, and only that hash is checked against the root.
+    /// A stored `leaf_hash` is compared case-insensitively and rejected with
+    /// `LeafHashMismatch` if it differs. Returns `LeafIndexOutOfRange` for an
+    /// unknown index and `InvalidProof` if the proof is missing or does not
+    /// reach the root.
+    pub fn verify_and_get_leaf(&self, index: usize) -> Result<&LeafInfo, MerkleError> {
+        let leaf = self
+            .leaves
+            .get(index)
+            .ok_or(MerkleError::LeafIndexOutOfRange(index))?;
+        let proof = self
+            .proofs
+            .get(&index.to_string())
+            .ok_or(MerkleError::InvalidProof)?;
+
+        let computed = hash_leaf_namespaced(
+            &self.namespace_bytes()?,
+            &encode_leaf(&leaf.dna_hex, &leaf.pointer, &leaf.platform_id, leaf.timestamp),
+        );
+        if let Some(stored) = &leaf.leaf_hash {
+            if hex::decode(stored)? != computed {
+                return Err(MerkleError::LeafHashMismatch(index));
+            }
+        }
+
+        if root_matches(&fold_proof(computed.to_vec(), proof)?, &self.root) {
+            Ok(leaf)
+        } else {
+            Err(MerkleError::InvalidProof)
+        }
+    }
 }
 
 /// Balanced binary Merkle tree with BLAKE3 hashing
 pub struct MerkleTree {
+    namespace: Vec<u8>,
     leaves: Vec<Vec<u8>>,
     root: Option<MerkleNode>,
     leaf_map: HashMap<Vec<u8>, usize>,
+    /// Earliest timestamp accepted by `try_add_leaf`; `None` disables checks
+    min_timestamp: Option<i64>,
+    /// DNA algorithm recorded in exported manifests
+    algorithm: String,
+    /// Edition mode of each DNA with edition leaves, keyed by DNA hex
+    edition_modes: HashMap<String, EditionMode>,
 }
 
 impl MerkleTree {
     /// Create new empty Merkle tree
     pub fn new() -> Self {
+        Self::with_namespace(&[])
+    }
+
+    /// Create new empty Merkle tree whose leaf hashes are namespaced
+    ///
+    /// Every leaf hash input is prefixed with `namespace`, so the same
+    /// registration in two registries yields distinct leaves and roots.
+    pub fn with_namespace(namespace: &[u8]) -> Self {
         Self {
+            namespace: namespace.to_vec(),
             leaves: Vec::new(),
             root: None,
             leaf_map: HashMap::new(),
+            min_timestamp: None,
+            algorithm: DEFAULT_DNA_ALGORITHM.to_string(),
+            edition_modes: HashMap::new(),
+        }
+    }
+
+    /// Enable timestamp validation in [`MerkleTree::try_add_leaf`]
+    ///
+    /// Timestamps before `min_timestamp` or more than
+    /// [`MAX_TIMESTAMP_SKEW_SECS`] in the future are rejected.
+    pub fn with_strict_timestamps(mut self, min_timestamp: i64) -> Self {
+        self.min_timestamp = Some(min_timestamp);
+        self
+    }
+
+    /// Set the DNA algorithm recorded by [`MerkleTree::export_manifest`]
+    ///
+    /// Defaults to [`DEFAULT_DNA_ALGORITHM`].
+    pub fn with_algorithm(mut self, algorithm: &str) -> Self {
+        self.algorithm = algorithm.to_string();
+        self
+    }
+
+    /// Add registration leaf, validating its timestamp in strict mode
+    ///
+    /// Returns `InvalidTimestamp` if the tree was created with
+    /// [`MerkleTree::with_strict_timestamps`] and `timestamp` is out of
+    /// range. `add_leaf` never validates.
+    pub fn try_add_leaf(
+        &mut self,
+        dna_hex: &str,
+        pointer: &str,
+        platform_id: &str,
+        timestamp: Option<i64>,
+    ) -> Result<(), MerkleError> {
+        if let (Some(min_timestamp), Some(ts)) = (self.min_timestamp, timestamp) {
+            let max_timestamp = chrono::Utc::now().timestamp() + MAX_TIMESTAMP_SKEW_SECS;
+            if ts < min_timestamp || ts > max_timestamp {
+                return Err(MerkleError::InvalidTimestamp(ts));
+            }
         }
+
+        self.add_leaf(dna_hex, pointer, platform_id, timestamp);
+        Ok(())
     }
 
     /// Add registration leaf to tree
@@ -120,26 +500,90 @@ impl MerkleTree {
         let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp());
         
         // Construct leaf data
-        let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-        let leaf_bytes = leaf_data.as_bytes().to_vec();
+        let leaf_bytes = encode_leaf(dna_hex, pointer, platform_id, timestamp);
         
         // Store leaf
         self.leaf_map.insert(leaf_bytes.clone(), self.leaves.len());
         self.leaves.push(leaf_bytes);
+
+        // Any previous build no longer reflects the leaves
+        self.root = None;
+    }
+
+    /// Add edition leaf to tree
+    ///
+    /// Leaf = BLAKE3(dna_hex#chain#contract_hex#token_id#edition_no), the
+    /// universal edition key. Edition trees should not set a namespace.
+    pub fn add_edition_leaf(
+        &mut self,
+        dna_hex: &str,
+        chain: &str,
+        contract_hex: &str,
+        token_id: &str,
+        edition_no: u32,
+    ) {
+        let leaf_bytes = encode_edition_leaf(dna_hex, chain, contract_hex, token_id, edition_no);
+
+        self.edition_modes
+            .entry(dna_hex.to_string())
+            .or_insert(EditionMode::Serial);
+        self.leaf_map.insert(leaf_bytes.clone(), self.leaves.len());
+        self.leaves.push(leaf_bytes);
+        self.root = None;
+    }
+
+    /// Add edition leaf, enforcing the DNA's edition policy
+    ///
+    /// Returns `EditionPolicyViolation` if the DNA already has an edition
+    /// leaf and either that DNA or the new leaf is `Strict1To1`. The first
+    /// leaf for a DNA sets its mode. `add_edition_leaf` never validates and
+    /// records untracked DNAs as `Serial`.
+    pub fn try_add_edition_leaf(
+        &mut self,
+        dna_hex: &str,
+        chain: &str,
+        contract_hex: &str,
+        token_id: &str,
+        edition_no: u32,
+        mode: EditionMode,
+    ) -> Result<(), MerkleError> {
+        self.check_edition_policy(dna_hex, mode)?;
+        self.edition_modes.insert(dna_hex.to_string(), mode);
+        self.add_edition_leaf(dna_hex, chain, contract_hex, token_id, edition_no);
+        Ok(())
+    }
+
+    /// `EditionPolicyViolation` if another `mode` edition of `dna_hex` can't be added
+    fn check_edition_policy(&self, dna_hex: &str, mode: EditionMode) -> Result<(), MerkleError> {
+        if let Some(&existing) = self.edition_modes.get(dna_hex) {
+            if existing == EditionMode::Strict1To1 || mode == EditionMode::Strict1To1 {
+                return Err(MerkleError::EditionPolicyViolation(dna_hex.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Edition mode recorded for a DNA, if it has edition leaves
+    pub fn edition_mode(&self, dna_hex: &str) -> Option<EditionMode> {
+        self.edition_modes.get(dna_hex).copied()
     }
 
     /// Construct balanced binary Merkle tree from leaves
+    ///
+    /// The last node of an odd level is paired with itself. A single-leaf
+    /// tree has no levels to pair, so its root is the leaf hash and its
+    /// proof is empty. Returns `EmptyTree` if no leaves have been added.
     pub fn build_tree(&mut self) -> Result<String, MerkleError> {
         if self.leaves.is_empty() {
             self.root = None;
-            return Ok(String::new());
+            return Err(MerkleError::EmptyTree);
         }
 
         // Create leaf nodes
         let mut nodes: Vec<MerkleNode> = self
             .leaves
             .iter()
-            .map(|leaf| MerkleNode::new_leaf(leaf.clone()))
+            .map(|leaf| MerkleNode::new_leaf(leaf.clone(), &self.namespace))
             .collect();
 
         // Build tree bottom-up
@@ -164,6 +608,38 @@ impl MerkleTree {
         Ok(hex::encode(nodes[0].hash))
     }
 
+    /// Build the tree and check that its root equals `expected_root` (hex)
+    ///
+    /// Returns `RootMismatch` if it differs. The tree stays built either
+    /// way, so the actual root can still be read with `get_root`.
+    pub fn build_tree_expecting(&mut self, expected_root: &str) -> Result<(), MerkleError> {
+        let root = self.build_tree()?;
+
+        if !root_matches(&root, expected_root) {
+            return Err(MerkleError::RootMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Add a leaf, rebuild, and return its index, the new root and its proof
+    ///
+    /// Convenience for incremental registration, where each new leaf's
+    /// proof is needed immediately after it is committed.
+    pub fn add_and_prove(
+        &mut self,
+        dna_hex: &str,
+        pointer: &str,
+        platform_id: &str,
+        timestamp: Option<i64>,
+    ) -> Result<(usize, String, Vec<ProofElement>), MerkleError> {
+        self.add_leaf(dna_hex, pointer, platform_id, timestamp);
+        let index = self.leaves.len() - 1;
+        let root = self.build_tree()?;
+        let proof = self.get_proof(index)?;
+        Ok((index, root, proof))
+    }
+
     /// Get Merkle root hash
     pub fn get_root(&self) -> Result<String, MerkleError> {
         self.root
@@ -188,7 +664,7 @@ impl MerkleTree {
         let mut nodes: Vec<MerkleNode> = self
             .leaves
             .iter()
-            .map(|leaf| MerkleNode::new_leaf(leaf.clone()))
+            .map(|leaf| MerkleNode::new_leaf(leaf.clone(), &self.namespace))
             .collect();
         
         let mut current_index = leaf_index;
@@ -234,38 +710,100 @@ impl MerkleTree {
         Ok(proof)
     }
 
+    /// Side of its parent that the leaf's path takes at each level, leaf first
+    ///
+    /// Entry `i` is where the node holding leaf `index` sits at level `i`, so
+    /// it is always the opposite of the `position` of the matching element
+    /// of [`MerkleTree::get_proof`], which describes the sibling. The last
+    /// node of an odd level pairs with its own duplicate and counts as left.
+    #[allow(clippy::manual_is_multiple_of)] // usize::is_multiple_of needs Rust 1.87
+    pub fn leaf_directions(&self, index: usize) -> Result<Vec<Position>, MerkleError> {
+        if index >= self.leaves.len() {
+            return Err(MerkleError::LeafIndexOutOfRange(index));
+        }
+
+        Ok((0..self.depth())
+            .map(|level| {
+                if (index >> level) % 2 == 0 {
+                    Position::Left
+                } else {
+                    Position::Right
+                }
+            })
+            .collect())
+    }
+
+    /// Generate Merkle proof for leaf at given index, keeping the index
+    pub fn get_proof_indexed(&self, leaf_index: usize) -> Result<Proof, MerkleError> {
+        Ok(Proof {
+            index: leaf_index,
+            elements: self.get_proof(leaf_index)?,
+        })
+    }
+
+    /// Generate a self-contained proof bundle for leaf at given index
+    ///
+    /// The bundle always carries the leaf hash; `include_data` adds the raw
+    /// leaf data so verifiers can recompute and cross-check it.
+    pub fn get_proof_bundle(
+        &self,
+        leaf_index: usize,
+        include_data: bool,
+    ) -> Result<ProofBundle, MerkleError> {
+        let proof = self.get_proof(leaf_index)?;
+        let leaf = &self.leaves[leaf_index];
+
+        Ok(ProofBundle {
+            namespace: (!self.namespace.is_empty()).then(|| hex::encode(&self.namespace)),
+            data: include_data.then(|| String::from_utf8_lossy(leaf).into_owned()),
+            leaf_hash: Some(hex::encode(hash_leaf_namespaced(&self.namespace, leaf))),
+            proof,
+            root: self.get_root()?,
+        })
+    }
+
     /// Verify Merkle proof for a leaf
+    ///
+    /// Returns `InvalidProof` if the proof length doesn't match
+    /// [`MerkleTree::depth`]; an empty proof is only accepted for a
+    /// single-leaf tree.
     pub fn verify_proof(
         &self,
         leaf_data: &[u8],
         proof: &[ProofElement],
         root_hash: &str,
     ) -> Result<bool, MerkleError> {
-        // Compute leaf hash
-        let mut current_hash = blake3::hash(leaf_data).as_bytes().to_vec();
-
-        // Traverse proof path
-        for proof_element in proof {
-            let sibling_hash = hex::decode(&proof_element.hash)
-                .map_err(|_| MerkleError::InvalidProof)?;
-
-            let mut combined = Vec::with_capacity(64);
-            match proof_element.position {
-                Position::Left => {
-                    combined.extend_from_slice(&sibling_hash);
-                    combined.extend_from_slice(&current_hash);
-                }
-                Position::Right => {
-                    combined.extend_from_slice(&current_hash);
-                    combined.extend_from_slice(&sibling_hash);
-                }
-            }
+        // Compare with expected root
+        let computed = hex::encode(self.compute_proof_root(leaf_data, proof)?);
+        Ok(root_matches(&computed, root_hash))
+    }
+
+    /// Verify Merkle proof for a leaf against a raw 32-byte root
+    ///
+    /// Same as [`MerkleTree::verify_proof`] but for callers that hold the
+    /// root as bytes (e.g. read from an on-chain account), avoiding a hex
+    /// round-trip.
+    pub fn verify_proof_bytes(
+        &self,
+        leaf_data: &[u8],
+        proof: &[ProofElement],
+        root: &[u8; 32],
+    ) -> Result<bool, MerkleError> {
+        Ok(self.compute_proof_root(leaf_data, proof)? == *root)
+    }
 
-            current_hash = blake3::hash(&combined).as_bytes().to_vec();
+    /// Fold a proof path from a leaf up to the root it implies
+    fn compute_proof_root(
+        &self,
+        leaf_data: &[u8],
+        proof: &[ProofElement],
+    ) -> Result<[u8; 32], MerkleError> {
+        if proof.len() != self.depth() {
+            return Err(MerkleError::InvalidProof);
         }
 
-        // Compare with expected root
-        Ok(hex::encode(current_hash) == root_hash)
+        let leaf_hash = hash_leaf_namespaced(&self.namespace, leaf_data);
+        Ok(fold_raw(leaf_hash, &proof_to_raw(proof)?))
     }
 
     /// Export tree manifest for IPFS storage
@@ -276,40 +814,58 @@ impl MerkleTree {
 
         let root = self.get_root()?;
         let mut leaves = Vec::new();
-        let mut proofs = HashMap::new();
+        let mut proofs = BTreeMap::new();
 
-        // Export leaves
+        // Export leaves; anything that doesn't decode back into the four
+        // registration fields would produce a corrupt LeafInfo
         for (i, leaf_data) in self.leaves.iter().enumerate() {
-            let leaf_str = String::from_utf8_lossy(leaf_data);
+            let leaf_str = std::str::from_utf8(leaf_data).map_err(|_| MerkleError::NonTextLeaf(i))?;
             let parts: Vec<&str> = leaf_str.split('|').collect();
+            let [dna_hex, pointer, platform_id, timestamp] = parts[..] else {
+                return Err(MerkleError::NonTextLeaf(i));
+            };
+            let timestamp = timestamp.parse().map_err(|_| MerkleError::NonTextLeaf(i))?;
 
-            if parts.len() >= 4 {
-                leaves.push(LeafInfo {
-                    index: i,
-                    dna_hex: parts[0].to_string(),
-                    pointer: parts[1].to_string(),
-                    platform_id: parts[2].to_string(),
-                    timestamp: parts[3].parse().unwrap_or(0),
-                });
-
-                // Generate proof for each leaf
-                let proof = self.get_proof(i)?;
-                proofs.insert(i.to_string(), proof);
-            }
+            leaves.push(LeafInfo {
+                index: i,
+                dna_hex: dna_hex.to_string(),
+                pointer: pointer.to_string(),
+                platform_id: platform_id.to_string(),
+                timestamp,
+                leaf_hash: Some(hex::encode(hash_leaf_namespaced(&self.namespace, leaf_data))),
+            });
+
+            // Generate proof for each leaf
+            let proof = self.get_proof(i)?;
+            proofs.insert(i.to_string(), proof);
         }
 
         Ok(Manifest {
+            namespace: (!self.namespace.is_empty()).then(|| hex::encode(&self.namespace)),
             root,
             total_leaves: self.leaves.len(),
             leaves,
             proofs,
+            algorithm: self.algorithm.clone(),
         })
     }
 
+    /// Export tree manifest together with its locally computed CID
+    pub fn export_manifest_with_cid(&self) -> Result<(Manifest, String), MerkleError> {
+        let manifest = self.export_manifest()?;
+        let cid = manifest.compute_cid()?;
+        Ok((manifest, cid))
+    }
+
     /// Import tree from manifest
+    ///
+    /// A manifest without leaves is rejected with `EmptyTree`.
     pub fn import_manifest(&mut self, manifest: &Manifest) -> Result<(), MerkleError> {
+        self.namespace = manifest.namespace_bytes()?;
         self.leaves.clear();
         self.leaf_map.clear();
+        self.edition_modes.clear();
+        self.algorithm = manifest.algorithm.clone();
 
         // Import leaves
         for leaf in &manifest.leaves {
@@ -321,21 +877,136 @@ impl MerkleTree {
             );
         }
 
-        // Rebuild tree
-        let root = self.build_tree()?;
+        // Rebuild tree and verify root matches
+        self.build_tree_expecting(&manifest.root)
+    }
 
-        // Verify root matches
-        if root != manifest.root {
-            return Err(MerkleError::RootMismatch);
+    /// Encode a built tree as compact binary, without JSON or proofs
+    ///
+    /// Layout: [`TREE_MAGIC`], a format version byte, then the namespace,
+    /// the DNA algorithm and a `u32` leaf count followed by each leaf, every
+    /// variable-length field prefixed by its `u32` length (little-endian),
+    /// and finally the 32-byte root. Internal nodes are not stored;
+    /// [`MerkleTree::from_bytes`] rebuilds them.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MerkleError> {
+        let root = self.root.as_ref().ok_or(MerkleError::TreeNotBuilt)?;
+
+        let mut out = TREE_MAGIC.to_vec();
+        out.push(TREE_FORMAT_VERSION);
+        put_prefixed(&mut out, &self.namespace)?;
+        put_prefixed(&mut out, self.algorithm.as_bytes())?;
+        out.extend_from_slice(&encode_len(self.leaves.len())?);
+        for leaf in &self.leaves {
+            put_prefixed(&mut out, leaf)?;
         }
+        out.extend_from_slice(&root.hash);
 
-        Ok(())
+        Ok(out)
+    }
+
+    /// Decode and rebuild a tree written by [`MerkleTree::to_bytes`]
+    ///
+    /// Truncated or otherwise malformed input fails with `Serialization`;
+    /// a rebuilt root that differs from the stored one fails with
+    /// `RootMismatch`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let mut rest = bytes;
+        if take(&mut rest, TREE_MAGIC.len())? != TREE_MAGIC {
+            return Err(MerkleError::Serialization(
+                "not a binary Merkle tree".to_string(),
+            ));
+        }
+        let version = take(&mut rest, 1)?[0];
+        if version != TREE_FORMAT_VERSION {
+            return Err(MerkleError::Serialization(format!(
+                "unsupported tree format version {}",
+                version
+            )));
+        }
+
+        let namespace = take_prefixed(&mut rest)?;
+        let algorithm = std::str::from_utf8(take_prefixed(&mut rest)?)
+            .map_err(|e| MerkleError::Serialization(e.to_string()))?;
+        let mut tree = MerkleTree::with_namespace(namespace).with_algorithm(algorithm);
+
+        let leaf_count = decode_len(&mut rest)?;
+        for _ in 0..leaf_count {
+            let leaf = take_prefixed(&mut rest)?.to_vec();
+            tree.leaf_map.insert(leaf.clone(), tree.leaves.len());
+            tree.leaves.push(leaf);
+        }
+
+        let root = hex::encode(take(&mut rest, 32)?);
+        if !rest.is_empty() {
+            return Err(MerkleError::Serialization(format!(
+                "{} trailing bytes after tree",
+                rest.len()
+            )));
+        }
+
+        tree.build_tree_expecting(&root)?;
+        Ok(tree)
+    }
+
+    /// Merge two trees into a new, unbuilt tree
+    ///
+    /// Leaves of `self` keep their indices; leaves of `other` are appended,
+    /// so a leaf at index `i` in `other` moves to `self.leaf_count() + i`.
+    /// Proofs from either source tree are invalid against the merged root
+    /// and must be regenerated after `build_tree`. Both trees must share a
+    /// namespace.
+    ///
+    /// Each edition leaf of `other` goes through the same policy check as
+    /// [`MerkleTree::try_add_edition_leaf`], so the merge fails with
+    /// `EditionPolicyViolation` if it would give a `Strict1To1` DNA a
+    /// second edition.
+    pub fn merge(&self, other: &MerkleTree) -> Result<MerkleTree, MerkleError> {
+        if self.namespace != other.namespace {
+            return Err(MerkleError::NamespaceMismatch);
+        }
+
+        let mut merged = MerkleTree::with_namespace(&self.namespace);
+        merged.min_timestamp = self.min_timestamp;
+        merged.algorithm = self.algorithm.clone();
+        merged.edition_modes = self.edition_modes.clone();
+
+        for leaf in &self.leaves {
+            merged.leaf_map.insert(leaf.clone(), merged.leaves.len());
+            merged.leaves.push(leaf.clone());
+        }
+        for leaf in &other.leaves {
+            let edition = edition_leaf_dna(leaf)
+                .and_then(|dna| Some((dna, *other.edition_modes.get(dna)?)));
+            if let Some((dna, mode)) = edition {
+                merged.check_edition_policy(dna, mode)?;
+                merged.edition_modes.insert(dna.to_string(), mode);
+            }
+            merged.leaf_map.insert(leaf.clone(), merged.leaves.len());
+            merged.leaves.push(leaf.clone());
+        }
+
+        Ok(merged)
     }
 
     /// Get number of leaves
     pub fn leaf_count(&self) -> usize {
         self.leaves.len()
     }
+
+    /// Check whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Number of levels above the leaves, i.e. the length of every proof
+    pub fn depth(&self) -> usize {
+        self.leaves.len().next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Check whether the tree is built and up to date with its leaves
+    pub fn is_built(&self) -> bool {
+        self.root.is_some()
+    }
 }
 
 impl Default for MerkleTree {
@@ -344,6 +1015,54 @@ impl Default for MerkleTree {
     }
 }
 
+/// DNA hex of an edition leaf (`dna#chain#contract#token_id#edition_no`)
+///
+/// `None` for registration leaves and anything else without five
+/// `#`-separated fields.
+fn edition_leaf_dna(leaf: &[u8]) -> Option<&str> {
+    let leaf = std::str::from_utf8(leaf).ok()?;
+    let fields: Vec<&str> = leaf.split('#').collect();
+    (fields.len() == 5).then_some(fields[0])
+}
+
+/// Length as the `u32` little-endian prefix used by [`MerkleTree::to_bytes`]
+fn encode_len(len: usize) -> Result<[u8; 4], MerkleError> {
+    u32::try_from(len)
+        .map(u32::to_le_bytes)
+        .map_err(|_| MerkleError::Serialization(format!("field of {} bytes is too long", len)))
+}
+
+/// Append `bytes` with its length prefix
+fn put_prefixed(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), MerkleError> {
+    out.extend_from_slice(&encode_len(bytes.len())?);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Split the next `n` bytes off `rest`
+fn take<'a>(rest: &mut &'a [u8], n: usize) -> Result<&'a [u8], MerkleError> {
+    if rest.len() < n {
+        return Err(MerkleError::Serialization(
+            "binary tree is truncated".to_string(),
+        ));
+    }
+    let (head, tail) = rest.split_at(n);
+    *rest = tail;
+    Ok(head)
+}
+
+/// Read a `u32` little-endian length prefix
+fn decode_len(rest: &mut &[u8]) -> Result<usize, MerkleError> {
+    let bytes = take(rest, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Split off one length-prefixed field
+fn take_prefixed<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8], MerkleError> {
+    let len = decode_len(rest)?;
+    take(rest, len)
+}
+
 /// Compute leaf hash for DNA registration
 pub fn compute_leaf_hash(
     dna_hex: &str,
@@ -352,11 +1071,27 @@ pub fn compute_leaf_hash(
     timestamp: Option<i64>,
 ) -> String {
     let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp());
-    let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-    hex::encode(blake3::hash(leaf_data.as_bytes()).as_bytes())
+    hex::encode(hash_leaf(&encode_leaf(dna_hex, pointer, platform_id, timestamp)))
+}
+
+/// Compute leaf hash for DNA registration in a namespaced tree
+pub fn compute_leaf_hash_namespaced(
+    namespace: &[u8],
+    dna_hex: &str,
+    pointer: &str,
+    platform_id: &str,
+    timestamp: Option<i64>,
+) -> String {
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    hex::encode(hash_leaf_namespaced(
+        namespace,
+        &encode_leaf(dna_hex, pointer, platform_id, timestamp),
+    ))
 }
 
 /// Standalone proof verification without tree instance
+///
+/// Equivalent to [`verify_registration`].
 pub fn verify_proof_standalone(
     dna_hex: &str,
     pointer: &str,
@@ -365,12 +1100,40 @@ pub fn verify_proof_standalone(
     proof: &[ProofElement],
     root_hash: &str,
 ) -> Result<bool, MerkleError> {
-    let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-    let mut current_hash = blake3::hash(leaf_data.as_bytes()).as_bytes().to_vec();
+    verify_registration(dna_hex, pointer, platform_id, timestamp, proof, root_hash)
+}
+
+/// Verify a registration against a hex root using only its fields and proof
+///
+/// The leaf is encoded with [`leaf_encoding::encode_leaf`]; each proof
+/// element's `position` alone decides the concatenation order.
+pub fn verify_registration(
+    dna_hex: &str,
+    pointer: &str,
+    platform_id: &str,
+    timestamp: i64,
+    proof: &[ProofElement],
+    root_hash: &str,
+) -> Result<bool, MerkleError> {
+    let leaf_hash = hash_leaf(&encode_leaf(dna_hex, pointer, platform_id, timestamp));
+    let computed = fold_proof(leaf_hash.to_vec(), proof)?;
+    Ok(root_matches(&computed, root_hash))
+}
+
+/// Compare a computed (lowercase) hex root with a caller-supplied one
+///
+/// Roots from other tools may use upper or mixed case; the caller's root is
+/// normalized to lowercase before comparing.
+fn root_matches(computed: &str, expected: &str) -> bool {
+    computed == expected.to_ascii_lowercase()
+}
+
+/// Hash a leaf hash up through a position-ordered proof, returning the hex root
+fn fold_proof(leaf_hash: Vec<u8>, proof: &[ProofElement]) -> Result<String, MerkleError> {
+    let mut current_hash = leaf_hash;
 
     for proof_element in proof {
-        let sibling_hash = hex::decode(&proof_element.hash)
-            .map_err(|_| MerkleError::InvalidProof)?;
+        let sibling_hash = hex::decode(&proof_element.hash)?;
 
         let mut combined = Vec::with_capacity(64);
         match proof_element.position {
@@ -387,12 +1150,103 @@ pub fn verify_proof_standalone(
         current_hash = blake3::hash(&combined).as_bytes().to_vec();
     }
 
-    Ok(hex::encode(current_hash) == root_hash)
+    Ok(hex::encode(current_hash))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Decode every sibling hash of a proof, for use with [`verify_raw`]
+pub fn proof_to_raw(proof: &[ProofElement]) -> Result<Vec<ProofElementRaw>, MerkleError> {
+    proof.iter().map(ProofElementRaw::try_from).collect()
+}
+
+/// Verify a decoded proof for `leaf_hash` against `root`
+///
+/// Allocation-free counterpart of [`verify_proof_standalone`] for hot
+/// loops; order at each level comes from the stored positions.
+pub fn verify_raw(leaf_hash: &[u8; 32], proof: &[ProofElementRaw], root: &[u8; 32]) -> bool {
+    fold_raw(*leaf_hash, proof) == *root
+}
+
+/// Fold decoded siblings into a leaf hash, producing the root
+fn fold_raw(leaf_hash: [u8; 32], proof: &[ProofElementRaw]) -> [u8; 32] {
+    proof.iter().fold(leaf_hash, |current, element| {
+        let mut hasher = blake3::Hasher::new();
+        match element.position {
+            Position::Left => hasher.update(&element.hash).update(&current),
+            Position::Right => hasher.update(&current).update(&element.hash),
+        };
+        hasher.finalize().into()
+    })
+}
+
+/// Verify an indexed proof against a hex leaf hash
+///
+/// Concatenation order at each level is taken from the leaf index rather
+/// than the stored positions: an even index means the sibling is on the right.
+#[allow(clippy::manual_is_multiple_of)] // usize::is_multiple_of needs Rust 1.87
+pub fn verify_indexed(leaf_hash: &str, proof: &Proof, root_hash: &str) -> Result<bool, MerkleError> {
+    let mut current_hash = hex::decode(leaf_hash)?;
+    let mut index = proof.index;
+
+    for proof_element in &proof.elements {
+        let sibling_hash = hex::decode(&proof_element.hash)?;
+
+        let mut combined = Vec::with_capacity(64);
+        if index % 2 == 0 {
+            combined.extend_from_slice(&current_hash);
+            combined.extend_from_slice(&sibling_hash);
+        } else {
+            combined.extend_from_slice(&sibling_hash);
+            combined.extend_from_slice(&current_hash);
+        }
+
+        current_hash = blake3::hash(&combined).as_bytes().to_vec();
+        index /= 2;
+    }
+
+    Ok(root_matches(&hex::encode(current_hash), root_hash))
+}
+
+/// Verify a stream of NDJSON proof bundles, one [`ProofBundle`] per line
+///
+/// Writes one `{"index": n, "valid": bool}` line to `w` per input line,
+/// where `index` is the 0-based line number; blank lines are skipped. Lines
+/// that don't parse or verify are reported as invalid rather than aborting
+/// the run. Returns `(total, valid_count)`; only I/O errors fail.
+pub fn verify_bundles_stream<R: BufRead, W: Write>(
+    r: R,
+    mut w: W,
+) -> Result<(usize, usize), MerkleError> {
+    let (mut total, mut valid_count) = (0, 0);
+
+    for (index, line) in r.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let valid = serde_json::from_str::<ProofBundle>(&line)
+            .ok()
+            .and_then(|bundle| bundle.verify().ok())
+            .unwrap_or(false);
+
+        total += 1;
+        if valid {
+            valid_count += 1;
+        }
+        writeln!(
+            w,
+            "{}",
+            serde_json::json!({ "index": index, "valid": valid })
+        )?;
+    }
+
+    w.flush()?;
+    Ok((total, valid_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_merkle_tree_basic() {
@@ -431,4 +1285,900 @@ mod tests {
         let is_valid = tree.verify_proof(leaf_data, &proof, &root).unwrap();
         assert!(is_valid);
     }
+
+    #[test]
+    fn test_is_built() {
+        let mut tree = MerkleTree::new();
+        assert!(tree.is_empty());
+        assert!(!tree.is_built());
+
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        assert!(!tree.is_empty());
+        assert!(!tree.is_built());
+
+        tree.build_tree().unwrap();
+        assert!(tree.is_built());
+
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        assert!(!tree.is_built());
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+    }
+
+    #[test]
+    fn test_verify_indexed_odd_leaf() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+
+        // Leaf 4 is paired with its own duplicate on the first level
+        let mut proof = tree.get_proof_indexed(4).unwrap();
+        assert_eq!(proof.index, 4);
+        let leaf_hash = compute_leaf_hash("dna4", "ptr4", "platform", Some(1004));
+        assert!(verify_indexed(&leaf_hash, &proof, &root).unwrap());
+
+        // Direction comes from the index, so mislabelled positions that break
+        // position-based verification do not affect the indexed check
+        for element in &mut proof.elements {
+            element.position = Position::Right;
+        }
+        assert!(!verify_proof_standalone(
+            "dna4",
+            "ptr4",
+            "platform",
+            1004,
+            &proof.elements,
+            &root
+        )
+        .unwrap());
+        assert!(verify_indexed(&leaf_hash, &proof, &root).unwrap());
+
+        // A wrong index is rejected
+        proof.index = 3;
+        assert!(!verify_indexed(&leaf_hash, &proof, &root).unwrap());
+    }
+
+    #[test]
+    fn test_compact_proof_roundtrip() {
+        let mut tree = MerkleTree::new();
+        for i in 0..1000 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+        assert_eq!(tree.depth(), 10);
+
+        let proof = tree.get_proof_indexed(777).unwrap();
+        let compact = proof.to_compact().unwrap();
+        assert_eq!(compact.len(), 1 + 10 * 32 + 10usize.div_ceil(8));
+
+        let decoded = Proof::from_compact(&compact, 10).unwrap();
+        assert_eq!(decoded.index, 777);
+        assert_eq!(decoded.elements.len(), proof.elements.len());
+        for (a, b) in decoded.elements.iter().zip(&proof.elements) {
+            assert_eq!(a.hash, b.hash);
+            assert_eq!(a.position, b.position);
+        }
+        let leaf_hash = compute_leaf_hash("dna777", "ptr777", "platform", Some(1777));
+        assert!(verify_indexed(&leaf_hash, &decoded, &root).unwrap());
+
+        assert!(Proof::from_compact(&compact, 9).is_err());
+        assert!(Proof::from_compact(&compact[..compact.len() - 1], 10).is_err());
+    }
+
+    #[test]
+    fn test_compact_proof_rejects_unencodable() {
+        let element = |hash: String| ProofElement {
+            hash,
+            position: Position::Right,
+        };
+
+        // 256 levels would wrap the depth byte to 0
+        let deep = Proof {
+            index: 0,
+            elements: vec![element("ab".repeat(32)); 256],
+        };
+        assert!(matches!(deep.to_compact(), Err(MerkleError::InvalidProof)));
+        let max = Proof {
+            index: 0,
+            elements: vec![element("ab".repeat(32)); 255],
+        };
+        assert_eq!(max.to_compact().unwrap()[0], 255);
+
+        for hash in ["zz".repeat(32), "ab".repeat(16)] {
+            let bad = Proof {
+                index: 0,
+                elements: vec![element(hash)],
+            };
+            assert!(matches!(bad.to_compact(), Err(MerkleError::InvalidHex(_))));
+        }
+    }
+
+    #[test]
+    fn test_strict_timestamps() {
+        let now = chrono::Utc::now().timestamp();
+
+        // Off by default
+        let mut tree = MerkleTree::new();
+        assert!(tree.try_add_leaf("dna0", "ptr0", "platform", Some(-1)).is_ok());
+
+        let mut tree = MerkleTree::new().with_strict_timestamps(0);
+        assert!(matches!(
+            tree.try_add_leaf("dna1", "ptr1", "platform", Some(-1)),
+            Err(MerkleError::InvalidTimestamp(-1))
+        ));
+        let far_future = now + 365 * MAX_TIMESTAMP_SKEW_SECS;
+        assert!(matches!(
+            tree.try_add_leaf("dna2", "ptr2", "platform", Some(far_future)),
+            Err(MerkleError::InvalidTimestamp(_))
+        ));
+        assert_eq!(tree.leaf_count(), 0);
+
+        tree.try_add_leaf("dna3", "ptr3", "platform", Some(now)).unwrap();
+        tree.try_add_leaf("dna4", "ptr4", "platform", None).unwrap();
+        assert_eq!(tree.leaf_count(), 2);
+    }
+
+    #[test]
+    fn test_export_manifest_rejects_malformed_leaves() {
+        for (raw, bad_index) in [
+            (b"dna|ptr|platform".to_vec(), 2),
+            (b"dna|ptr|plat|form|1000".to_vec(), 2),
+            (b"dna|ptr|platform|soon".to_vec(), 2),
+            (vec![0xff, b'|', b'p', b'|', b'x', b'|', b'1'], 2),
+        ] {
+            let mut tree = MerkleTree::new();
+            tree.add_leaf("dna0", "ptr0", "platform", Some(1000));
+            tree.add_leaf("dna1", "ptr1", "platform", Some(1001));
+            tree.leaves.push(raw);
+            tree.build_tree().unwrap();
+
+            assert!(matches!(
+                tree.export_manifest(),
+                Err(MerkleError::NonTextLeaf(i)) if i == bad_index
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_raw() {
+        let mut tree = MerkleTree::new();
+        for i in 0..7 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+        let root_bytes: [u8; 32] = hex::decode(&root).unwrap().try_into().unwrap();
+
+        let proof = tree.get_proof(6).unwrap();
+        let raw = proof_to_raw(&proof).unwrap();
+        let leaf_hash = hash_leaf(&encode_leaf("dna6", "ptr6", "platform", 1006));
+        assert!(verify_raw(&leaf_hash, &raw, &root_bytes));
+        assert!(!verify_raw(&leaf_hash, &raw[1..], &root_bytes));
+
+        let mut bad = proof.clone();
+        bad[0].hash.truncate(10);
+        assert!(matches!(
+            proof_to_raw(&bad),
+            Err(MerkleError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_standalone_verification_odd_tree() {
+        let mut tree = MerkleTree::new();
+        for i in 0..7 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+
+        for i in 0..7 {
+            let proof = tree.get_proof(i as usize).unwrap();
+            assert!(
+                verify_proof_standalone(
+                    &format!("dna{}", i),
+                    &format!("ptr{}", i),
+                    "platform",
+                    1000 + i,
+                    &proof,
+                    &root
+                )
+                .unwrap(),
+                "leaf {} failed standalone verification",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_trees() {
+        let mut first = MerkleTree::new();
+        for i in 0..3 {
+            first.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform1", Some(1000 + i));
+        }
+        first.build_tree().unwrap();
+
+        let mut second = MerkleTree::new();
+        for i in 3..5 {
+            second.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform2", Some(1000 + i));
+        }
+
+        let mut merged = first.merge(&second).unwrap();
+        assert_eq!(merged.leaf_count(), 5);
+        assert!(!merged.is_built());
+
+        let root = merged.build_tree().unwrap();
+        assert_ne!(root, first.get_root().unwrap());
+
+        for i in 0..5 {
+            let platform = if i < 3 { "platform1" } else { "platform2" };
+            let leaf_data = format!("dna{}|ptr{}|{}|{}", i, i, platform, 1000 + i);
+            let proof = merged.get_proof(i as usize).unwrap();
+            assert!(merged.verify_proof(leaf_data.as_bytes(), &proof, &root).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_build_empty_tree() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.build_tree(), Err(MerkleError::EmptyTree)));
+        assert!(!tree.is_built());
+
+        let empty = Manifest {
+            namespace: None,
+            root: String::new(),
+            total_leaves: 0,
+            leaves: Vec::new(),
+            proofs: BTreeMap::new(),
+            algorithm: DEFAULT_DNA_ALGORITHM.to_string(),
+        };
+        assert!(matches!(tree.import_manifest(&empty), Err(MerkleError::EmptyTree)));
+    }
+
+    #[test]
+    fn test_manifest_serialization_is_deterministic() {
+        let mut tree = MerkleTree::new();
+        for i in 0..12 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        tree.build_tree().unwrap();
+
+        let first = serde_json::to_vec(&tree.export_manifest().unwrap()).unwrap();
+        let second = serde_json::to_vec(&tree.export_manifest().unwrap()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_unified_error_variants() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.build_tree(), Err(MerkleError::EmptyTree)));
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+
+        for i in 0..3 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        assert!(matches!(tree.get_proof(0), Err(MerkleError::TreeNotBuilt)));
+        assert!(matches!(
+            tree.build_tree_expecting(&"00".repeat(32)),
+            Err(MerkleError::RootMismatch)
+        ));
+        let root = tree.get_root().unwrap();
+
+        assert!(matches!(
+            tree.get_proof(3),
+            Err(MerkleError::LeafIndexOutOfRange(3))
+        ));
+
+        let leaf_data = encode_leaf("dna1", "ptr1", "platform", 1001);
+        let mut proof = tree.get_proof(1).unwrap();
+        assert!(matches!(
+            tree.verify_proof(&leaf_data, &proof[1..], &root),
+            Err(MerkleError::InvalidProof)
+        ));
+        proof[0].hash = "zz".into();
+        assert!(matches!(
+            tree.verify_proof(&leaf_data, &proof, &root),
+            Err(MerkleError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_tree_expecting() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+
+        assert!(tree.build_tree_expecting(&root).is_ok());
+
+        let wrong = "00".repeat(32);
+        assert!(matches!(tree.build_tree_expecting(&wrong), Err(MerkleError::RootMismatch)));
+        assert_eq!(tree.get_root().unwrap(), root);
+
+        let mut manifest = tree.export_manifest().unwrap();
+        assert!(MerkleTree::new().import_manifest(&manifest).is_ok());
+        manifest.root = wrong;
+        assert!(matches!(
+            MerkleTree::new().import_manifest(&manifest),
+            Err(MerkleError::RootMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_verify_registration() {
+        let mut tree = MerkleTree::new();
+        for i in 0..6 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+
+        for i in [0i64, 3, 4, 5] {
+            let proof = tree.get_proof(i as usize).unwrap();
+            let dna = format!("dna{}", i);
+            let pointer = format!("ptr{}", i);
+
+            assert!(verify_registration(&dna, &pointer, "platform", 1000 + i, &proof, &root).unwrap());
+            assert!(!verify_registration(&dna, &pointer, "platform", 999, &proof, &root).unwrap());
+        }
+
+        // A proof for one leaf does not verify another
+        let proof = tree.get_proof(1).unwrap();
+        assert!(!verify_registration("dna2", "ptr2", "platform", 1002, &proof, &root).unwrap());
+    }
+
+    #[test]
+    fn test_proof_bundle_leaf_hash_only() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        tree.build_tree().unwrap();
+
+        // Hash-only bundle verifies without the raw data
+        let bundle = tree.get_proof_bundle(3, false).unwrap();
+        assert!(bundle.data.is_none());
+        assert_eq!(
+            bundle.leaf_hash.as_deref(),
+            Some(compute_leaf_hash("dna3", "ptr3", "platform", Some(1003)).as_str())
+        );
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(!json.contains("\"data\""));
+        let parsed: ProofBundle = serde_json::from_str(&json).unwrap();
+        assert!(parsed.verify().unwrap());
+
+        // With data, the hash is cross-checked
+        let mut bundle = tree.get_proof_bundle(3, true).unwrap();
+        assert!(bundle.verify().unwrap());
+
+        // Hex case doesn't matter
+        let mut upper = bundle.clone();
+        upper.leaf_hash = upper.leaf_hash.map(|hash| hash.to_uppercase());
+        upper.root = upper.root.to_uppercase();
+        assert!(upper.verify().unwrap());
+
+        bundle.data = Some("dna3|ptr3|platform|9999".to_string());
+        assert!(!bundle.verify().unwrap());
+
+        // Data alone is enough too
+        bundle.data = Some("dna3|ptr3|platform|1003".to_string());
+        bundle.leaf_hash = None;
+        assert!(bundle.verify().unwrap());
+
+        bundle.data = None;
+        assert!(matches!(bundle.verify(), Err(MerkleError::InvalidProof)));
+    }
+
+    #[test]
+    fn test_namespaced_leaves() {
+        let mut first = MerkleTree::with_namespace(b"opensea");
+        let mut second = MerkleTree::with_namespace(b"foundation");
+        for tree in [&mut first, &mut second] {
+            tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+            tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        }
+
+        let root_a = first.build_tree().unwrap();
+        let root_b = second.build_tree().unwrap();
+        assert_ne!(root_a, root_b);
+
+        let hash_a = compute_leaf_hash_namespaced(b"opensea", "abc123", "ptr1", "platform1", Some(1000));
+        let hash_b = compute_leaf_hash_namespaced(b"foundation", "abc123", "ptr1", "platform1", Some(1000));
+        assert_ne!(hash_a, hash_b);
+
+        // Proofs and bundles verify within their own namespace
+        let proof = second.get_proof(0).unwrap();
+        assert!(second.verify_proof(b"abc123|ptr1|platform1|1000", &proof, &root_b).unwrap());
+        assert!(!first.verify_proof(b"abc123|ptr1|platform1|1000", &proof, &root_b).unwrap());
+
+        let bundle = first.get_proof_bundle(0, true).unwrap();
+        assert_eq!(bundle.leaf_hash, Some(hash_a));
+        assert!(bundle.verify().unwrap());
+
+        // The empty namespace matches the plain leaf hash
+        assert_eq!(
+            compute_leaf_hash_namespaced(b"", "abc123", "ptr1", "platform1", Some(1000)),
+            compute_leaf_hash("abc123", "ptr1", "platform1", Some(1000))
+        );
+
+        assert!(matches!(first.merge(&second), Err(MerkleError::NamespaceMismatch)));
+    }
+
+    #[test]
+    fn test_namespaced_manifest_round_trip() {
+        let mut tree = MerkleTree::with_namespace(b"opensea");
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        let root = tree.build_tree().unwrap();
+
+        let manifest = tree.export_manifest().unwrap();
+        assert_eq!(manifest.namespace.as_deref(), Some(hex::encode(b"opensea").as_str()));
+        assert_eq!(manifest.verify_and_get_leaf(1).unwrap().dna_hex, "def456");
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: Manifest = serde_json::from_str(&json).unwrap();
+        let mut imported = MerkleTree::new();
+        imported.import_manifest(&parsed).unwrap();
+        assert_eq!(imported.get_root().unwrap(), root);
+
+        // Un-namespaced manifests omit the field entirely
+        let mut plain = MerkleTree::new();
+        plain.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        plain.build_tree().unwrap();
+        let json = serde_json::to_string(&plain.export_manifest().unwrap()).unwrap();
+        assert!(!json.contains("namespace"));
+    }
+
+    #[test]
+    fn test_manifest_cid_is_stable() {
+        let build = || {
+            let mut tree = MerkleTree::new();
+            for i in 0..4 {
+                tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+            }
+            tree.build_tree().unwrap();
+            tree
+        };
+
+        let (manifest, cid) = build().export_manifest_with_cid().unwrap();
+        assert!(cid.starts_with("bafkrei"));
+        assert_eq!(manifest.compute_cid().unwrap(), cid);
+        assert_eq!(build().export_manifest().unwrap().compute_cid().unwrap(), cid);
+
+        // Round-tripping through JSON preserves the CID
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.compute_cid().unwrap(), cid);
+
+        let mut changed = manifest.clone();
+        changed.leaves[0].timestamp += 1;
+        assert_ne!(changed.compute_cid().unwrap(), cid);
+    }
+
+    #[test]
+    fn test_verify_proof_bytes() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+        let root_bytes: [u8; 32] = hex::decode(&root).unwrap().try_into().unwrap();
+
+        for i in 0..5 {
+            let leaf_data = format!("dna{}|ptr{}|platform|{}", i, i, 1000 + i);
+            let proof = tree.get_proof(i as usize).unwrap();
+            assert_eq!(
+                tree.verify_proof_bytes(leaf_data.as_bytes(), &proof, &root_bytes).unwrap(),
+                tree.verify_proof(leaf_data.as_bytes(), &proof, &root).unwrap()
+            );
+            assert!(tree.verify_proof_bytes(leaf_data.as_bytes(), &proof, &root_bytes).unwrap());
+        }
+
+        let proof = tree.get_proof(0).unwrap();
+        assert!(!tree.verify_proof_bytes(b"dna0|ptr0|platform|999", &proof, &root_bytes).unwrap());
+        assert!(!tree.verify_proof(b"dna0|ptr0|platform|999", &proof, &root).unwrap());
+    }
+
+    #[test]
+    fn test_add_and_prove() {
+        let mut tree = MerkleTree::new();
+        for i in 0..4 {
+            let (index, root, proof) = tree
+                .add_and_prove(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i))
+                .unwrap();
+            assert_eq!(index, i as usize);
+            assert_eq!(root, tree.get_root().unwrap());
+
+            let leaf_data = format!("dna{}|ptr{}|platform|{}", i, i, 1000 + i);
+            assert!(tree.verify_proof(leaf_data.as_bytes(), &proof, &root).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_verify_proof_length_checked() {
+        let mut single = MerkleTree::new();
+        single.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        let root = single.build_tree().unwrap();
+        assert_eq!(single.depth(), 0);
+        assert!(single.verify_proof(b"abc123|ptr1|platform1|1000", &[], &root).unwrap());
+
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        let root = tree.build_tree().unwrap();
+        assert_eq!(tree.depth(), 3);
+
+        // Empty proof against a multi-leaf root
+        let leaf_data = b"dna0|ptr0|platform|1000";
+        assert!(matches!(tree.verify_proof(leaf_data, &[], &root), Err(MerkleError::InvalidProof)));
+
+        // Over-length proof
+        let mut proof = tree.get_proof(0).unwrap();
+        proof.push(proof[0].clone());
+        assert!(matches!(tree.verify_proof(leaf_data, &proof, &root), Err(MerkleError::InvalidProof)));
+    }
+
+    #[test]
+    fn test_add_leaf_invalidates_root() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        let old_root = tree.build_tree().unwrap();
+
+        tree.add_leaf("789abc", "ptr3", "platform3", Some(3000));
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+        assert!(matches!(tree.get_proof(0), Err(MerkleError::TreeNotBuilt)));
+
+        let new_root = tree.build_tree().unwrap();
+        assert_eq!(tree.get_root().unwrap(), new_root);
+        assert_ne!(new_root, old_root);
+    }
+
+    #[test]
+    fn test_proof_element_hash_representations() {
+        let bytes: Vec<u8> = (0u8..32).collect();
+        let hex_json = format!(r#"{{"hash":"{}","position":"left"}}"#, hex::encode(&bytes));
+        let array_json = format!(
+            r#"{{"hash":{},"position":"left"}}"#,
+            serde_json::to_string(&bytes).unwrap()
+        );
+
+        let from_hex: ProofElement = serde_json::from_str(&hex_json).unwrap();
+        let from_array: ProofElement = serde_json::from_str(&array_json).unwrap();
+        assert_eq!(from_hex, from_array);
+        assert_eq!(from_array.hash, hex::encode(&bytes));
+
+        // Normalized back to hex on the way out
+        assert_eq!(serde_json::to_string(&from_array).unwrap(), hex_json);
+
+        let short_json = r#"{"hash":[1,2,3],"position":"left"}"#;
+        assert!(serde_json::from_str::<ProofElement>(short_json).is_err());
+    }
+
+    #[test]
+    fn test_uppercase_root_verifies() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.add_leaf("789abc", "ptr3", "platform3", Some(3000));
+        let root = tree.build_tree().unwrap();
+        let upper = root.to_uppercase();
+
+        // A raw string comparison would reject the same bytes
+        assert_ne!(upper, root);
+
+        let proof = tree.get_proof(1).unwrap();
+        assert!(tree.verify_proof(b"def456|ptr2|platform2|2000", &proof, &upper).unwrap());
+        assert!(verify_registration("def456", "ptr2", "platform2", 2000, &proof, &upper).unwrap());
+        tree.build_tree_expecting(&upper).unwrap();
+    }
+
+    #[test]
+    fn test_verify_and_get_leaf() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.add_leaf("789abc", "ptr3", "platform3", Some(3000));
+        tree.build_tree().unwrap();
+        let mut manifest = tree.export_manifest().unwrap();
+
+        let leaf = manifest.verify_and_get_leaf(1).unwrap();
+        assert_eq!(leaf.index, 1);
+        assert_eq!(leaf.pointer, "ptr2");
+        assert!(matches!(
+            manifest.verify_and_get_leaf(3),
+            Err(MerkleError::LeafIndexOutOfRange(3))
+        ));
+
+        // Swap in a sibling hash from another leaf's proof
+        let other = manifest.proofs["0"][0].hash.clone();
+        manifest.proofs.get_mut("1").unwrap()[0].hash = other;
+        assert!(matches!(
+            manifest.verify_and_get_leaf(1),
+            Err(MerkleError::InvalidProof)
+        ));
+        assert!(manifest.verify_and_get_leaf(2).is_ok());
+    }
+
+    #[test]
+    fn test_verify_and_get_leaf_rejects_tampered_fields() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.build_tree().unwrap();
+        let manifest = tree.export_manifest().unwrap();
+
+        // Stored hashes are compared regardless of hex case
+        let mut upper = manifest.clone();
+        let stored = upper.leaves[1].leaf_hash.as_mut().unwrap();
+        *stored = stored.to_uppercase();
+        assert!(upper.verify_and_get_leaf(1).is_ok());
+
+        let tampers: [fn(&mut LeafInfo); 4] = [
+            |leaf| leaf.dna_hex = "ffffff".into(),
+            |leaf| leaf.pointer = "ptr-evil".into(),
+            |leaf| leaf.platform_id = "platform-evil".into(),
+            |leaf| leaf.timestamp += 1,
+        ];
+        for tamper in tampers {
+            // The untouched stored hash no longer matches the fields
+            let mut tampered = manifest.clone();
+            tamper(&mut tampered.leaves[1]);
+            assert!(matches!(
+                tampered.verify_and_get_leaf(1),
+                Err(MerkleError::LeafHashMismatch(1))
+            ));
+
+            // Without a stored hash, or with one forged to match the fields,
+            // the recomputed hash misses the root
+            let leaf = &tampered.leaves[1];
+            let forged = compute_leaf_hash(
+                &leaf.dna_hex,
+                &leaf.pointer,
+                &leaf.platform_id,
+                Some(leaf.timestamp),
+            );
+            for leaf_hash in [None, Some(forged)] {
+                tampered.leaves[1].leaf_hash = leaf_hash;
+                assert!(matches!(
+                    tampered.verify_and_get_leaf(1),
+                    Err(MerkleError::InvalidProof)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_edition_policy() {
+        let mut tree = MerkleTree::new();
+        let strict = "ab".repeat(32);
+        let serial = "cd".repeat(32);
+
+        tree.try_add_edition_leaf(&strict, "solana", "00ff", "1", 1, EditionMode::Strict1To1)
+            .unwrap();
+        assert_eq!(tree.edition_mode(&strict), Some(EditionMode::Strict1To1));
+        assert!(matches!(
+            tree.try_add_edition_leaf(&strict, "ethereum", "00ee", "2", 2, EditionMode::Serial),
+            Err(MerkleError::EditionPolicyViolation(dna)) if dna == strict
+        ));
+
+        for edition_no in 1..=3 {
+            let mode = EditionMode::Serial;
+            tree.try_add_edition_leaf(&serial, "solana", "00ff", "7", edition_no, mode)
+                .unwrap();
+        }
+        assert!(matches!(
+            tree.try_add_edition_leaf(&serial, "solana", "00ff", "7", 4, EditionMode::Strict1To1),
+            Err(MerkleError::EditionPolicyViolation(_))
+        ));
+
+        // Rejected leaves are not added
+        assert_eq!(tree.leaf_count(), 4);
+        assert_eq!(tree.edition_mode(&serial), Some(EditionMode::Serial));
+    }
+
+    #[test]
+    fn test_merge_enforces_edition_policy() {
+        let strict = "ab".repeat(32);
+        let serial = "cd".repeat(32);
+
+        let mut first = MerkleTree::new();
+        first
+            .try_add_edition_leaf(&strict, "solana", "00ff", "1", 1, EditionMode::Strict1To1)
+            .unwrap();
+        first.try_add_edition_leaf(&serial, "solana", "00ff", "7", 1, EditionMode::Serial).unwrap();
+
+        // A second strict edition of the same DNA, valid on its own
+        let mut second = MerkleTree::new();
+        second
+            .try_add_edition_leaf(&strict, "ethereum", "00ee", "2", 1, EditionMode::Strict1To1)
+            .unwrap();
+        assert!(matches!(
+            first.merge(&second),
+            Err(MerkleError::EditionPolicyViolation(dna)) if dna == strict
+        ));
+        assert!(matches!(
+            second.merge(&first),
+            Err(MerkleError::EditionPolicyViolation(dna)) if dna == strict
+        ));
+
+        // More serial editions and unrelated registrations still merge
+        let mut third = MerkleTree::new();
+        third.try_add_edition_leaf(&serial, "solana", "00ff", "7", 2, EditionMode::Serial).unwrap();
+        third.add_leaf(&strict, "ptr1", "platform1", Some(1000));
+        let merged = first.merge(&third).unwrap();
+        assert_eq!(merged.leaf_count(), 4);
+        assert_eq!(merged.edition_mode(&strict), Some(EditionMode::Strict1To1));
+        assert_eq!(merged.edition_mode(&serial), Some(EditionMode::Serial));
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let mut tree = MerkleTree::with_namespace(b"registry-a").with_algorithm("dHash+Grid/v2");
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.add_leaf("789abc", "ptr3", "platform3", Some(3000));
+        assert!(matches!(tree.to_bytes(), Err(MerkleError::TreeNotBuilt)));
+        let root = tree.build_tree().unwrap();
+
+        let bytes = tree.to_bytes().unwrap();
+        assert!(bytes.starts_with(TREE_MAGIC));
+        let restored = MerkleTree::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.get_root().unwrap(), root);
+        assert_eq!(restored.leaf_count(), 3);
+        assert_eq!(restored.export_manifest().unwrap().algorithm, "dHash+Grid/v2");
+        assert_eq!(restored.get_proof(2).unwrap(), tree.get_proof(2).unwrap());
+        assert!(restored.get_proof_bundle(2, true).unwrap().verify().unwrap());
+
+        // A flipped root byte no longer matches the rebuilt tree
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            MerkleTree::from_bytes(&corrupted),
+            Err(MerkleError::RootMismatch)
+        ));
+
+        let trailing = [bytes.as_slice(), &[0]].concat();
+        for bad in [&bytes[..bytes.len() - 1], &bytes[1..], &trailing] {
+            assert!(matches!(
+                MerkleTree::from_bytes(bad),
+                Err(MerkleError::Serialization(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_leaf_directions() {
+        let mut tree = MerkleTree::new();
+        for i in 0..4 {
+            tree.add_leaf(&format!("dna{}", i), "ptr", "platform", Some(1000 + i));
+        }
+        tree.build_tree().unwrap();
+
+        use Position::{Left, Right};
+        assert_eq!(tree.leaf_directions(0).unwrap(), [Left, Left]);
+        assert_eq!(tree.leaf_directions(3).unwrap(), [Right, Right]);
+        assert_eq!(tree.leaf_directions(2).unwrap(), [Left, Right]);
+        assert!(matches!(
+            tree.leaf_directions(4),
+            Err(MerkleError::LeafIndexOutOfRange(4))
+        ));
+
+        // Each step sits opposite its proof sibling
+        for index in 0..4 {
+            let proof = tree.get_proof(index).unwrap();
+            let directions = tree.leaf_directions(index).unwrap();
+            for (direction, element) in directions.iter().zip(&proof) {
+                assert_ne!(*direction, element.position);
+            }
+        }
+    }
+
+    #[test]
+    fn test_manifest_algorithm() {
+        let mut tree = MerkleTree::new().with_algorithm("dHash+Grid/v2");
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.build_tree().unwrap();
+
+        let manifest = tree.export_manifest().unwrap();
+        assert_eq!(manifest.algorithm, "dHash+Grid/v2");
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.algorithm, "dHash+Grid/v2");
+
+        let mut legacy = serde_json::to_value(&manifest).unwrap();
+        legacy.as_object_mut().unwrap().remove("algorithm");
+        let parsed: Manifest = serde_json::from_value(legacy).unwrap();
+        assert_eq!(parsed.algorithm, UNKNOWN_DNA_ALGORITHM);
+
+        let mut default_tree = MerkleTree::new();
+        default_tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        default_tree.build_tree().unwrap();
+        assert_eq!(
+            default_tree.export_manifest().unwrap().algorithm,
+            DEFAULT_DNA_ALGORITHM
+        );
+    }
+
+    #[test]
+    fn test_verify_bundles_stream() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        tree.add_leaf("789abc", "ptr3", "platform3", Some(3000));
+        tree.build_tree().unwrap();
+
+        let mut tampered = tree.get_proof_bundle(2, true).unwrap();
+        tampered.data = Some("789abc|ptr3|platform3|3001".to_string());
+        let lines = [
+            serde_json::to_string(&tree.get_proof_bundle(0, true).unwrap()).unwrap(),
+            serde_json::to_string(&tampered).unwrap(),
+            serde_json::to_string(&tree.get_proof_bundle(1, false).unwrap()).unwrap(),
+        ];
+        let input = lines.join("\n") + "\n";
+
+        let mut output = Vec::new();
+        let (total, valid) = verify_bundles_stream(input.as_bytes(), &mut output).unwrap();
+        assert_eq!((total, valid), (3, 2));
+
+        // Rewriting the tampered line as a hash-only bundle that claims the
+        // root itself as its leaf, with nothing left to fold, is still invalid
+        let root = tree.get_root().unwrap();
+        let forged = ProofBundle {
+            namespace: None,
+            data: None,
+            leaf_hash: Some(root.clone()),
+            proof: Vec::new(),
+            root: root.clone(),
+        };
+        let truncated = ProofBundle {
+            leaf_hash: Some(root[..32].to_string()),
+            ..tree.get_proof_bundle(1, false).unwrap()
+        };
+        let forged_input = [
+            lines[0].clone(),
+            serde_json::to_string(&forged).unwrap(),
+            serde_json::to_string(&truncated).unwrap(),
+        ]
+        .join("\n");
+        let (total, valid) = verify_bundles_stream(forged_input.as_bytes(), Vec::new()).unwrap();
+        assert_eq!((total, valid), (3, 1));
+        assert!(matches!(forged.verify(), Err(MerkleError::InvalidProof)));
+        assert!(matches!(truncated.verify(), Err(MerkleError::InvalidProof)));
+
+        let results: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let valid_flags: Vec<bool> = results
+            .iter()
+            .map(|r| r["valid"].as_bool().unwrap())
+            .collect();
+        assert_eq!(valid_flags, [true, false, true]);
+        assert_eq!(results[2]["index"], 2);
+    }
+
+    #[test]
+    fn test_single_leaf_tree() {
+        let mut tree = MerkleTree::new();
+        tree.add_leaf("abc123", "ptr1", "platform1", Some(1000));
+        let root = tree.build_tree().unwrap();
+
+        // The root is the bare leaf hash, not BLAKE3(leaf || leaf)
+        let leaf_hash = compute_leaf_hash("abc123", "ptr1", "platform1", Some(1000));
+        assert_eq!(root, leaf_hash);
+
+        let proof = tree.get_proof(0).unwrap();
+        assert!(proof.is_empty());
+        assert!(tree.verify_proof(b"abc123|ptr1|platform1|1000", &proof, &root).unwrap());
+        assert!(verify_registration("abc123", "ptr1", "platform1", 1000, &proof, &root).unwrap());
+        assert!(tree.get_proof_bundle(0, true).unwrap().verify().unwrap());
+
+        // A second leaf pairs the two and the root moves off the leaf hash
+        tree.add_leaf("def456", "ptr2", "platform2", Some(2000));
+        let root = tree.build_tree().unwrap();
+        assert_ne!(root, leaf_hash);
+        assert_eq!(tree.get_proof(0).unwrap().len(), 1);
+    }
 }
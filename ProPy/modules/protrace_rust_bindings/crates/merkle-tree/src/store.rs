@@ -0,0 +1,90 @@
+//! Pluggable manifest storage
+//!
+//! [`ManifestStore`] is the extension point for where manifests live; keys
+//! are opaque to callers. [`FileStore`] keeps them in a local directory.
+
+use std::path::PathBuf;
+
+use crate::{Manifest, MerkleError};
+
+/// Storage backend for tree manifests
+pub trait ManifestStore {
+    /// Store a manifest, returning the key to fetch it with
+    fn put(&self, manifest: &Manifest) -> Result<String, MerkleError>;
+
+    /// Fetch a manifest previously stored under `key`
+    fn get(&self, key: &str) -> Result<Manifest, MerkleError>;
+}
+
+/// Manifests stored as `<cid>.json` files in a directory
+///
+/// Keys are the manifest CIDs (see [`Manifest::compute_cid`]), so storing
+/// the same manifest twice yields the same key.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Create a store in `dir`, which is created on first `put`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, MerkleError> {
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(MerkleError::Storage(format!("invalid manifest key {:?}", key)));
+        }
+        Ok(self.dir.join(format!("{}.json", key)))
+    }
+}
+
+impl ManifestStore for FileStore {
+    fn put(&self, manifest: &Manifest) -> Result<String, MerkleError> {
+        let key = manifest.compute_cid()?;
+        let path = self.path_for(&key)?;
+
+        std::fs::create_dir_all(&self.dir).map_err(|e| MerkleError::Storage(e.to_string()))?;
+        std::fs::write(&path, manifest.to_canonical_json()?)
+            .map_err(|e| MerkleError::Storage(format!("{}: {}", path.display(), e)))?;
+
+        Ok(key)
+    }
+
+    fn get(&self, key: &str) -> Result<Manifest, MerkleError> {
+        let path = self.path_for(key)?;
+        let data = std::fs::read(&path)
+            .map_err(|e| MerkleError::Storage(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_slice(&data).map_err(|e| MerkleError::Serialization(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerkleTree;
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let mut tree = MerkleTree::new();
+        for i in 0..4 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", Some(1000 + i));
+        }
+        tree.build_tree().unwrap();
+        let manifest = tree.export_manifest().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("manifests"));
+
+        let key = store.put(&manifest).unwrap();
+        assert_eq!(key, manifest.compute_cid().unwrap());
+        assert_eq!(store.put(&manifest).unwrap(), key);
+
+        let loaded = store.get(&key).unwrap();
+        assert_eq!(loaded.root, manifest.root);
+        assert_eq!(loaded.to_canonical_json().unwrap(), manifest.to_canonical_json().unwrap());
+
+        assert!(matches!(store.get("missing"), Err(MerkleError::Storage(_))));
+        assert!(matches!(store.get("../etc/passwd"), Err(MerkleError::Storage(_))));
+    }
+}
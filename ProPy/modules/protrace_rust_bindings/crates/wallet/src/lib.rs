@@ -18,8 +18,13 @@ pub enum WalletError {
     InvalidKeypairFormat,
     #[error("File not found: {0}")]
     FileNotFound(String),
+    #[error("Environment variable not set: {0}")]
+    EnvVarNotSet(String),
 }
 
+/// Prefix selecting an environment variable in [`WalletManager::load`]
+pub const ENV_WALLET_PREFIX: &str = "env:";
+
 /// Wallet manager for handling Solana keypairs
 pub struct WalletManager {
     keypair: Keypair,
@@ -48,6 +53,32 @@ impl WalletManager {
         })
     }
 
+    /// Load wallet from an environment variable
+    ///
+    /// The value may be a JSON byte array, base58, or hex encoded keypair.
+    pub fn from_env(var: &str) -> Result<Self> {
+        let value = std::env::var(var).map_err(|_| WalletError::EnvVarNotSet(var.to_string()))?;
+        log::info!("Loading wallet from environment variable: {}", var);
+
+        let keypair = parse_keypair(&value)?;
+
+        Ok(Self {
+            keypair,
+            path: None,
+        })
+    }
+
+    /// Load wallet from a CLI wallet argument
+    ///
+    /// `env:NAME` reads the keypair from environment variable `NAME`;
+    /// anything else is treated as a keypair file path.
+    pub fn load(wallet: &str) -> Result<Self> {
+        match wallet.strip_prefix(ENV_WALLET_PREFIX) {
+            Some(var) => Self::from_env(var),
+            None => Self::from_file(wallet),
+        }
+    }
+
     /// Load wallet from JSON string
     pub fn from_json(json: &str) -> Result<Self> {
         let bytes: Vec<u8> = serde_json::from_str(json)
@@ -146,16 +177,31 @@ pub fn load_keypair_from_file<P: AsRef<Path>>(path: P) -> Result<Keypair> {
     let contents = fs::read_to_string(path_ref)
         .context("Failed to read keypair file")?;
 
+    parse_keypair(&contents)
+}
+
+/// Parse a keypair from a JSON byte array, hex, or base58 string
+pub fn parse_keypair(contents: &str) -> Result<Keypair> {
+    let contents = contents.trim();
+
     // Try parsing as JSON array
-    if let Ok(bytes) = serde_json::from_str::<Vec<u8>>(&contents) {
+    if let Ok(bytes) = serde_json::from_str::<Vec<u8>>(contents) {
         if bytes.len() == 64 {
             return Keypair::from_bytes(&bytes)
                 .map_err(|e| WalletError::KeypairLoadError(e.to_string()).into());
         }
     }
 
+    // Try parsing as hex (128 characters)
+    if contents.len() == 128 {
+        if let Ok(bytes) = hex::decode(contents) {
+            return Keypair::from_bytes(&bytes)
+                .map_err(|e| WalletError::KeypairLoadError(e.to_string()).into());
+        }
+    }
+
     // Try parsing as base58
-    if let Ok(bytes) = bs58::decode(contents.trim()).into_vec() {
+    if let Ok(bytes) = bs58::decode(contents).into_vec() {
         if bytes.len() == 64 {
             return Keypair::from_bytes(&bytes)
                 .map_err(|e| WalletError::KeypairLoadError(e.to_string()).into());
@@ -207,7 +253,6 @@ pub fn load_default_keypair() -> Result<Keypair> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
     use tempfile::tempdir;
 
     #[test]
@@ -237,4 +282,25 @@ mod tests {
 
         assert_eq!(wallet1.pubkey_string(), wallet2.pubkey_string());
     }
+
+    #[test]
+    fn test_wallet_from_env() {
+        let wallet = WalletManager::new();
+
+        let var = "PROTRACE_TEST_WALLET_JSON";
+        std::env::set_var(var, wallet.to_json().unwrap());
+        let loaded = WalletManager::load(&format!("env:{}", var)).unwrap();
+        assert_eq!(loaded.pubkey_string(), wallet.pubkey_string());
+        assert!(loaded.path().is_none());
+
+        let var = "PROTRACE_TEST_WALLET_BASE58";
+        std::env::set_var(var, wallet.private_key_base58());
+        assert_eq!(WalletManager::from_env(var).unwrap().pubkey_string(), wallet.pubkey_string());
+
+        let var = "PROTRACE_TEST_WALLET_HEX";
+        std::env::set_var(var, hex::encode(wallet.keypair().to_bytes()));
+        assert_eq!(WalletManager::from_env(var).unwrap().pubkey_string(), wallet.pubkey_string());
+
+        assert!(WalletManager::from_env("PROTRACE_TEST_WALLET_UNSET").is_err());
+    }
 }
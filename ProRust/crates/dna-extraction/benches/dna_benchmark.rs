@@ -62,6 +62,24 @@ fn bench_full_dna(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_fast_vs_default(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fast_vs_default");
+
+    let img = image::DynamicImage::ImageRgb8(create_test_image(2048, 2048));
+    for (name, extractor) in [
+        ("default", DnaExtractor::new()),
+        ("fast", DnaExtractor::new().with_fast_mode()),
+    ] {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &img, |b, img| {
+            b.iter(|| {
+                let _ = extractor.extract(black_box(img));
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_hamming_distance(c: &mut Criterion) {
     use protrace_dna::utils::hamming_distance;
 
@@ -89,6 +107,7 @@ criterion_group!(
     bench_dhash,
     bench_grid_hash,
     bench_full_dna,
+    bench_fast_vs_default,
     bench_hamming_distance,
     bench_similarity
 );
@@ -2,10 +2,12 @@
 //!
 //! Command-line tool for extracting DNA fingerprints from images.
 
-use protrace_dna::DnaExtractor;
-
 #[cfg(feature = "cli")]
 use clap::{Parser, Subcommand};
+#[cfg(feature = "cli")]
+use protrace_dna::DnaExtractor;
+#[cfg(feature = "cli")]
+use std::path::PathBuf;
 
 #[cfg(feature = "cli")]
 #[derive(Parser)]
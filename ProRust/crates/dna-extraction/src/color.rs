@@ -0,0 +1,115 @@
+//! Color Profile Stripping
+//!
+//! Removes embedded color-management metadata so an image decodes to its raw
+//! (assumed sRGB) pixel values. Decoders differ in whether and how they apply
+//! ICC profiles and gamma, which shifts pixel values enough to flip DNA bits;
+//! stripping the metadata first makes every decoder take the same raw path.
+//!
+//! Handled containers:
+//! - **PNG**: drops `iCCP`, `sRGB`, `gAMA` and `cHRM` chunks
+//! - **JPEG**: drops `APP2` segments carrying an `ICC_PROFILE`
+//!
+//! Other formats, and files these parsers can't walk, are returned unchanged.
+
+use std::borrow::Cow;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_COLOR_CHUNKS: [&[u8; 4]; 4] = [b"iCCP", b"sRGB", b"gAMA", b"cHRM"];
+const JPEG_ICC_MARKER: &[u8] = b"ICC_PROFILE\0";
+
+/// Return `bytes` with any embedded color profile removed
+pub fn strip_color_profile(bytes: &[u8]) -> Cow<'_, [u8]> {
+    let stripped = if bytes.starts_with(PNG_SIGNATURE) {
+        strip_png(bytes)
+    } else if bytes.starts_with(&[0xff, 0xd8]) {
+        strip_jpeg(bytes)
+    } else {
+        None
+    };
+
+    match stripped {
+        Some(stripped) => Cow::Owned(stripped),
+        None => Cow::Borrowed(bytes),
+    }
+}
+
+/// Copy a PNG, skipping color-management chunks
+fn strip_png(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = PNG_SIGNATURE.to_vec();
+    let mut pos = PNG_SIGNATURE.len();
+
+    while pos < bytes.len() {
+        let len = u32::from_be_bytes(bytes.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let kind = bytes.get(pos + 4..pos + 8)?;
+        // length + type + data + CRC
+        let end = pos.checked_add(12 + len)?;
+        let chunk = bytes.get(pos..end)?;
+
+        if !PNG_COLOR_CHUNKS.iter().any(|color| kind == *color) {
+            out.extend_from_slice(chunk);
+        }
+        pos = end;
+    }
+
+    Some(out)
+}
+
+/// Copy a JPEG, skipping ICC profile segments before the scan data
+fn strip_jpeg(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = bytes[..2].to_vec();
+    let mut pos = 2;
+
+    loop {
+        let marker = *bytes.get(pos + 1)?;
+        if *bytes.get(pos)? != 0xff {
+            return None;
+        }
+        // Start of scan: everything after it is entropy-coded image data
+        if marker == 0xda {
+            out.extend_from_slice(&bytes[pos..]);
+            return Some(out);
+        }
+
+        let len = u16::from_be_bytes([*bytes.get(pos + 2)?, *bytes.get(pos + 3)?]) as usize;
+        let end = pos + 2 + len;
+        let segment = bytes.get(pos..end)?;
+
+        let is_icc = marker == 0xe2
+            && segment
+                .get(4..)
+                .is_some_and(|data| data.starts_with(JPEG_ICC_MARKER));
+        if !is_icc {
+            out.extend_from_slice(segment);
+        }
+        pos = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jpeg_icc_segment() {
+        let mut icc = vec![0xff, 0xe2, 0x00, 0x00];
+        icc.extend_from_slice(JPEG_ICC_MARKER);
+        icc.extend_from_slice(&[1, 1, 0xaa, 0xbb]);
+        let len = (icc.len() - 2) as u16;
+        icc[2..4].copy_from_slice(&len.to_be_bytes());
+
+        let app0 = [0xff, 0xe0, 0x00, 0x04, 0x4a, 0x46];
+        let scan = [0xff, 0xda, 0x00, 0x02, 0x12, 0x34, 0xff, 0xd9];
+
+        let jpeg = [&[0xff, 0xd8][..], &app0, &icc, &scan].concat();
+        let expected = [&[0xff, 0xd8][..], &app0, &scan].concat();
+        assert_eq!(strip_color_profile(&jpeg).as_ref(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_strip_leaves_other_bytes_alone() {
+        assert!(matches!(strip_color_profile(b"GIF89a"), Cow::Borrowed(_)));
+        // Truncated PNG can't be walked, so it is passed through as-is
+        let truncated = [PNG_SIGNATURE, &[0, 0, 0, 13, b'I']].concat();
+        assert!(matches!(strip_color_profile(&truncated), Cow::Borrowed(_)));
+    }
+}
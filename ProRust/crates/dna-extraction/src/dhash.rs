@@ -4,7 +4,7 @@
 //!
 //! ## Optimized Algorithm (Aligned with Python v2.4x speedup)
 //!
-//! 1. **Fast center crop to 512×512** (zero-copy, ~1-2ms)
+//! 1. **Fast center crop** to 512×512 by default (zero-copy, ~1-2ms)
 //! 2. **Direct grayscale conversion** (ITU-R BT.601, ~0.5ms)
 //! 3. **Fast box blur** (3×3 kernel, optimized, ~1-2ms)
 //! 4. **Fast 4×4 block averaging** to 128×128 (~0.5ms)
@@ -13,11 +13,78 @@
 //! 7. **Direct bit packing** to 64-bit hash (~0.1ms)
 //!
 //! **Total**: ~3-5ms (Rust) vs 18-24ms (Python optimized) vs 30-40ms (Python baseline)
+//!
+//! ## Fast Mode
+//!
+//! [`compute_dhash_fast`] skips step 3 and uses a nearest-neighbor resize in
+//! step 5. It is noticeably quicker but **not bit-compatible** with
+//! [`compute_dhash`] or the Python engine, so fast and default hashes must
+//! never be compared against each other.
+//!
+//! ## Low-Entropy Images
+//!
+//! Solid-color and near-flat images have no gradients, so every one of them
+//! hashes to `0000000000000000` regardless of color. The hash itself is left
+//! unchanged (it must match the Python engine); instead
+//! [`compute_dhash_profiled`] also scores the luminance entropy of the
+//! block-averaged buffer from step 4, so callers can flag such images and
+//! fall back to comparing mean luminance without another pass over the image.
 
 use image::{imageops, ImageBuffer, Luma, RgbImage};
 use ndarray::Array2;
 
-use crate::DnaResult;
+use crate::{DnaError, DnaResult};
+
+/// Luminance entropy (bits) below which an image is considered low-entropy
+pub const LOW_ENTROPY_THRESHOLD: f64 = 1.0;
+
+/// Grayscale luminance summary used to detect flat images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LuminanceProfile {
+    /// Shannon entropy of the 256-bin luminance histogram (0.0 to 8.0 bits)
+    pub entropy: f64,
+    /// Mean luminance (0 to 255)
+    pub mean: u8,
+}
+
+impl LuminanceProfile {
+    /// Whether the image is too flat for its dHash to be meaningful
+    pub fn is_low_entropy(&self) -> bool {
+        self.entropy < LOW_ENTROPY_THRESHOLD
+    }
+}
+
+/// Luminance entropy and mean of a grayscale buffer
+fn luminance_profile(gray: &ImageBuffer<Luma<u8>, Vec<u8>>) -> LuminanceProfile {
+    let total = gray.pixels().len();
+    if total == 0 {
+        return LuminanceProfile {
+            entropy: 0.0,
+            mean: 0,
+        };
+    }
+
+    let mut histogram = [0u64; 256];
+    let mut sum = 0u64;
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+        sum += pixel[0] as u64;
+    }
+
+    let entropy = histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum();
+
+    LuminanceProfile {
+        entropy,
+        mean: (sum / total as u64) as u8,
+    }
+}
 
 /// Fast box blur using simple averaging
 fn box_blur(img: &Array2<f32>, kernel_size: usize) -> Array2<f32> {
@@ -30,8 +97,8 @@ fn box_blur(img: &Array2<f32>, kernel_size: usize) -> Array2<f32> {
             let mut sum = 0.0;
             let mut count = 0;
 
-            for ky in -(offset as isize)..=(offset as isize) {
-                for kx in -(offset as isize)..=(offset as isize) {
+            for ky in -offset..=offset {
+                for kx in -offset..=offset {
                     let ny = (y as isize + ky).clamp(0, (height - 1) as isize) as usize;
                     let nx = (x as isize + kx).clamp(0, (width - 1) as isize) as usize;
                     sum += img[[ny, nx]];
@@ -81,12 +148,71 @@ fn block_average(img: &Array2<f32>, block_size: usize) -> Array2<f32> {
     result
 }
 
+/// Side of the center crop used by [`compute_dhash`]
+pub const DEFAULT_DHASH_CROP: u32 = 512;
+
 /// Compute dHash (64-bit) from RGB image
 pub fn compute_dhash(img: &RgbImage, hash_size: u32) -> DnaResult<String> {
+    compute_dhash_with_crop(img, hash_size, DEFAULT_DHASH_CROP)
+}
+
+/// Compute dHash (64-bit) from a `crop_size`×`crop_size` center crop
+///
+/// Smaller crops are more robust to edits, larger ones keep more detail.
+/// Images smaller than the crop are used whole. Returns
+/// `InvalidDimensions` if `crop_size` is not larger than `hash_size` or
+/// the image is empty.
+pub fn compute_dhash_with_crop(
+    img: &RgbImage,
+    hash_size: u32,
+    crop_size: u32,
+) -> DnaResult<String> {
+    dhash_with_mode(img, hash_size, crop_size, false).map(|(hash, _)| hash)
+}
+
+/// Compute dHash (64-bit) without the blur, using a nearest-neighbor resize
+///
+/// Trades some noise robustness for throughput. The result is not
+/// bit-compatible with [`compute_dhash`].
+pub fn compute_dhash_fast(img: &RgbImage, hash_size: u32, crop_size: u32) -> DnaResult<String> {
+    dhash_with_mode(img, hash_size, crop_size, true).map(|(hash, _)| hash)
+}
+
+/// Compute dHash (64-bit) together with the cropped image's [`LuminanceProfile`]
+///
+/// The hash is identical to [`compute_dhash_with_crop`], or to
+/// [`compute_dhash_fast`] when `fast` is set. The profile is taken from the
+/// ~128×128 block-averaged buffer the hash is computed from.
+pub fn compute_dhash_profiled(
+    img: &RgbImage,
+    hash_size: u32,
+    crop_size: u32,
+    fast: bool,
+) -> DnaResult<(String, LuminanceProfile)> {
+    dhash_with_mode(img, hash_size, crop_size, fast)
+}
+
+fn dhash_with_mode(
+    img: &RgbImage,
+    hash_size: u32,
+    crop_size: u32,
+    fast: bool,
+) -> DnaResult<(String, LuminanceProfile)> {
     let (width, height) = img.dimensions();
+    if crop_size <= hash_size {
+        return Err(DnaError::InvalidDimensions(format!(
+            "dHash crop {} must be larger than hash size {}",
+            crop_size, hash_size
+        )));
+    }
+    if width == 0 || height == 0 {
+        return Err(DnaError::InvalidDimensions(format!(
+            "cannot crop a {}x{} image",
+            width, height
+        )));
+    }
 
-    // 1. Center crop to 512×512
-    let crop_size = 512;
+    // 1. Center crop to crop_size×crop_size
     let left = (width.saturating_sub(crop_size)) / 2;
     let top = (height.saturating_sub(crop_size)) / 2;
     let right = (left + crop_size).min(width);
@@ -106,8 +232,12 @@ pub fn compute_dhash(img: &RgbImage, hash_size: u32) -> DnaResult<String> {
         }
     }
 
-    // 3. Fast box blur (3×3 kernel)
-    let blurred = box_blur(&gray_array, 3);
+    // 3. Fast box blur (3×3 kernel), skipped in fast mode
+    let blurred = if fast {
+        gray_array
+    } else {
+        box_blur(&gray_array, 3)
+    };
 
     // 4. 4×4 block averaging to ~128×128
     let block_avg = block_average(&blurred, 4);
@@ -120,12 +250,12 @@ pub fn compute_dhash(img: &RgbImage, hash_size: u32) -> DnaResult<String> {
             Luma([val])
         });
 
-    let resized = imageops::resize(
-        &small_img,
-        hash_size + 1,
-        hash_size,
-        imageops::FilterType::Triangle, // Fast bilinear-like filter
-    );
+    let filter = if fast {
+        imageops::FilterType::Nearest
+    } else {
+        imageops::FilterType::Triangle // Fast bilinear-like filter
+    };
+    let resized = imageops::resize(&small_img, hash_size + 1, hash_size, filter);
 
     // 6. Compute horizontal gradients
     let mut bits = Vec::with_capacity((hash_size * hash_size) as usize);
@@ -143,7 +273,7 @@ pub fn compute_dhash(img: &RgbImage, hash_size: u32) -> DnaResult<String> {
         hash_int = (hash_int << 1) | (*bit as u64);
     }
 
-    Ok(format!("{:016x}", hash_int))
+    Ok((format!("{:016x}", hash_int), luminance_profile(&small_img)))
 }
 
 #[cfg(test)]
@@ -164,6 +294,40 @@ mod tests {
         assert!(u64::from_str_radix(&hash, 16).is_ok());
     }
 
+    #[test]
+    fn test_dhash_with_crop() {
+        let img = RgbImage::from_fn(600, 600, |x, y| {
+            image::Rgb([(x % 256) as u8, ((x * y) % 256) as u8, (y % 256) as u8])
+        });
+        assert_eq!(
+            compute_dhash(&img, 8).unwrap(),
+            compute_dhash_with_crop(&img, 8, DEFAULT_DHASH_CROP).unwrap()
+        );
+        assert_ne!(
+            compute_dhash(&img, 8).unwrap(),
+            compute_dhash_with_crop(&img, 8, 128).unwrap()
+        );
+
+        for crop in [0, 8] {
+            assert!(matches!(
+                compute_dhash_with_crop(&img, 8, crop),
+                Err(DnaError::InvalidDimensions(_))
+            ));
+        }
+        assert!(compute_dhash(&RgbImage::new(0, 0), 8).is_err());
+    }
+
+    #[test]
+    fn test_dhash_fast() {
+        let img = RgbImage::from_fn(600, 600, |x, y| {
+            image::Rgb([(x % 256) as u8, ((x * y) % 256) as u8, (y % 256) as u8])
+        });
+        let hash = compute_dhash_fast(&img, 8, 512).unwrap();
+
+        assert_eq!(hash.len(), 16);
+        assert!(u64::from_str_radix(&hash, 16).is_ok());
+    }
+
     #[test]
     fn test_box_blur() {
         let img = Array2::from_shape_fn((5, 5), |(i, j)| (i + j) as f32);
@@ -186,4 +350,20 @@ mod tests {
             assert_eq!(*val, 1.0);
         }
     }
+
+    #[test]
+    fn test_dhash_profiled_flat_vs_varied() {
+        let white = RgbImage::from_pixel(64, 64, image::Rgb([255, 255, 255]));
+        let (hash, profile) = compute_dhash_profiled(&white, 8, 512, false).unwrap();
+        assert_eq!(hash, compute_dhash(&white, 8).unwrap());
+        assert_eq!(profile.entropy, 0.0);
+        assert_eq!(profile.mean, 255);
+        assert!(profile.is_low_entropy());
+
+        let varied =
+            RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * 4) as u8, (y * 4) as u8, 0]));
+        let (hash, profile) = compute_dhash_profiled(&varied, 8, 512, true).unwrap();
+        assert_eq!(hash, compute_dhash_fast(&varied, 8, 512).unwrap());
+        assert!(!profile.is_low_entropy());
+    }
 }
@@ -4,7 +4,8 @@
 //!
 //! ## Optimized Algorithm (Aligned with Python v2.4x speedup)
 //!
-//! 1. **Fast padding to 2048×2048** (~0.5ms vs 2-3ms Python optimized)
+//! 1. **Fast padding to 2048×2048** (~0.5ms vs 2-3ms Python optimized);
+//!    larger or extreme-aspect images are centered on a max(w, h, 2048) square
 //! 2. **Extract center 1024×1024** (zero-copy, ~0.1ms)
 //! 3. **Direct grayscale conversion** (~0.5ms)
 //! 4. **Multi-scale grid hashing** (parallel-ready, ~1-2ms):
@@ -25,18 +26,22 @@ use ndarray::Array2;
 
 use crate::DnaResult;
 
-/// Pad image to target size (centered)
-fn pad_to_square(img: &RgbImage, target_size: u32) -> RgbImage {
+/// Pad image onto a black square canvas (centered)
+///
+/// The canvas side is `max(width, height, min_size)`, so wide or tall images
+/// are always squared rather than passed through with their original aspect.
+fn pad_to_square(img: &RgbImage, min_size: u32) -> RgbImage {
     let (width, height) = img.dimensions();
+    let side = width.max(height).max(min_size);
 
-    if width >= target_size && height >= target_size {
+    if width == side && height == side {
         return img.clone();
     }
 
-    let mut padded = RgbImage::from_pixel(target_size, target_size, Rgb([0, 0, 0]));
+    let mut padded = RgbImage::from_pixel(side, side, Rgb([0, 0, 0]));
 
-    let paste_x = (target_size - width) / 2;
-    let paste_y = (target_size - height) / 2;
+    let paste_x = (side - width) / 2;
+    let paste_y = (side - height) / 2;
 
     imageops::replace(&mut padded, img, paste_x as i64, paste_y as i64);
 
@@ -172,13 +177,13 @@ fn process_grid_scale(gray_array: &Array2<f32>, block_size: usize) -> Vec<u8> {
 /// **Optimization**: Supports parallel grid processing with "parallel" feature
 /// for 40-50% speedup (matching Python ThreadPoolExecutor improvements).
 pub fn compute_grid_hash(img: &RgbImage) -> DnaResult<String> {
-    // 1. Pad to 2048×2048
+    // 1. Pad to a square of side max(width, height, 2048)
     let padded = pad_to_square(img, 2048);
 
     // 2. Extract center 1024×1024
     let center_size = 1024;
-    let left = (2048 - center_size) / 2;
-    let top = (2048 - center_size) / 2;
+    let left = (padded.width() - center_size) / 2;
+    let top = (padded.height() - center_size) / 2;
     let center = imageops::crop_imm(&padded, left, top, center_size, center_size).to_image();
 
     // 3. Convert to grayscale
@@ -223,7 +228,7 @@ pub fn compute_grid_hash(img: &RgbImage) -> DnaResult<String> {
     for chunk in all_bits.chunks(8) {
         let mut byte = 0u8;
         for (i, bit) in chunk.iter().enumerate() {
-            byte |= (*bit as u8) << (7 - i);
+            byte |= *bit << (7 - i);
         }
         hex_string.push_str(&format!("{:02x}", byte));
     }
@@ -267,6 +272,34 @@ mod tests {
         assert_eq!(padded.dimensions(), (200, 200));
     }
 
+    #[test]
+    fn test_pad_to_square_extreme_aspect() {
+        let img = RgbImage::new(4000, 500);
+        let padded = pad_to_square(&img, 2048);
+
+        assert_eq!(padded.dimensions(), (4000, 4000));
+    }
+
+    #[test]
+    fn test_grid_hash_panorama() {
+        // Bright gradient on top, black below, so a vertical flip moves content
+        let img = RgbImage::from_fn(4000, 500, |x, y| {
+            if y < 250 {
+                let v = (x % 256) as u8;
+                Rgb([v, 255 - v, 128])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+
+        let hash = compute_grid_hash(&img).unwrap();
+        assert_eq!(hash.len(), 48);
+        assert_eq!(hash, compute_grid_hash(&img).unwrap());
+
+        let flipped = imageops::flip_vertical(&img);
+        assert_ne!(hash, compute_grid_hash(&flipped).unwrap());
+    }
+
     #[test]
     fn test_median() {
         let arr = Array2::from_shape_vec((3, 3), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0])
@@ -0,0 +1,222 @@
+//! BK-Tree DNA Index
+//!
+//! Metric tree over Hamming distance for fast near-duplicate lookup.
+//! A query with threshold `t` only descends into children whose edge
+//! distance lies within `t` of the query's distance to the parent, so most
+//! of the index is skipped for small thresholds.
+//!
+//! With the `serde` feature the index can be saved to and loaded from disk
+//! (bincode), so a long-running service doesn't rebuild it on restart.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use crate::utils::hamming_distance;
+use crate::{DnaError, DnaResult};
+
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// BK-tree node: a DNA hash and its children keyed by distance to it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node {
+    dna_hex: String,
+    children: BTreeMap<u32, Node>,
+}
+
+/// BK-tree index of DNA hashes
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BkTree {
+    root: Option<Node>,
+    len: usize,
+}
+
+impl BkTree {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of hashes in the index
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a DNA hash
+    ///
+    /// All hashes must have the same length, since Hamming distance is only
+    /// a metric over equal-length inputs. Exact duplicates are ignored.
+    pub fn insert(&mut self, dna_hex: &str) -> DnaResult<()> {
+        if hex::decode(dna_hex).is_err() {
+            return Err(DnaError::InvalidFormat(dna_hex.to_string()));
+        }
+
+        let mut node = match &mut self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(Node {
+                    dna_hex: dna_hex.to_string(),
+                    children: BTreeMap::new(),
+                });
+                self.len = 1;
+                return Ok(());
+            }
+        };
+
+        if node.dna_hex.len() != dna_hex.len() {
+            return Err(DnaError::InvalidFormat(format!(
+                "expected {} hex chars, got {}",
+                node.dna_hex.len(),
+                dna_hex.len()
+            )));
+        }
+
+        loop {
+            let distance = hamming_distance(&node.dna_hex, dna_hex);
+            if distance == 0 {
+                return Ok(());
+            }
+
+            match node.children.entry(distance) {
+                Entry::Occupied(child) => node = child.into_mut(),
+                Entry::Vacant(slot) => {
+                    slot.insert(Node {
+                        dna_hex: dna_hex.to_string(),
+                        children: BTreeMap::new(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Find all hashes within `threshold` bits of `dna_hex`
+    ///
+    /// Returns `(hash, distance)` pairs sorted by distance, then hash.
+    pub fn query(&self, dna_hex: &str, threshold: u32) -> Vec<(&str, u32)> {
+        let mut matches = Vec::new();
+        let mut stack: Vec<&Node> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            let distance = hamming_distance(&node.dna_hex, dna_hex);
+            if distance == u32::MAX {
+                // Mismatched length: nothing in the index can match
+                return Vec::new();
+            }
+            if distance <= threshold {
+                matches.push((node.dna_hex.as_str(), distance));
+            }
+
+            let low = distance.saturating_sub(threshold);
+            let high = distance.saturating_add(threshold);
+            stack.extend(node.children.range(low..=high).map(|(_, child)| child));
+        }
+
+        matches.sort_unstable_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        matches
+    }
+
+    /// Save the index to `path` (bincode)
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> DnaResult<()> {
+        let bytes =
+            bincode::serialize(self).map_err(|e| DnaError::IndexSerialization(e.to_string()))?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Load an index previously written by [`BkTree::save`]
+    #[cfg(feature = "serde")]
+    pub fn load<P: AsRef<Path>>(path: P) -> DnaResult<Self> {
+        let bytes = std::fs::read(path)?;
+        bincode::deserialize(&bytes).map_err(|e| DnaError::IndexSerialization(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic 256-bit hashes spread across the space
+    fn sample_hashes(count: u64) -> Vec<String> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        (0..count)
+            .map(|_| {
+                (0..4)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        format!("{:016x}", state)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_query_matches_linear_scan() {
+        let hashes = sample_hashes(200);
+        let mut tree = BkTree::new();
+        for hash in &hashes {
+            tree.insert(hash).unwrap();
+        }
+        assert_eq!(tree.len(), 200);
+
+        // Re-inserting is a no-op
+        tree.insert(&hashes[0]).unwrap();
+        assert_eq!(tree.len(), 200);
+
+        for query in hashes.iter().take(10) {
+            for threshold in [0, 26, 120] {
+                let mut expected: Vec<(&str, u32)> = hashes
+                    .iter()
+                    .map(|h| (h.as_str(), hamming_distance(h, query)))
+                    .filter(|(_, d)| *d <= threshold)
+                    .collect();
+                expected.sort_unstable_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+                assert_eq!(tree.query(query, threshold), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert_rejects_mismatched_length() {
+        let mut tree = BkTree::new();
+        tree.insert("00ff").unwrap();
+        assert!(tree.insert("00ff00").is_err());
+        assert!(tree.insert("zz").is_err());
+        assert!(tree.query("00ff00", 256).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load_roundtrip() {
+        let hashes = sample_hashes(200);
+        let mut tree = BkTree::new();
+        for hash in &hashes {
+            tree.insert(hash).unwrap();
+        }
+
+        let path = std::env::temp_dir().join(format!("protrace_bktree_{}.bin", std::process::id()));
+        tree.save(&path).unwrap();
+        let loaded = BkTree::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, tree);
+        assert_eq!(loaded.len(), 200);
+        for query in hashes.iter().step_by(20) {
+            assert_eq!(loaded.query(query, 40), tree.query(query, 40));
+        }
+    }
+}
@@ -35,18 +35,36 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
-use image::DynamicImage;
-use ndarray;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::Cursor;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
 
+pub mod color;
 pub mod dhash;
 pub mod grid;
+pub mod index;
 pub mod utils;
 
-pub use dhash::compute_dhash;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testutil;
+
+pub use dhash::{
+    compute_dhash, compute_dhash_fast, compute_dhash_profiled, compute_dhash_with_crop,
+    LuminanceProfile, DEFAULT_DHASH_CROP,
+};
 pub use grid::compute_grid_hash;
-pub use utils::{hamming_distance, is_duplicate, similarity};
+pub use index::BkTree;
+pub use utils::{
+    collision_probability, distance_with_metric, hamming_distance, is_duplicate,
+    is_duplicate_with_metric, similarity, SimilarityMetric,
+};
+
+/// Default cap on decoded image size (width × height) for [`DnaExtractor`]
+pub const DEFAULT_MAX_PIXELS: u64 = 100_000_000;
 
 /// DNA extraction errors
 #[derive(Error, Debug)]
@@ -62,6 +80,25 @@ pub enum DnaError {
 
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Index serialization failed: {0}")]
+    IndexSerialization(String),
+
+    #[error("Failed to build thread pool: {0}")]
+    ThreadPool(String),
+
+    #[error("Unsupported image format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Failed to extract DNA from {path}: {source}")]
+    ExtractionFailed {
+        path: String,
+        #[source]
+        source: Box<DnaError>,
+    },
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 /// Result type for DNA operations
@@ -69,6 +106,7 @@ pub type DnaResult<T> = Result<T, DnaError>;
 
 /// 256-bit DNA fingerprint
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DnaHash {
     /// Complete 256-bit hash (64 hex chars)
     pub dna_hex: String,
@@ -78,6 +116,49 @@ pub struct DnaHash {
     pub grid_hash: String,
 }
 
+/// DNA together with the luminance profile of the image it came from
+///
+/// Returned by [`DnaExtractor::extract_profiled`]. Flat images all share the
+/// same hash bits, so the profile is what tells them apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfiledDna {
+    pub dna: DnaHash,
+    pub luminance: LuminanceProfile,
+}
+
+/// Maximum mean-luminance difference for two low-entropy images to match
+pub const LOW_ENTROPY_LUMINANCE_TOLERANCE: u8 = 16;
+
+impl ProfiledDna {
+    /// Whether the image is near-flat, so its hash bits carry little information
+    pub fn is_low_entropy(&self) -> bool {
+        self.luminance.is_low_entropy()
+    }
+
+    /// Check if this is a duplicate of another profiled DNA
+    ///
+    /// Same as [`DnaHash::is_duplicate_of`], except that two low-entropy
+    /// images must also have mean luminances within
+    /// [`LOW_ENTROPY_LUMINANCE_TOLERANCE`].
+    pub fn is_duplicate_of(&self, other: &ProfiledDna, threshold: u32) -> bool {
+        let both_flat = self.is_low_entropy() && other.is_low_entropy();
+        let luminance_gap = self.luminance.mean.abs_diff(other.luminance.mean);
+        if both_flat && luminance_gap > LOW_ENTROPY_LUMINANCE_TOLERANCE {
+            return false;
+        }
+        self.dna.is_duplicate_of(&other.dna, threshold)
+    }
+}
+
+/// Cryptographic hash used for a DNA signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigAlgo {
+    /// BLAKE3 (256-bit)
+    Blake3,
+    /// SHA-256
+    Sha256,
+}
+
 impl DnaHash {
     /// Create new DNA hash from components
     pub fn new(dhash: String, grid_hash: String) -> Self {
@@ -118,6 +199,20 @@ impl DnaHash {
         similarity(&self.dna_hex, &other.dna_hex)
     }
 
+    /// Hamming distance over the dHash component only (0-64 bits by default)
+    ///
+    /// A quarter of the work of [`DnaHash::hamming_distance`], for cheap
+    /// pre-filtering before a full comparison.
+    pub fn dhash_distance(&self, other: &DnaHash) -> u32 {
+        hamming_distance(&self.dhash, &other.dhash)
+    }
+
+    /// Similarity (0.0 to 1.0) over the dHash component only
+    pub fn dhash_similarity(&self, other: &DnaHash) -> f64 {
+        let bits = (self.dhash.len() * 4) as f64;
+        1.0 - self.dhash_distance(other) as f64 / bits
+    }
+
     /// Check if this is a duplicate of another hash (≥90% similarity)
     pub fn is_duplicate_of(&self, other: &DnaHash, threshold: u32) -> bool {
         is_duplicate(&self.dna_hex, &other.dna_hex, threshold)
@@ -127,14 +222,36 @@ impl DnaHash {
     pub fn blake3_signature(&self) -> String {
         hex::encode(blake3::hash(self.dna_hex.as_bytes()).as_bytes())
     }
+
+    /// Cryptographic hash of the DNA with the given algorithm, as hex
+    pub fn signature(&self, algo: SigAlgo) -> String {
+        match algo {
+            SigAlgo::Blake3 => self.blake3_signature(),
+            SigAlgo::Sha256 => utils::sha256_signature(&self.dna_hex),
+        }
+    }
+}
+
+impl std::fmt::Display for DnaHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.dna_hex)
+    }
 }
 
 /// DNA extractor with configurable parameters
 pub struct DnaExtractor {
     /// Size for dHash (default: 8)
     pub dhash_size: u32,
+    /// Center crop size for dHash (default: 512)
+    pub dhash_crop: u32,
     /// Enable parallel processing
     pub parallel: bool,
+    /// Skip the dHash blur and use a nearest-neighbor resize (default: false)
+    pub fast: bool,
+    /// Strip embedded color profiles before decoding (default: false)
+    pub ignore_color_profile: bool,
+    /// Largest image (width × height) decoded from a path or bytes (default: 100M)
+    pub max_pixels: u64,
 }
 
 impl Default for DnaExtractor {
@@ -148,7 +265,11 @@ impl DnaExtractor {
     pub fn new() -> Self {
         Self {
             dhash_size: 8,
+            dhash_crop: DEFAULT_DHASH_CROP,
             parallel: false,
+            fast: false,
+            ignore_color_profile: false,
+            max_pixels: DEFAULT_MAX_PIXELS,
         }
     }
 
@@ -162,35 +283,216 @@ impl DnaExtractor {
         self
     }
 
+    /// Trade dHash robustness for throughput
+    ///
+    /// Fast mode skips the 3×3 blur and resizes with nearest-neighbor. The
+    /// resulting DNA is **not bit-compatible** with the default mode, so only
+    /// compare it against DNA extracted in fast mode.
+    pub fn with_fast_mode(mut self) -> Self {
+        self.fast = true;
+        self
+    }
+
+    /// Ignore color management and decode raw pixel values as sRGB
+    ///
+    /// Embedded ICC profiles (and PNG gamma/chromaticity chunks) are removed
+    /// before decoding, so the same pixels hash identically with or without a
+    /// profile and across decoder versions. Only affects the path and bytes
+    /// entry points; an already-decoded `DynamicImage` is used as given.
+    pub fn with_ignore_color_profile(mut self) -> Self {
+        self.ignore_color_profile = true;
+        self
+    }
+
+    /// Set the largest image, in pixels, decoded from a path or bytes
+    ///
+    /// Larger images are rejected with `InvalidDimensions` from their header
+    /// alone, before any pixel data is decoded, which guards against
+    /// decompression bombs.
+    pub fn with_max_pixels(mut self, max_pixels: u64) -> Self {
+        self.max_pixels = max_pixels;
+        self
+    }
+
     /// Extract DNA from image file path
+    ///
+    /// The format is taken from the file's magic bytes, not its extension;
+    /// anything that isn't a readable raster format is rejected with
+    /// `UnsupportedFormat` naming what was found.
     pub fn extract_from_path<P: AsRef<Path>>(&self, path: P) -> DnaResult<DnaHash> {
-        let img = image::open(path)?;
+        let bytes = std::fs::read(path)?;
+        let bytes = self.prepare_bytes(&bytes);
+        let format = sniff_format(&bytes)?;
+        let img = self.decode(&bytes, Some(format))?;
         self.extract(&img)
     }
 
     /// Extract DNA from image bytes
     pub fn extract_from_bytes(&self, bytes: &[u8]) -> DnaResult<DnaHash> {
-        let img = image::load_from_memory(bytes)?;
+        let img = self.decode(&self.prepare_bytes(bytes), None)?;
         self.extract(&img)
     }
 
+    /// Decode image bytes, enforcing `max_pixels` before the full decode
+    ///
+    /// Decoder allocations are also capped at 8 bytes per allowed pixel
+    /// (16-bit RGBA), in case a header understates the real size.
+    fn decode(&self, bytes: &[u8], format: Option<ImageFormat>) -> DnaResult<DynamicImage> {
+        let reader = || -> DnaResult<image::io::Reader<Cursor<&[u8]>>> {
+            Ok(match format {
+                Some(format) => image::io::Reader::with_format(Cursor::new(bytes), format),
+                None => image::io::Reader::new(Cursor::new(bytes)).with_guessed_format()?,
+            })
+        };
+
+        let (width, height) = reader()?.into_dimensions()?;
+        let pixels = width as u64 * height as u64;
+        if pixels > self.max_pixels {
+            return Err(DnaError::InvalidDimensions(format!(
+                "{}×{} image exceeds the {} pixel limit",
+                width, height, self.max_pixels
+            )));
+        }
+
+        let mut limits = image::io::Limits::default();
+        limits.max_alloc = Some(self.max_pixels.saturating_mul(8));
+        let mut reader = reader()?;
+        reader.limits(limits);
+
+        reader.decode().map_err(|e| match e {
+            image::ImageError::Limits(limit) => DnaError::InvalidDimensions(limit.to_string()),
+            e => e.into(),
+        })
+    }
+
+    /// Apply byte-level settings before decoding
+    fn prepare_bytes<'a>(&self, bytes: &'a [u8]) -> std::borrow::Cow<'a, [u8]> {
+        if self.ignore_color_profile {
+            color::strip_color_profile(bytes)
+        } else {
+            std::borrow::Cow::Borrowed(bytes)
+        }
+    }
+
     /// Extract DNA from DynamicImage
     pub fn extract(&self, img: &DynamicImage) -> DnaResult<DnaHash> {
+        Ok(self.extract_profiled(img)?.dna)
+    }
+
+    /// Extract DNA together with the image's luminance profile
+    ///
+    /// The profile comes from the downscaled dHash buffer, so this costs no
+    /// more than [`DnaExtractor::extract`]. Use it to flag low-entropy images.
+    pub fn extract_profiled(&self, img: &DynamicImage) -> DnaResult<ProfiledDna> {
         // Convert to RGB
         let rgb_img = img.to_rgb8();
 
         // Compute dHash (64-bit)
-        let dhash = compute_dhash(&rgb_img, self.dhash_size)?;
+        let (dhash, luminance) =
+            compute_dhash_profiled(&rgb_img, self.dhash_size, self.dhash_crop, self.fast)?;
 
         // Compute Grid hash (192-bit)
         let grid_hash = compute_grid_hash(&rgb_img)?;
 
-        Ok(DnaHash::new(dhash, grid_hash))
+        Ok(ProfiledDna {
+            dna: DnaHash::new(dhash, grid_hash),
+            luminance,
+        })
+    }
+
+    /// Extract DNA of the horizontally mirrored image
+    ///
+    /// Storing this alongside the normal DNA lets a mirrored upload be
+    /// caught by comparing its normal DNA against the stored flipped DNA.
+    pub fn compute_dna_flipped(&self, img: &DynamicImage) -> DnaResult<DnaHash> {
+        self.extract(&img.fliph())
+    }
+
+    /// Extract DNA from multiple images, stopping at the first failure
+    ///
+    /// Images are processed in order; the returned error names the path
+    /// that failed.
+    pub fn extract_batch_strict<P: AsRef<Path>>(&self, paths: &[P]) -> DnaResult<Vec<DnaHash>> {
+        paths
+            .iter()
+            .map(|path| {
+                self.extract_from_path(path)
+                    .map_err(|e| DnaError::ExtractionFailed {
+                        path: path.as_ref().display().to_string(),
+                        source: Box::new(e),
+                    })
+            })
+            .collect()
+    }
+
+    /// BLAKE3 hash of the raw file bytes at `path`
+    ///
+    /// Equal hashes mean byte-identical files, which are duplicates without
+    /// any need to decode them.
+    pub fn content_hash<P: AsRef<Path>>(&self, path: P) -> DnaResult<[u8; 32]> {
+        let bytes = std::fs::read(path)?;
+        Ok(*blake3::hash(&bytes).as_bytes())
+    }
+
+    /// Group byte-identical files, as a cheap pre-check before DNA extraction
+    ///
+    /// Returns indices into `paths` for every group of two or more identical
+    /// files, ordered by first occurrence. Files that can't be read are left
+    /// out; they fail again, with a proper error, at extraction time.
+    pub fn quick_dedupe<P: AsRef<Path>>(&self, paths: &[P]) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of: HashMap<[u8; 32], usize> = HashMap::new();
+
+        for (i, path) in paths.iter().enumerate() {
+            let Ok(hash) = self.content_hash(path) else {
+                continue;
+            };
+            match group_of.entry(hash) {
+                Entry::Occupied(entry) => groups[*entry.get()].push(i),
+                Entry::Vacant(entry) => {
+                    entry.insert(groups.len());
+                    groups.push(vec![i]);
+                }
+            }
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Extract DNA for each tile of a `grid × grid` split of the image
+    ///
+    /// Tiles are returned row-major. Storing per-tile DNA lets a cropped
+    /// copy be matched against the tiles of the original (see [`tile_overlap`]).
+    /// Edge pixels left over when the dimensions don't divide evenly are dropped.
+    pub fn extract_tiles(&self, img: &DynamicImage, grid: usize) -> DnaResult<Vec<DnaHash>> {
+        let (width, height) = img.dimensions();
+        let grid = grid as u32;
+
+        if grid == 0 || width / grid == 0 || height / grid == 0 {
+            return Err(DnaError::InvalidDimensions(format!(
+                "{}×{} image cannot be split into a {}×{} tile grid",
+                width, height, grid, grid
+            )));
+        }
+
+        let tile_w = width / grid;
+        let tile_h = height / grid;
+
+        let mut tiles = Vec::with_capacity((grid * grid) as usize);
+        for row in 0..grid {
+            for col in 0..grid {
+                let tile = img.crop_imm(col * tile_w, row * tile_h, tile_w, tile_h);
+                tiles.push(self.extract(&tile)?);
+            }
+        }
+
+        Ok(tiles)
     }
 
     /// Extract DNA from multiple images in batch
     #[cfg(feature = "parallel")]
-    pub fn extract_batch<P: AsRef<Path>>(
+    pub fn extract_batch<P: AsRef<Path> + Sync>(
         &self,
         paths: &[P],
     ) -> Vec<DnaResult<DnaHash>> {
@@ -209,6 +511,76 @@ impl DnaExtractor {
         }
     }
 
+    /// Extract DNA from multiple images on a dedicated pool of `threads` workers
+    ///
+    /// Unlike [`DnaExtractor::extract_batch`], this doesn't touch rayon's global
+    /// pool, so a large batch can't starve other work in the process.
+    #[cfg(feature = "parallel")]
+    pub fn extract_batch_with_threads<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+        threads: usize,
+    ) -> DnaResult<Vec<DnaResult<DnaHash>>> {
+        use rayon::prelude::*;
+
+        // rayon treats 0 as "one per core", which defeats the bound
+        if threads == 0 {
+            return Err(DnaError::ThreadPool("thread count must be non-zero".to_string()));
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| DnaError::ThreadPool(e.to_string()))?;
+
+        Ok(pool.install(|| {
+            paths
+                .par_iter()
+                .map(|path| self.extract_from_path(path))
+                .collect()
+        }))
+    }
+
+    /// Extract DNA from multiple images, aborting with `Cancelled` once `cancel` is set
+    ///
+    /// The flag is checked before each image, so another thread holding the
+    /// same `Arc<AtomicBool>` can stop a long batch; images already in
+    /// flight finish first. Runs in parallel under the same conditions as
+    /// [`DnaExtractor::extract_batch`].
+    pub fn extract_batch_cancellable<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+        cancel: &AtomicBool,
+    ) -> DnaResult<Vec<DnaResult<DnaHash>>> {
+        self.extract_batch_observed(paths, cancel, |_| {})
+    }
+
+    /// [`DnaExtractor::extract_batch_cancellable`], calling `on_extracted`
+    /// with each finished image's index
+    fn extract_batch_observed<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+        cancel: &AtomicBool,
+        on_extracted: impl Fn(usize) + Sync,
+    ) -> DnaResult<Vec<DnaResult<DnaHash>>> {
+        let extract = |(i, path): (usize, &P)| {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(DnaError::Cancelled);
+            }
+            let result = self.extract_from_path(path);
+            on_extracted(i);
+            Ok(result)
+        };
+
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            use rayon::prelude::*;
+            return paths.par_iter().enumerate().map(extract).collect();
+        }
+
+        paths.iter().enumerate().map(extract).collect()
+    }
+
     /// Extract DNA from multiple images in batch (sequential)
     #[cfg(not(feature = "parallel"))]
     pub fn extract_batch<P: AsRef<Path>>(
@@ -222,9 +594,62 @@ impl DnaExtractor {
     }
 }
 
+/// Detect the raster format of `bytes` from its magic bytes
+fn sniff_format(bytes: &[u8]) -> DnaResult<image::ImageFormat> {
+    match image::guess_format(bytes) {
+        Ok(format) if format.reading_enabled() => Ok(format),
+        Ok(format) => Err(DnaError::UnsupportedFormat(format!("{:?}", format))),
+        Err(_) => Err(DnaError::UnsupportedFormat(describe_non_image(bytes).to_string())),
+    }
+}
+
+/// Name common non-image file types for `UnsupportedFormat` errors
+fn describe_non_image(bytes: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF", "PDF"),
+        (b"PK\x03\x04", "ZIP archive"),
+        (b"\x1f\x8b", "gzip archive"),
+        (b"<svg", "SVG"),
+        (b"<?xml", "XML"),
+    ];
+
+    if let Some((_, name)) = SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return name;
+    }
+    if bytes.is_empty() {
+        "empty file"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "text"
+    } else {
+        "unknown"
+    }
+}
+
+/// Fraction of query tiles that match at least one stored tile
+///
+/// A tile matches when its Hamming distance to a stored tile is at most
+/// `threshold` bits. Returns 0.0 when `query_tiles` is empty.
+pub fn tile_overlap(query_tiles: &[DnaHash], stored_tiles: &[DnaHash], threshold: u32) -> f64 {
+    if query_tiles.is_empty() {
+        return 0.0;
+    }
+
+    let matched = query_tiles
+        .iter()
+        .filter(|query| {
+            stored_tiles
+                .iter()
+                .any(|stored| query.is_duplicate_of(stored, threshold))
+        })
+        .count();
+
+    matched as f64 / query_tiles.len() as f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::{checkerboard, noise_image, solid_color};
 
     #[test]
     fn test_dna_hash_creation() {
@@ -264,4 +689,359 @@ mod tests {
 
         assert_eq!(dna1.similarity(&dna2), 1.0);
     }
+
+    #[test]
+    fn test_signature_algorithms() {
+        let dna = DnaHash::new(
+            "0123456789abcdef".to_string(),
+            "0123456789abcdef0123456789abcdef0123456789abcdef".to_string(),
+        );
+
+        let blake3 = dna.signature(SigAlgo::Blake3);
+        let sha256 = dna.signature(SigAlgo::Sha256);
+        assert_eq!(blake3.len(), 64);
+        assert_eq!(sha256.len(), 64);
+        assert_eq!(blake3, dna.blake3_signature());
+        assert_ne!(blake3, sha256);
+    }
+
+    #[test]
+    fn test_tile_overlap_cropped_corner() {
+        let extractor = DnaExtractor::new();
+        let original = noise_image(512, 512, 42);
+        let stored = extractor.extract_tiles(&original, 4).unwrap();
+        assert_eq!(stored.len(), 16);
+
+        // Top-left half-size crop, tiled so its tiles line up with the original's
+        let cropped = original.crop_imm(0, 0, 256, 256);
+        let query = extractor.extract_tiles(&cropped, 2).unwrap();
+        assert_eq!(query.len(), 4);
+
+        assert!(tile_overlap(&query, &stored, 26) >= 0.75);
+
+        // An unrelated image shares nothing with the original's tiles
+        let unrelated = checkerboard(256, 256, 32);
+        let unrelated_tiles = extractor.extract_tiles(&unrelated, 2).unwrap();
+        assert!(tile_overlap(&unrelated_tiles, &stored, 26) < tile_overlap(&query, &stored, 26));
+    }
+
+    #[test]
+    fn test_extract_tiles_rejects_oversized_grid() {
+        let extractor = DnaExtractor::new();
+        assert!(extractor.extract_tiles(&noise_image(4, 4, 1), 8).is_err());
+        assert!(extractor.extract_tiles(&noise_image(4, 4, 1), 0).is_err());
+    }
+
+    #[test]
+    fn test_extract_batch_strict_names_failed_path() {
+        let dir = std::env::temp_dir().join(format!("protrace_dna_strict_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let good = dir.join("good.png");
+        crate::testutil::gradient_image(64, 64).save(&good).unwrap();
+        let missing = dir.join("missing.png");
+
+        let extractor = DnaExtractor::new();
+        assert_eq!(extractor.extract_batch_strict(&[&good, &good]).unwrap().len(), 2);
+
+        let err = extractor
+            .extract_batch_strict(&[&good, &missing, &good])
+            .unwrap_err();
+        assert!(matches!(err, DnaError::ExtractionFailed { .. }));
+        assert!(err.to_string().contains("missing.png"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_quick_dedupe_groups_identical_files() {
+        let dir = std::env::temp_dir().join(format!("protrace_dna_dedupe_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let original = dir.join("original.png");
+        crate::testutil::gradient_image(64, 64).save(&original).unwrap();
+        let copy = dir.join("copy.png");
+        std::fs::copy(&original, &copy).unwrap();
+        let other = dir.join("other.png");
+        noise_image(64, 64, 3).save(&other).unwrap();
+
+        let extractor = DnaExtractor::new();
+        assert_eq!(
+            extractor.content_hash(&original).unwrap(),
+            extractor.content_hash(&copy).unwrap()
+        );
+        assert_ne!(
+            extractor.content_hash(&original).unwrap(),
+            extractor.content_hash(&other).unwrap()
+        );
+
+        let missing = dir.join("missing.png");
+        let groups = extractor.quick_dedupe(&[&other, &original, &missing, &copy]);
+        assert_eq!(groups, vec![vec![1, 3]]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_batch_cancellable() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        let dir = std::env::temp_dir().join(format!("protrace_dna_cancel_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("image.png");
+        noise_image(64, 64, 7).save(&image).unwrap();
+        let paths = vec![&image; 10];
+
+        let extractor = DnaExtractor::new();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let results = extractor
+            .extract_batch_cancellable(&paths, &cancel)
+            .unwrap();
+        assert_eq!(results.len(), 10);
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        // Raise the flag once the third image is done
+        let extracted = AtomicUsize::new(0);
+        let result = extractor.extract_batch_observed(&paths, &cancel, |_| {
+            if extracted.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                cancel.store(true, Ordering::SeqCst);
+            }
+        });
+        assert!(matches!(result, Err(DnaError::Cancelled)));
+        assert_eq!(extracted.load(Ordering::SeqCst), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_extract_batch_with_threads() {
+        let dir = std::env::temp_dir().join(format!("protrace_dna_pool_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths: Vec<_> = (0..4)
+            .map(|i| {
+                let path = dir.join(format!("img{}.png", i));
+                noise_image(64, 64, i).save(&path).unwrap();
+                path
+            })
+            .collect();
+
+        let extractor = DnaExtractor::new();
+        let results = extractor.extract_batch_with_threads(&paths, 2).unwrap();
+        assert_eq!(results.len(), 4);
+        for (path, result) in paths.iter().zip(&results) {
+            assert_eq!(result.as_ref().unwrap(), &extractor.extract_from_path(path).unwrap());
+        }
+
+        assert!(matches!(
+            extractor.extract_batch_with_threads(&paths, 0),
+            Err(DnaError::ThreadPool(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_from_path_sniffs_format() {
+        let dir = std::env::temp_dir().join(format!("protrace_dna_sniff_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let extractor = DnaExtractor::new();
+
+        let png = dir.join("real.png");
+        crate::testutil::gradient_image(64, 64).save(&png).unwrap();
+        assert!(extractor.extract_from_path(&png).is_ok());
+
+        let text = dir.join("notes.png");
+        std::fs::write(&text, "not an image\n").unwrap();
+        let err = extractor.extract_from_path(&text).unwrap_err();
+        assert!(matches!(&err, DnaError::UnsupportedFormat(kind) if kind == "text"));
+
+        let pdf = dir.join("scan.png");
+        std::fs::write(&pdf, b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n").unwrap();
+        let err = extractor.extract_from_path(&pdf).unwrap_err();
+        assert!(err.to_string().contains("PDF"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_color_profile() {
+        // Insert a (fake) iCCP chunk right after IHDR, with a valid CRC
+        let mut plain = Vec::new();
+        noise_image(128, 128, 3)
+            .write_to(
+                &mut std::io::Cursor::new(&mut plain),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+
+        let data = b"profile\0\0\x78\x9c\x03\x00\x00\x00\x00\x01";
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(b"iCCP");
+        chunk.extend_from_slice(data);
+        let crc = crc32(&chunk[4..]);
+        chunk.extend_from_slice(&crc.to_be_bytes());
+
+        let ihdr_end = 8 + 12 + 13;
+        let profiled = [&plain[..ihdr_end], &chunk, &plain[ihdr_end..]].concat();
+
+        let extractor = DnaExtractor::new().with_ignore_color_profile();
+        assert_eq!(
+            extractor.extract_from_bytes(&profiled).unwrap(),
+            extractor.extract_from_bytes(&plain).unwrap()
+        );
+        assert_eq!(
+            color::strip_color_profile(&profiled).as_ref(),
+            plain.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_max_pixels_rejects_decompression_bomb() {
+        let mut png = Vec::new();
+        noise_image(4, 4, 1)
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        let extractor = DnaExtractor::new();
+        assert!(extractor.extract_from_bytes(&png).is_ok());
+        assert!(matches!(
+            DnaExtractor::new()
+                .with_max_pixels(15)
+                .extract_from_bytes(&png),
+            Err(DnaError::InvalidDimensions(_))
+        ));
+
+        // Claim 100k × 100k in IHDR (fixing its CRC); the file stays tiny
+        png[16..20].copy_from_slice(&100_000u32.to_be_bytes());
+        png[20..24].copy_from_slice(&100_000u32.to_be_bytes());
+        let crc = crc32(&png[12..29]);
+        png[29..33].copy_from_slice(&crc.to_be_bytes());
+        assert!(png.len() < 1024);
+
+        let err = extractor.extract_from_bytes(&png).unwrap_err();
+        assert!(matches!(&err, DnaError::InvalidDimensions(msg) if msg.contains("100000×100000")));
+
+        let dir = std::env::temp_dir().join(format!("protrace_dna_bomb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bomb.png");
+        std::fs::write(&path, &png).unwrap();
+        assert!(matches!(
+            extractor.extract_from_path(&path),
+            Err(DnaError::InvalidDimensions(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn test_dhash_crop_sizes() {
+        let img = noise_image(1024, 1024, 7);
+
+        for crop in [256, 512] {
+            let extractor = DnaExtractor {
+                dhash_crop: crop,
+                ..DnaExtractor::new()
+            };
+            let dna = extractor.extract(&img).unwrap();
+            assert_eq!(dna.dhash.len(), 16);
+            assert!(u64::from_str_radix(&dna.dhash, 16).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_fast_mode_dhash() {
+        let img = noise_image(1024, 1024, 5);
+        let dna = DnaExtractor::new().with_fast_mode().extract(&img).unwrap();
+
+        assert_eq!(dna.dhash.len(), 16);
+        assert!(u64::from_str_radix(&dna.dhash, 16).is_ok());
+        assert_eq!(
+            dna.grid_hash,
+            DnaExtractor::new().extract(&img).unwrap().grid_hash
+        );
+    }
+
+    #[test]
+    fn test_compute_dna_flipped() {
+        let extractor = DnaExtractor::new();
+        let original = noise_image(256, 256, 11);
+        let mirrored = original.fliph();
+
+        let original_flipped = extractor.compute_dna_flipped(&original).unwrap();
+        let mirrored_dna = extractor.extract(&mirrored).unwrap();
+        assert_eq!(mirrored_dna, original_flipped);
+
+        let original_dna = extractor.extract(&original).unwrap();
+        assert_ne!(original_dna, original_flipped);
+    }
+
+    #[test]
+    fn test_dhash_distance() {
+        let a = DnaHash::new("0f".repeat(8), "a".repeat(48));
+        let b = DnaHash::new("0f".repeat(8), "b".repeat(48));
+        assert_eq!(a.dhash_distance(&b), 0);
+        assert_eq!(a.dhash_similarity(&b), 1.0);
+        assert!(a.hamming_distance(&b) > 0);
+
+        let c = DnaHash::new(format!("1f{}", "0f".repeat(7)), "a".repeat(48));
+        assert_eq!(a.dhash_distance(&c), 1);
+        assert_eq!(a.dhash_similarity(&c), 1.0 - 1.0 / 64.0);
+    }
+
+    #[test]
+    fn test_dna_hash_display() {
+        let dna = DnaHash::new("a".repeat(16), "b".repeat(48));
+        assert_eq!(dna.to_string(), dna.hex());
+        assert_eq!(format!("{}", dna), format!("{}{}", "a".repeat(16), "b".repeat(48)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dna_hash_serde_roundtrip() {
+        let dna = DnaHash::new("a".repeat(16), "b".repeat(48));
+        let json = serde_json::to_string(&dna).unwrap();
+        let parsed: DnaHash = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, dna);
+    }
+
+    #[test]
+    fn test_solid_color_images_flagged_low_entropy() {
+        let extractor = DnaExtractor::new();
+        let white = extractor.extract_profiled(&solid_color(256, 256, [255, 255, 255])).unwrap();
+        let black = extractor.extract_profiled(&solid_color(256, 256, [0, 0, 0])).unwrap();
+
+        assert!(white.is_low_entropy());
+        assert!(black.is_low_entropy());
+        assert_eq!(white.luminance.mean, 255);
+        assert_eq!(black.luminance.mean, 0);
+
+        // Identical hash bits, but told apart once the profile is considered
+        assert_eq!(white.dna.dhash, black.dna.dhash);
+        assert!(white.dna.is_duplicate_of(&black.dna, 26));
+        assert!(!white.is_duplicate_of(&black, 26));
+        assert!(white.is_duplicate_of(&white.clone(), 26));
+
+        let textured = extractor.extract_profiled(&noise_image(256, 256, 3)).unwrap();
+        assert!(!textured.is_low_entropy());
+        assert_eq!(textured.dna, extractor.extract(&noise_image(256, 256, 3)).unwrap());
+    }
 }
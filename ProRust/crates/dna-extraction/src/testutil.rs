@@ -0,0 +1,84 @@
+//! Deterministic Test Images
+//!
+//! Reproducible image generators for DNA tests, available to downstream
+//! crates through the `test-utils` feature.
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+/// Diagonal RGB gradient
+pub fn gradient_image(width: u32, height: u32) -> DynamicImage {
+    let w = width.max(1) as f32;
+    let h = height.max(1) as f32;
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        let r = (x as f32 / w * 255.0) as u8;
+        let g = (y as f32 / h * 255.0) as u8;
+        let b = ((x + y) as f32 / (w + h) * 255.0) as u8;
+        Rgb([r, g, b])
+    }))
+}
+
+/// Image filled with a single RGB color
+pub fn solid_color(width: u32, height: u32, rgb: [u8; 3]) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb(rgb)))
+}
+
+/// Black and white checkerboard with square cells of `cell` pixels
+pub fn checkerboard(width: u32, height: u32, cell: u32) -> DynamicImage {
+    let cell = cell.max(1);
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        if (x / cell + y / cell) % 2 == 0 {
+            Rgb([255, 255, 255])
+        } else {
+            Rgb([0, 0, 0])
+        }
+    }))
+}
+
+/// Pseudo-random RGB noise; the same seed always yields the same image
+pub fn noise_image(width: u32, height: u32, seed: u64) -> DynamicImage {
+    // xorshift64* — the state must never be zero
+    let mut state = seed ^ 0x9e37_79b9_7f4a_7c15;
+    if state == 0 {
+        state = 1;
+    }
+    let mut next = move || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    };
+
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |_, _| {
+        let v = next().to_le_bytes();
+        Rgb([v[0], v[1], v[2]])
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DnaExtractor;
+
+    #[test]
+    fn test_gradient_image_dna_is_reproducible() {
+        let extractor = DnaExtractor::new();
+        let dna1 = extractor.extract(&gradient_image(256, 256)).unwrap();
+        let dna2 = extractor.extract(&gradient_image(256, 256)).unwrap();
+
+        assert_eq!(dna1, dna2);
+    }
+
+    #[test]
+    fn test_noise_image_seeded() {
+        assert_eq!(noise_image(32, 32, 7), noise_image(32, 32, 7));
+        assert_ne!(noise_image(32, 32, 7), noise_image(32, 32, 8));
+    }
+
+    #[test]
+    fn test_checkerboard_cells() {
+        let img = checkerboard(4, 4, 2).to_rgb8();
+        assert_eq!(img.get_pixel(0, 0), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(2, 0), &Rgb([0, 0, 0]));
+        assert_eq!(img.get_pixel(2, 2), &Rgb([255, 255, 255]));
+    }
+}
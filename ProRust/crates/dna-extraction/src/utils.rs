@@ -2,7 +2,9 @@
 //!
 //! Helper functions for DNA hash comparison and analysis.
 
-// Utility functions - no imports needed
+use std::collections::HashMap;
+
+use crate::index::BkTree;
 
 /// Calculate Hamming distance between two DNA hashes
 ///
@@ -67,6 +69,169 @@ pub fn is_duplicate(hash1: &str, hash2: &str, threshold: u32) -> bool {
     hamming_distance(hash1, hash2) <= threshold
 }
 
+/// Hex characters in the dHash prefix of a 256-bit DNA hash
+const DHASH_HEX_LEN: usize = 16;
+
+/// Distance metric for comparing DNA hashes
+///
+/// All metrics yield a normalized distance from 0.0 (identical) to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimilarityMetric {
+    /// Fraction of differing bits
+    Hamming,
+    /// Jaccard distance over set bits: 1 - |A ∩ B| / |A ∪ B|
+    Jaccard,
+    /// Weighted mean of the dHash and grid component Hamming fractions
+    WeightedComponent { dhash_w: f64, grid_w: f64 },
+}
+
+/// Fraction of differing bits between two equal-length byte slices
+fn bit_difference(bytes1: &[u8], bytes2: &[u8]) -> f64 {
+    let bits = bytes1.len() * 8;
+    if bits == 0 {
+        return 0.0;
+    }
+    let differing: u32 = bytes1
+        .iter()
+        .zip(bytes2)
+        .map(|(b1, b2)| (b1 ^ b2).count_ones())
+        .sum();
+    differing as f64 / bits as f64
+}
+
+/// Calculate the normalized distance between two DNA hashes under `metric`
+///
+/// Returns 1.0 (maximally different) for hashes of mismatched length or
+/// invalid hex, mirroring [`hamming_distance`]. `WeightedComponent` expects
+/// full 256-bit hashes and returns 1.0 unless both weights are finite and
+/// non-negative with a positive sum.
+///
+/// # Example
+///
+/// ```
+/// use protrace_dna::utils::{distance_with_metric, SimilarityMetric};
+///
+/// let hash = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+/// assert_eq!(distance_with_metric(hash, hash, SimilarityMetric::Jaccard), 0.0);
+/// ```
+pub fn distance_with_metric(hash1: &str, hash2: &str, metric: SimilarityMetric) -> f64 {
+    let (Ok(bytes1), Ok(bytes2)) = (hex::decode(hash1), hex::decode(hash2)) else {
+        return 1.0;
+    };
+    if bytes1.len() != bytes2.len() {
+        return 1.0;
+    }
+
+    match metric {
+        SimilarityMetric::Hamming => bit_difference(&bytes1, &bytes2),
+        SimilarityMetric::Jaccard => {
+            let (intersection, union) =
+                bytes1.iter().zip(&bytes2).fold((0, 0), |(i, u), (b1, b2)| {
+                    (i + (b1 & b2).count_ones(), u + (b1 | b2).count_ones())
+                });
+            if union == 0 {
+                0.0
+            } else {
+                1.0 - intersection as f64 / union as f64
+            }
+        }
+        SimilarityMetric::WeightedComponent { dhash_w, grid_w } => {
+            let split = DHASH_HEX_LEN / 2;
+            let valid = |w: f64| w.is_finite() && w >= 0.0;
+            let total_w = dhash_w + grid_w;
+            if bytes1.len() <= split || !valid(dhash_w) || !valid(grid_w) || total_w <= 0.0 {
+                return 1.0;
+            }
+            let dhash = bit_difference(&bytes1[..split], &bytes2[..split]);
+            let grid = bit_difference(&bytes1[split..], &bytes2[split..]);
+            (dhash_w * dhash + grid_w * grid) / total_w
+        }
+    }
+}
+
+/// Check if two hashes represent duplicate images under `metric`
+///
+/// `threshold` is a normalized distance; 26/256 ≈ 0.1 matches the default
+/// Hamming threshold of [`is_duplicate`].
+pub fn is_duplicate_with_metric(
+    hash1: &str,
+    hash2: &str,
+    threshold: f64,
+    metric: SimilarityMetric,
+) -> bool {
+    distance_with_metric(hash1, hash2, metric) <= threshold
+}
+
+/// Byte ranges where two DNA hashes differ, as `(start, len)` spans
+///
+/// Adjacent differing bytes are merged into one span. In a 256-bit DNA,
+/// bytes 0-7 are the dHash and bytes 8-31 the grid hash, so the spans show
+/// which component changed.
+///
+/// # Example
+///
+/// ```
+/// use protrace_dna::utils::diff_regions;
+///
+/// let hash1 = "0123456789abcdef0000000000000000000000000000000000000000000000ff";
+/// let hash2 = "0123456789abcdef000000000000000000000000000000000000000000000000";
+/// assert_eq!(diff_regions(hash1, hash2).unwrap(), vec![(31, 1)]);
+/// ```
+pub fn diff_regions(hash1: &str, hash2: &str) -> Result<Vec<(usize, usize)>, crate::DnaError> {
+    let decode =
+        |hash: &str| hex::decode(hash).map_err(|e| crate::DnaError::InvalidFormat(e.to_string()));
+    let (bytes1, bytes2) = (decode(hash1)?, decode(hash2)?);
+    if bytes1.len() != bytes2.len() {
+        return Err(crate::DnaError::InvalidFormat(format!(
+            "hash lengths differ: {} vs {} bytes",
+            bytes1.len(),
+            bytes2.len()
+        )));
+    }
+
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for (i, (b1, b2)) in bytes1.iter().zip(&bytes2).enumerate() {
+        if b1 == b2 {
+            continue;
+        }
+        match spans.last_mut() {
+            Some((start, len)) if *start + *len == i => *len += 1,
+            _ => spans.push((i, 1)),
+        }
+    }
+
+    Ok(spans)
+}
+
+/// Lowercase a hex string, rejecting odd lengths and non-hex characters
+///
+/// Tools disagree on hex casing, and `AbC123` and `abc123` name the same
+/// bytes but compare unequal as strings. Normalize before comparing.
+///
+/// # Example
+///
+/// ```
+/// use protrace_dna::utils::normalize_hex;
+///
+/// assert_eq!(normalize_hex("AbC123").unwrap(), "abc123");
+/// assert!(normalize_hex("xyz0").is_err());
+/// ```
+pub fn normalize_hex(s: &str) -> Result<String, crate::DnaError> {
+    if s.len() % 2 != 0 {
+        return Err(crate::DnaError::InvalidFormat(format!(
+            "odd hex length: {}",
+            s.len()
+        )));
+    }
+    if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(crate::DnaError::InvalidFormat(format!(
+            "invalid hex character {:?}",
+            c
+        )));
+    }
+    Ok(s.to_ascii_lowercase())
+}
+
 /// Convert hex string to binary string
 pub fn hex_to_binary(hex: &str) -> String {
     let bytes = hex::decode(hex).unwrap_or_default();
@@ -108,6 +273,74 @@ pub fn find_duplicate_pairs(
     duplicates
 }
 
+/// Find all duplicate pairs using a BK-tree instead of comparing every pair
+///
+/// Returns the same `(index1, index2, hamming_distance)` edges as
+/// [`find_duplicate_pairs`], sorted by index, but only visits the parts of
+/// the index within `threshold` of each hash, so large batches with few
+/// duplicates stay far below O(n²). Hashes the index rejects (malformed, or
+/// a different length than the first valid one) have no edges.
+pub fn duplicate_graph(hashes: &[String], threshold: u32) -> Vec<(usize, usize, u32)> {
+    // The index stores each distinct hash once; keep every index it came from
+    let mut indices: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut index = BkTree::new();
+    for (i, hash) in hashes.iter().enumerate() {
+        if index.insert(hash).is_ok() {
+            indices.entry(hash.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (hash, from) in &indices {
+        for (other, distance) in index.query(hash, threshold) {
+            for &i in from {
+                for &j in &indices[other] {
+                    if i < j {
+                        edges.push((i, j, distance));
+                    }
+                }
+            }
+        }
+    }
+
+    edges.sort_unstable();
+    edges
+}
+
+/// Probability that two random `bits`-bit hashes are within `threshold` bits
+///
+/// The binomial tail `Σ_{k=0}^{threshold} C(bits, k) / 2^bits`, i.e. the
+/// false-positive rate of [`is_duplicate`] for unrelated images if hash bits
+/// were independent and uniform. Terms are summed in log space so large
+/// `bits` do not underflow to zero.
+///
+/// # Example
+///
+/// ```
+/// use protrace_dna::utils::collision_probability;
+///
+/// assert_eq!(collision_probability(256, 256), 1.0);
+/// assert!(collision_probability(26, 256) < 1e-40);
+/// ```
+pub fn collision_probability(threshold: u32, bits: u32) -> f64 {
+    if threshold >= bits {
+        return 1.0;
+    }
+
+    let n = bits as f64;
+    let mut ln_choose = 0.0;
+    let mut total = 0.0;
+    for k in 0..=threshold {
+        if k > 0 {
+            // ln C(n, k) = ln C(n, k - 1) + ln((n - k + 1) / k)
+            ln_choose += ((n - k as f64 + 1.0) / k as f64).ln();
+        }
+        total += (ln_choose - n * std::f64::consts::LN_2).exp();
+    }
+
+    total.min(1.0)
+}
+
 /// Compute BLAKE3 hash of a DNA fingerprint
 pub fn blake3_signature(dna_hex: &str) -> String {
     hex::encode(blake3::hash(dna_hex.as_bytes()).as_bytes())
@@ -125,6 +358,63 @@ pub fn sha256_signature(dna_hex: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_collision_probability() {
+        let exact = collision_probability(0, 256);
+        assert!((exact / 2f64.powi(-256) - 1.0).abs() < 1e-9);
+
+        // C(256, 1) = 256 more ways to be one bit apart
+        assert!((collision_probability(1, 256) / (257.0 * 2f64.powi(-256)) - 1.0).abs() < 1e-9);
+
+        let mut previous = 0.0;
+        for threshold in 0..=256 {
+            let p = collision_probability(threshold, 256);
+            // Far past the midpoint the added terms fall below f64 precision
+            assert!(p >= previous, "decreasing at threshold {}", threshold);
+            assert!(threshold > 128 || p > previous);
+            previous = p;
+        }
+        assert!((previous - 1.0).abs() < 1e-9);
+        assert!((collision_probability(128, 256) - 0.5).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_duplicate_graph_matches_brute_force() {
+        // xorshift64 so the batch is the same on every run
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut hashes = Vec::new();
+        while hashes.len() < 300 {
+            let base: Vec<u8> = (0..32).map(|_| next() as u8).collect();
+            hashes.push(hex::encode(&base));
+            // Near-duplicates straddling the threshold, plus an exact copy
+            for _ in 0..4 {
+                let mut variant = base.clone();
+                for _ in 0..next() % 40 {
+                    let bit = next() % 256;
+                    variant[(bit / 8) as usize] ^= 1 << (bit % 8);
+                }
+                hashes.push(hex::encode(&variant));
+            }
+            hashes.push(hex::encode(&base));
+        }
+        hashes.truncate(300);
+
+        let brute_force = find_duplicate_pairs(&hashes, 26);
+        assert!(brute_force.len() > 100);
+        assert_eq!(duplicate_graph(&hashes, 26), brute_force);
+        assert_eq!(
+            duplicate_graph(&hashes, 0),
+            find_duplicate_pairs(&hashes, 0)
+        );
+    }
+
     #[test]
     fn test_hamming_distance_identical() {
         let hash = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
@@ -184,4 +474,106 @@ mod tests {
         assert_eq!(pairs.len(), 1); // Only first two are similar enough
         assert_eq!(pairs[0], (0, 1, 1));
     }
+
+    #[test]
+    fn test_metrics_on_grid_only_difference() {
+        // Identical dHash, 8 differing bits in the grid component
+        let hash1 = "0123456789abcdef0000000000000000000000000000000000000000000000ff";
+        let hash2 = "0123456789abcdef000000000000000000000000000000000000000000000000";
+
+        let hamming = distance_with_metric(hash1, hash2, SimilarityMetric::Hamming);
+        assert_eq!(hamming, 8.0 / 256.0);
+        assert_eq!(hamming, 1.0 - similarity(hash1, hash2));
+
+        // Weighting only the dHash ignores the grid difference entirely
+        let dhash_only = SimilarityMetric::WeightedComponent {
+            dhash_w: 1.0,
+            grid_w: 0.0,
+        };
+        assert_eq!(distance_with_metric(hash1, hash2, dhash_only), 0.0);
+
+        // Weighting only the grid scores it against 192 bits, not 256
+        let grid_only = SimilarityMetric::WeightedComponent {
+            dhash_w: 0.0,
+            grid_w: 1.0,
+        };
+        let grid = distance_with_metric(hash1, hash2, grid_only);
+        assert_eq!(grid, 8.0 / 192.0);
+        assert!(grid > hamming);
+
+        assert!(is_duplicate_with_metric(hash1, hash2, 0.0, dhash_only));
+        assert!(!is_duplicate_with_metric(
+            hash1,
+            hash2,
+            0.0,
+            SimilarityMetric::Hamming
+        ));
+    }
+
+    #[test]
+    fn test_weighted_component_rejects_invalid_weights() {
+        let hash1 = "0123456789abcdef0000000000000000000000000000000000000000000000ff";
+        let hash2 = "0123456789abcdef000000000000000000000000000000000000000000000000";
+
+        // A negative weight could otherwise cancel out a real difference
+        for (dhash_w, grid_w) in [
+            (2.0, -1.0),
+            (-1.0, 2.0),
+            (f64::NAN, 1.0),
+            (1.0, f64::INFINITY),
+            (0.0, 0.0),
+        ] {
+            let metric = SimilarityMetric::WeightedComponent { dhash_w, grid_w };
+            assert_eq!(distance_with_metric(hash1, hash2, metric), 1.0);
+            assert_eq!(distance_with_metric(hash1, hash1, metric), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_diff_regions_grid_only() {
+        let hash1 = "0123456789abcdef00ff00000000000000000000aabbcc0000000000000000ff";
+        let hash2 = "0123456789abcdef0000000000000000000000000000000000000000000000ee";
+
+        let spans = diff_regions(hash1, hash2).unwrap();
+        assert_eq!(spans, vec![(9, 1), (20, 3), (31, 1)]);
+        assert!(spans
+            .iter()
+            .all(|&(start, len)| start >= 8 && start + len <= 32));
+
+        assert!(diff_regions(hash1, hash1).unwrap().is_empty());
+        assert!(diff_regions(hash1, "00").is_err());
+        assert!(diff_regions(hash1, &"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_hex() {
+        let root = blake3_signature("root");
+        let upper = root.to_uppercase();
+
+        // Same bytes, but the raw string comparison fails
+        assert_ne!(upper, root);
+        assert_eq!(normalize_hex(&upper).unwrap(), root);
+        assert_eq!(normalize_hex(&root).unwrap(), root);
+
+        assert!(normalize_hex("abc").is_err());
+        assert!(normalize_hex("0g").is_err());
+    }
+
+    #[test]
+    fn test_jaccard_distance() {
+        let hash1 = "0f";
+        let hash2 = "ff";
+        assert_eq!(
+            distance_with_metric(hash1, hash2, SimilarityMetric::Jaccard),
+            0.5
+        );
+        assert_eq!(
+            distance_with_metric("00", "00", SimilarityMetric::Jaccard),
+            0.0
+        );
+        assert_eq!(
+            distance_with_metric("00", "0000", SimilarityMetric::Jaccard),
+            1.0
+        );
+    }
 }
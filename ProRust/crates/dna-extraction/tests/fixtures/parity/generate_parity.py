@@ -0,0 +1,66 @@
+"""
+Regenerate parity.json from the Python DNA implementation.
+
+Usage (from the repository root, with ProPy requirements installed):
+
+    python ProRust/crates/dna-extraction/tests/fixtures/parity/generate_parity.py
+
+Place a real photograph at photo.jpg before running; the other fixtures are
+committed alongside this script.
+"""
+
+import json
+import platform
+import sys
+from pathlib import Path
+
+import numpy
+import PIL
+import scipy
+
+FIXTURE_DIR = Path(__file__).resolve().parent
+REPO_ROOT = FIXTURE_DIR.parents[5]
+sys.path.insert(0, str(REPO_ROOT / "ProPy" / "modules"))
+
+from protrace_legacy.image_dna import compute_dna  # noqa: E402
+
+GENERATOR_VERSION = 2
+
+IMAGES = {
+    "photo": "photo.jpg",
+    "textured": "textured.png",
+    "gradient": "gradient.png",
+    "flat": "flat.png",
+}
+
+
+def main():
+    missing = [image for image in IMAGES.values() if not (FIXTURE_DIR / image).is_file()]
+    if missing:
+        sys.exit(f"missing fixture images in {FIXTURE_DIR}: {', '.join(missing)}")
+
+    vectors = []
+    for name, image in IMAGES.items():
+        dna = compute_dna(str(FIXTURE_DIR / image))
+        vectors.append({"name": name, "image": image, "dna_hex": dna["dna_hex"]})
+
+    fixture = {
+        "source": "python",
+        "generator": {
+            "script": Path(__file__).name,
+            "version": GENERATOR_VERSION,
+            "reference": "ProPy/modules/protrace_legacy/image_dna.py",
+            "python": platform.python_version(),
+            "pillow": PIL.__version__,
+            "numpy": numpy.__version__,
+            "scipy": scipy.__version__,
+        },
+        "vectors": vectors,
+    }
+    with open(FIXTURE_DIR / "parity.json", "w") as f:
+        json.dump(fixture, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,76 @@
+//! Parity test vectors
+//!
+//! Frozen `dna_hex` values for committed fixture images in
+//! `tests/fixtures/parity`. Any change to the extraction algorithm that
+//! alters these values must regenerate the fixture deliberately
+//! (see `tests/fixtures/parity/generate_parity.py`).
+//!
+//! Until that script has been run against the Python reference the vectors
+//! are Rust-recorded (`"source": "rust"`) and only guard against drift;
+//! `test_parity_fixture_is_python_generated` stays ignored until then.
+
+use protrace_dna::DnaExtractor;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Deserialize)]
+struct Fixture {
+    source: String,
+    #[serde(default)]
+    generator: Option<Generator>,
+    vectors: Vec<Vector>,
+}
+
+#[derive(Deserialize)]
+struct Generator {
+    script: String,
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Vector {
+    name: String,
+    image: String,
+    dna_hex: String,
+}
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/parity")
+}
+
+fn load_fixture() -> Fixture {
+    let data = std::fs::read_to_string(fixture_dir().join("parity.json")).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+#[test]
+fn test_dna_matches_parity_vectors() {
+    let fixture = load_fixture();
+    assert!(fixture.vectors.len() >= 3);
+
+    let extractor = DnaExtractor::new();
+    for vector in &fixture.vectors {
+        let dna = extractor
+            .extract_from_path(fixture_dir().join(&vector.image))
+            .unwrap();
+        assert_eq!(
+            dna.hex(),
+            vector.dna_hex,
+            "{} drifted from its {} parity vector",
+            vector.name,
+            fixture.source
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs photo.jpg and a generate_parity.py run with Pillow, numpy and scipy"]
+fn test_parity_fixture_is_python_generated() {
+    let fixture = load_fixture();
+    assert_eq!(fixture.source, "python");
+
+    let generator = fixture.generator.expect("fixture records its generator");
+    assert_eq!(generator.script, "generate_parity.py");
+    assert!(generator.version >= 2);
+    assert!(fixture.vectors.iter().any(|vector| vector.name == "photo"));
+}
@@ -0,0 +1,53 @@
+//! Proof Bundle Verifier
+//!
+//! Checks a standalone proof bundle without the full CLI or Solana dependencies.
+//! Prints VALID or INVALID and exits with 0 or 1; unreadable bundles exit with 2.
+
+#[cfg(feature = "cli")]
+use clap::Parser;
+#[cfg(feature = "cli")]
+use protrace_merkle::ProofBundle;
+#[cfg(feature = "cli")]
+use std::path::PathBuf;
+
+#[cfg(feature = "cli")]
+#[derive(Parser)]
+#[command(name = "verify")]
+#[command(about = "Verify a ProTrace Merkle proof bundle", long_about = None)]
+struct Cli {
+    /// Path to proof bundle JSON
+    #[arg(short, long)]
+    bundle: PathBuf,
+}
+
+#[cfg(feature = "cli")]
+fn load_bundle(path: &PathBuf) -> Result<ProofBundle, Box<dyn std::error::Error>> {
+    let data = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+#[cfg(feature = "cli")]
+fn main() {
+    let cli = Cli::parse();
+
+    let result = load_bundle(&cli.bundle).and_then(|bundle| Ok(bundle.verify()?));
+
+    match result {
+        Ok(true) => println!("VALID"),
+        Ok(false) => {
+            println!("INVALID");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error reading {}: {}", cli.bundle.display(), e);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+fn main() {
+    eprintln!("This binary requires the 'cli' feature to be enabled.");
+    eprintln!("Build with: cargo build --features cli");
+    std::process::exit(1);
+}
@@ -31,19 +31,24 @@
 //! assert!(tree.verify_proof(0, &proof, &root).unwrap());
 //! ```
 
-use blake3;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
 use thiserror::Error;
 
 /// Merkle tree errors
+///
+/// `EmptyTree`, `TreeNotBuilt`, `LeafIndexOutOfRange`, `InvalidProof`,
+/// `RootMismatch` and `InvalidHex` have the same names and meanings in the
+/// ProPy binding's merkle crate, so a `match` on them works against either.
 #[derive(Error, Debug)]
 pub enum MerkleError {
     #[error("Tree is empty")]
     EmptyTree,
 
-    #[error("Invalid leaf index: {0}")]
-    InvalidIndex(usize),
+    #[error("Leaf index out of range: {0}")]
+    LeafIndexOutOfRange(usize),
 
     #[error("Tree not built")]
     TreeNotBuilt,
@@ -51,15 +56,34 @@ pub enum MerkleError {
     #[error("Invalid proof")]
     InvalidProof,
 
+    #[error("Root mismatch")]
+    RootMismatch,
+
     #[error("Invalid hex encoding: {0}")]
     InvalidHex(#[from] hex::FromHexError),
+
+    #[error("Invalid hash length: {0} bytes (must be {MIN_ROOT_BYTES}-32)")]
+    InvalidHashLength(usize),
+
+    #[error("Tree leaves are not sorted")]
+    NotSorted,
+
+    #[error("DNA is registered: {0}")]
+    DnaRegistered(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
+/// Shortest hash length accepted by [`MerkleTree::with_root_bytes`]
+pub const MIN_ROOT_BYTES: usize = 16;
+
 /// Result type for Merkle operations
 pub type MerkleResult<T> = Result<T, MerkleError>;
 
 /// Merkle tree node
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct MerkleNode {
     hash: Vec<u8>,
     left: Option<Box<MerkleNode>>,
@@ -68,23 +92,27 @@ struct MerkleNode {
 }
 
 impl MerkleNode {
-    /// Create leaf node
-    fn leaf(data: &[u8]) -> Self {
-        let hash = blake3::hash(data).as_bytes().to_vec();
+    /// Create leaf node from a precomputed (possibly truncated) leaf hash
+    fn from_hash(hash: &[u8]) -> Self {
         Self {
-            hash,
+            hash: hash.to_vec(),
             left: None,
             right: None,
             is_leaf: true,
         }
     }
 
-    /// Create internal node
-    fn internal(left: MerkleNode, right: MerkleNode) -> Self {
+    /// Create internal node, truncating its hash to `hash_len` bytes
+    fn internal(
+        left: MerkleNode,
+        right: MerkleNode,
+        hash_len: usize,
+        key: Option<&[u8; 32]>,
+    ) -> Self {
         let mut combined = left.hash.clone();
         combined.extend_from_slice(&right.hash);
-        let hash = blake3::hash(&combined).as_bytes().to_vec();
-        
+        let hash = node_hash(key, &combined)[..hash_len].to_vec();
+
         Self {
             hash,
             left: Some(Box::new(left)),
@@ -108,12 +136,123 @@ pub struct ProofElement {
     pub position: String,
 }
 
+/// Self-contained proof that a leaf is committed by a root
+///
+/// Same JSON format as the ProPy binding's `ProofBundle`, so bundles from
+/// `protrace merkle export-proofs` verify here. Carries the raw leaf
+/// `data`, the precomputed `leaf_hash`, or both; when both are present the
+/// hash is recomputed from the data and cross-checked. `namespace` is the
+/// hex-encoded prefix hashed before the data by namespaced trees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofBundle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_hash: Option<String>,
+    pub proof: Vec<ProofElement>,
+    pub root: String,
+}
+
+impl ProofBundle {
+    /// Verify the bundle's proof against its root
+    ///
+    /// Fails with `InvalidProof` if the bundle carries neither data nor a
+    /// leaf hash, and with `InvalidHashLength` if the leaf hash isn't 32
+    /// bytes; returns `false` if data and leaf hash disagree. As in the
+    /// binding, a hash-only bundle with an empty proof is `InvalidProof`.
+    pub fn verify(&self) -> MerkleResult<bool> {
+        let data_hash = match &self.data {
+            Some(data) => {
+                let mut hasher = blake3::Hasher::new();
+                if let Some(namespace) = &self.namespace {
+                    hasher.update(&hex::decode(namespace)?);
+                }
+                hasher.update(data.as_bytes());
+                Some(*hasher.finalize().as_bytes())
+            }
+            None => None,
+        };
+        let stored_hash = match &self.leaf_hash {
+            Some(leaf_hash) => {
+                let bytes = hex::decode(leaf_hash)?;
+                let len = bytes.len();
+                Some(<[u8; 32]>::try_from(bytes).map_err(|_| MerkleError::InvalidHashLength(len))?)
+            }
+            None => None,
+        };
+
+        let leaf_hash = match (data_hash, stored_hash) {
+            (Some(computed), Some(stored)) if computed != stored => return Ok(false),
+            (Some(hash), _) => hash,
+            (None, Some(_)) if self.proof.is_empty() => return Err(MerkleError::InvalidProof),
+            (None, Some(hash)) => hash,
+            (None, None) => return Err(MerkleError::InvalidProof),
+        };
+        verify_proof_from_leaf_hash(&leaf_hash, &self.proof, &self.root)
+    }
+}
+
+/// Registered leaf adjacent to an absent DNA, with its inclusion proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketLeaf {
+    /// Raw leaf data (`dna_hex|pointer|platform_id|timestamp`)
+    pub leaf: String,
+    pub proof: Vec<ProofElement>,
+}
+
+/// Proof that a DNA is absent from a sorted tree
+///
+/// `left` and `right` are the adjacent leaves whose DNAs bracket
+/// `dna_hex`. `left` is `None` when the DNA sorts before every leaf, and
+/// `right` is `None` when it sorts after every leaf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonMembershipProof {
+    pub dna_hex: String,
+    pub left: Option<BracketLeaf>,
+    pub right: Option<BracketLeaf>,
+}
+
+/// DNA part of raw leaf data, i.e. everything before the first `|`
+fn leaf_dna(leaf: &[u8]) -> &[u8] {
+    leaf.split(|&b| b == b'|').next().unwrap_or(leaf)
+}
+
+type LeafHashFn = dyn Fn(&[u8]) -> [u8; 32] + Send + Sync;
+
+/// Caller-supplied leaf hash function, see [`MerkleTree::with_leaf_hasher`]
+struct LeafHasher(Box<LeafHashFn>);
+
+impl std::fmt::Debug for LeafHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LeafHasher(..)")
+    }
+}
+
 /// Balanced binary Merkle tree
 #[derive(Debug)]
 pub struct MerkleTree {
     leaves: Vec<Vec<u8>>,
     root: Option<MerkleNode>,
     leaf_map: HashMap<Vec<u8>, usize>,
+    leaf_hash_cache: Option<Vec<[u8; 32]>>,
+    /// Length of every node hash in the tree (leaf and internal)
+    root_bytes: usize,
+    /// BLAKE3 key for keyed hashing of every node, if any
+    key: Option<[u8; 32]>,
+    /// Order leaves by DNA on build, enabling non-membership proofs
+    sorted: bool,
+    /// Replaces BLAKE3 for leaf hashes only; internal nodes are unaffected
+    leaf_hasher: Option<LeafHasher>,
+}
+
+/// BLAKE3 hash of `data`, keyed when `key` is set
+fn node_hash(key: Option<&[u8; 32]>, data: &[u8]) -> [u8; 32] {
+    match key {
+        Some(key) => *blake3::keyed_hash(key, data).as_bytes(),
+        None => *blake3::hash(data).as_bytes(),
+    }
 }
 
 impl Default for MerkleTree {
@@ -129,6 +268,79 @@ impl MerkleTree {
             leaves: Vec::new(),
             root: None,
             leaf_map: HashMap::new(),
+            leaf_hash_cache: None,
+            root_bytes: 32,
+            key: None,
+            sorted: false,
+            leaf_hasher: None,
+        }
+    }
+
+    /// Create new empty Merkle tree whose leaves are sorted by DNA on build
+    ///
+    /// `build_tree` reorders the leaves by their DNA hex (ties broken by the
+    /// remaining fields), so leaf indices reflect sorted order rather than
+    /// insertion order. Sorted trees support [`MerkleTree::non_membership_proof`].
+    pub fn with_sorted_leaves() -> Self {
+        Self {
+            sorted: true,
+            ..Self::new()
+        }
+    }
+
+    /// Create new empty Merkle tree using BLAKE3 keyed hashing
+    ///
+    /// Leaf and internal hashes are all keyed, so the root can't be linked
+    /// to its leaves without `key`, and proofs only verify against a tree
+    /// with the same key. The standalone verifiers are unkeyed.
+    pub fn with_keyed_hash(key: [u8; 32]) -> Self {
+        Self {
+            key: Some(key),
+            ..Self::new()
+        }
+    }
+
+    /// Create new empty Merkle tree that hashes leaves with `leaf_hasher`
+    ///
+    /// Only leaf hashes change; internal nodes stay BLAKE3 of the two child
+    /// hashes. Proofs verify against the root with [`MerkleTree::verify_proof`]
+    /// or, given the custom leaf hash, [`verify_proof_from_leaf_hash`]; the
+    /// other standalone verifiers assume BLAKE3 leaves.
+    pub fn with_leaf_hasher(
+        leaf_hasher: impl Fn(&[u8]) -> [u8; 32] + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            leaf_hasher: Some(LeafHasher(Box::new(leaf_hasher))),
+            ..Self::new()
+        }
+    }
+
+    /// Create new empty Merkle tree using BLAKE3 hashes truncated to `root_bytes`
+    ///
+    /// Leaf and internal hashes are truncated alike, so roots and proof
+    /// elements are `root_bytes` long. Values below [`MIN_ROOT_BYTES`] or
+    /// above 32 are rejected.
+    pub fn with_root_bytes(root_bytes: usize) -> MerkleResult<Self> {
+        if !(MIN_ROOT_BYTES..=32).contains(&root_bytes) {
+            return Err(MerkleError::InvalidHashLength(root_bytes));
+        }
+
+        Ok(Self {
+            root_bytes,
+            ..Self::new()
+        })
+    }
+
+    /// Get the node hash length in bytes
+    pub fn root_bytes(&self) -> usize {
+        self.root_bytes
+    }
+
+    /// Full 32-byte hash of one leaf's data
+    fn leaf_hash(&self, leaf: &[u8]) -> [u8; 32] {
+        match &self.leaf_hasher {
+            Some(LeafHasher(hasher)) => hasher(leaf),
+            None => node_hash(self.key.as_ref(), leaf),
         }
     }
 
@@ -143,11 +355,28 @@ impl MerkleTree {
     /// * `platform_id` - Platform identifier
     /// * `timestamp` - Unix timestamp
     pub fn add_leaf(&mut self, dna_hex: &str, pointer: &str, platform_id: &str, timestamp: u64) {
-        let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-        let leaf_bytes = leaf_data.as_bytes().to_vec();
+        let leaf_bytes = encode_leaf(dna_hex, pointer, platform_id, timestamp);
 
         self.leaf_map.insert(leaf_bytes.clone(), self.leaves.len());
         self.leaves.push(leaf_bytes);
+        self.leaf_hash_cache = None;
+        self.root = None;
+    }
+
+    /// Add registration leaf using the ProPy binding's field semantics
+    ///
+    /// Identical to [`MerkleTree::add_leaf`] but takes a signed timestamp,
+    /// matching `protrace_merkle_tree::MerkleTree::add_leaf`, so services
+    /// holding DNA hex can build trees whose leaves hash the same in both
+    /// crates without re-decoding images.
+    pub fn add_registration(
+        &mut self,
+        dna_hex: &str,
+        pointer: &str,
+        platform_id: &str,
+        timestamp: i64,
+    ) {
+        self.add_raw_leaf(&encode_leaf(dna_hex, pointer, platform_id, timestamp));
     }
 
     /// Add raw leaf data
@@ -155,6 +384,8 @@ impl MerkleTree {
         let leaf_bytes = data.to_vec();
         self.leaf_map.insert(leaf_bytes.clone(), self.leaves.len());
         self.leaves.push(leaf_bytes);
+        self.leaf_hash_cache = None;
+        self.root = None;
     }
 
     /// Get number of leaves
@@ -162,23 +393,113 @@ impl MerkleTree {
         self.leaves.len()
     }
 
+    /// Check whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Number of levels above the leaves, i.e. the length of every proof
+    ///
+    /// A single-leaf tree has depth 0: its root is the leaf hash itself.
+    pub fn depth(&self) -> usize {
+        self.leaves.len().next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Check whether the tree is built and up to date with its leaves
+    ///
+    /// Adding a leaf invalidates the build until `build_tree` is called again.
+    pub fn is_built(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// Get BLAKE3 hash of every leaf, in insertion order
+    ///
+    /// Served from the cache filled by `build_tree` when available,
+    /// otherwise computed on the fly. These are full 32-byte digests;
+    /// the tree itself uses their first `root_bytes` bytes.
+    pub fn leaf_hashes(&self) -> Vec<[u8; 32]> {
+        match &self.leaf_hash_cache {
+            Some(hashes) => hashes.clone(),
+            None => self
+                .leaves
+                .iter()
+                .map(|leaf| self.leaf_hash(leaf))
+                .collect(),
+        }
+    }
+
+    /// Iterate over `(index, leaf_bytes, leaf_hash)` for every leaf
+    ///
+    /// Uses the leaf hashes cached by `build_tree` when available.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = (usize, &[u8], [u8; 32])> + '_ {
+        self.leaves.iter().enumerate().map(move |(i, leaf)| {
+            let hash = match &self.leaf_hash_cache {
+                Some(hashes) => hashes[i],
+                None => self.leaf_hash(leaf),
+            };
+            (i, leaf.as_slice(), hash)
+        })
+    }
+
     /// Build balanced binary Merkle tree from leaves
     ///
     /// Returns root hash as hex string
     pub fn build_tree(&mut self) -> MerkleResult<String> {
+        self.build_tree_cancellable(&AtomicBool::new(false))
+    }
+
+    /// Build the tree, aborting with `Cancelled` once `cancel` is set
+    ///
+    /// The flag is checked before each leaf hash and each level, so another
+    /// thread holding the same `Arc<AtomicBool>` can stop a large build. A
+    /// cancelled build leaves the tree unbuilt.
+    pub fn build_tree_cancellable(&mut self, cancel: &AtomicBool) -> MerkleResult<String> {
+        let result = self.build_levels(cancel);
+        if matches!(result, Err(MerkleError::Cancelled)) {
+            self.root = None;
+        }
+        result
+    }
+
+    fn build_levels(&mut self, cancel: &AtomicBool) -> MerkleResult<String> {
         if self.leaves.is_empty() {
             return Err(MerkleError::EmptyTree);
         }
 
+        if self.sorted {
+            self.leaves
+                .sort_by(|a, b| leaf_dna(a).cmp(leaf_dna(b)).then_with(|| a.cmp(b)));
+            self.leaf_map = self
+                .leaves
+                .iter()
+                .enumerate()
+                .map(|(i, leaf)| (leaf.clone(), i))
+                .collect();
+            self.leaf_hash_cache = None;
+        }
+
+        let leaf_hashes = match &self.leaf_hash_cache {
+            Some(hashes) => hashes.clone(),
+            None => self
+                .leaves
+                .iter()
+                .map(|leaf| {
+                    check_cancelled(cancel)?;
+                    Ok(self.leaf_hash(leaf))
+                })
+                .collect::<MerkleResult<Vec<_>>>()?,
+        };
+
         // Create leaf nodes
-        let mut nodes: Vec<MerkleNode> = self
-            .leaves
+        let mut nodes: Vec<MerkleNode> = leaf_hashes
             .iter()
-            .map(|leaf| MerkleNode::leaf(leaf))
+            .map(|hash| MerkleNode::from_hash(&hash[..self.root_bytes]))
             .collect();
+        self.leaf_hash_cache = Some(leaf_hashes);
 
         // Build tree bottom-up
         while nodes.len() > 1 {
+            check_cancelled(cancel)?;
             let mut next_level = Vec::new();
 
             for i in (0..nodes.len()).step_by(2) {
@@ -190,7 +511,12 @@ impl MerkleTree {
                     nodes[i].clone()
                 };
 
-                next_level.push(MerkleNode::internal(left, right));
+                next_level.push(MerkleNode::internal(
+                    left,
+                    right,
+                    self.root_bytes,
+                    self.key.as_ref(),
+                ));
             }
 
             nodes = next_level;
@@ -200,6 +526,17 @@ impl MerkleTree {
         Ok(self.root.as_ref().unwrap().hash_hex())
     }
 
+    /// Build the tree and check that its root equals `expected_root` (hex)
+    ///
+    /// Returns `RootMismatch` if it differs. The tree stays built either
+    /// way, so the actual root can still be read with `get_root`.
+    pub fn build_tree_expecting(&mut self, expected_root: &str) -> MerkleResult<()> {
+        if !root_matches(&self.build_tree()?, expected_root) {
+            return Err(MerkleError::RootMismatch);
+        }
+        Ok(())
+    }
+
     /// Get Merkle root hash
     pub fn get_root(&self) -> MerkleResult<String> {
         match &self.root {
@@ -221,7 +558,7 @@ impl MerkleTree {
     /// Returns vector of sibling hashes along path to root
     pub fn get_proof(&self, index: usize) -> MerkleResult<Vec<ProofElement>> {
         if index >= self.leaves.len() {
-            return Err(MerkleError::InvalidIndex(index));
+            return Err(MerkleError::LeafIndexOutOfRange(index));
         }
 
         if self.root.is_none() {
@@ -233,9 +570,9 @@ impl MerkleTree {
 
         // Create leaf nodes for proof generation
         let mut nodes: Vec<MerkleNode> = self
-            .leaves
+            .leaf_hashes()
             .iter()
-            .map(|leaf| MerkleNode::leaf(leaf))
+            .map(|hash| MerkleNode::from_hash(&hash[..self.root_bytes]))
             .collect();
 
         // Build proof by traversing tree levels
@@ -247,20 +584,18 @@ impl MerkleTree {
                 leaf_index - 1
             };
 
-            // Get sibling hash
-            if sibling_index < nodes.len() {
-                let sibling = &nodes[sibling_index];
-                let position = if leaf_index % 2 == 0 {
-                    "right"
-                } else {
-                    "left"
-                };
-                
-                proof.push(ProofElement {
-                    hash: sibling.hash_hex(),
-                    position: position.to_string(),
-                });
-            }
+            // Get sibling hash (the last node of an odd level pairs with itself)
+            let sibling = nodes.get(sibling_index).unwrap_or(&nodes[leaf_index]);
+            let position = if leaf_index % 2 == 0 {
+                "right"
+            } else {
+                "left"
+            };
+
+            proof.push(ProofElement {
+                hash: sibling.hash_hex(),
+                position: position.to_string(),
+            });
 
             // Build next level
             for i in (0..nodes.len()).step_by(2) {
@@ -271,7 +606,12 @@ impl MerkleTree {
                     nodes[i].clone()
                 };
 
-                next_level.push(MerkleNode::internal(left, right));
+                next_level.push(MerkleNode::internal(
+                    left,
+                    right,
+                    self.root_bytes,
+                    self.key.as_ref(),
+                ));
             }
 
             nodes = next_level;
@@ -288,6 +628,9 @@ impl MerkleTree {
     /// * `index` - Leaf index
     /// * `proof` - Proof elements (sibling hashes with positions)
     /// * `root_hash` - Expected root hash
+    ///
+    /// Returns `InvalidProof` if the proof length doesn't match [`Self::depth`];
+    /// in particular an empty proof is only accepted for a single-leaf tree.
     pub fn verify_proof(
         &self,
         index: usize,
@@ -295,11 +638,15 @@ impl MerkleTree {
         root_hash: &str,
     ) -> MerkleResult<bool> {
         if index >= self.leaves.len() {
-            return Err(MerkleError::InvalidIndex(index));
+            return Err(MerkleError::LeafIndexOutOfRange(index));
+        }
+
+        if proof.len() != self.depth() {
+            return Err(MerkleError::InvalidProof);
         }
 
         // Start with leaf hash
-        let mut current = blake3::hash(&self.leaves[index]).as_bytes().to_vec();
+        let mut current = self.leaf_hash(&self.leaves[index])[..self.root_bytes].to_vec();
         let mut current_index = index;
 
         // Apply proof elements
@@ -318,13 +665,103 @@ impl MerkleTree {
                 combined
             };
 
-            current = blake3::hash(&combined).as_bytes().to_vec();
+            current = node_hash(self.key.as_ref(), &combined)[..self.root_bytes].to_vec();
             current_index /= 2;
         }
 
         // Compare with expected root
         let computed_root = hex::encode(&current);
-        Ok(computed_root == root_hash)
+        Ok(root_matches(&computed_root, root_hash))
+    }
+
+    /// Prove that no leaf of a sorted tree has DNA `dna_hex`
+    ///
+    /// Returns the leaves bracketing `dna_hex` with their inclusion proofs;
+    /// check it with [`verify_non_membership`]. Fails with `NotSorted` for
+    /// trees not created by [`MerkleTree::with_sorted_leaves`], and with
+    /// `DnaRegistered` if the DNA is present.
+    pub fn non_membership_proof(&self, dna_hex: &str) -> MerkleResult<NonMembershipProof> {
+        if !self.sorted {
+            return Err(MerkleError::NotSorted);
+        }
+        if self.root.is_none() {
+            return Err(MerkleError::TreeNotBuilt);
+        }
+
+        let dna = dna_hex.as_bytes();
+        let pos = self.leaves.partition_point(|leaf| leaf_dna(leaf) < dna);
+        if self.leaves.get(pos).is_some_and(|leaf| leaf_dna(leaf) == dna) {
+            return Err(MerkleError::DnaRegistered(dna_hex.to_string()));
+        }
+
+        let bracket = |index: usize| -> MerkleResult<BracketLeaf> {
+            Ok(BracketLeaf {
+                leaf: String::from_utf8_lossy(&self.leaves[index]).into_owned(),
+                proof: self.get_proof(index)?,
+            })
+        };
+
+        Ok(NonMembershipProof {
+            dna_hex: dna_hex.to_string(),
+            left: pos.checked_sub(1).map(bracket).transpose()?,
+            right: (pos < self.leaves.len()).then(|| bracket(pos)).transpose()?,
+        })
+    }
+
+    /// Check the built tree's internal consistency
+    ///
+    /// Recomputes every internal node hash from its children and compares
+    /// it with the stored hash, returning `InvalidProof` at the first
+    /// mismatch. Intended for debugging corrupted in-memory state.
+    pub fn validate(&self) -> MerkleResult<()> {
+        let root = self.root.as_ref().ok_or(MerkleError::TreeNotBuilt)?;
+        let mut stack = vec![root];
+
+        while let Some(node) = stack.pop() {
+            let (left, right) = match (&node.left, &node.right) {
+                (Some(left), Some(right)) => (left, right),
+                (None, None) => continue,
+                _ => return Err(MerkleError::InvalidProof),
+            };
+
+            let mut combined = left.hash.clone();
+            combined.extend_from_slice(&right.hash);
+            if node_hash(self.key.as_ref(), &combined)[..self.root_bytes] != node.hash[..] {
+                return Err(MerkleError::InvalidProof);
+            }
+
+            stack.push(left);
+            stack.push(right);
+        }
+
+        Ok(())
+    }
+
+    /// Render the tree as a Graphviz DOT graph
+    ///
+    /// Each node is labelled with the first 8 hex characters of its hash;
+    /// render with `dot -Tpng tree.dot -o tree.png`.
+    pub fn to_dot(&self) -> MerkleResult<String> {
+        let root = self.root.as_ref().ok_or(MerkleError::TreeNotBuilt)?;
+        let mut out = String::from("digraph merkle {\n    node [shape=box, fontname=monospace];\n");
+        let mut stack = vec![(root, 0usize)];
+        let mut next_id = 1;
+
+        while let Some((node, id)) = stack.pop() {
+            let hex = node.hash_hex();
+            let label = &hex[..hex.len().min(8)];
+            let style = if node.is_leaf { ", style=rounded" } else { "" };
+            out.push_str(&format!("    n{} [label=\"{}\"{}];\n", id, label, style));
+
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                out.push_str(&format!("    n{} -> n{};\n", id, next_id));
+                stack.push((child, next_id));
+                next_id += 1;
+            }
+        }
+
+        out.push_str("}\n");
+        Ok(out)
     }
 
     /// Get leaf data at index
@@ -332,23 +769,186 @@ impl MerkleTree {
         self.leaves
             .get(index)
             .map(|v| v.as_slice())
-            .ok_or(MerkleError::InvalidIndex(index))
+            .ok_or(MerkleError::LeafIndexOutOfRange(index))
     }
 
     /// Get leaf hash at index
     pub fn get_leaf_hash(&self, index: usize) -> MerkleResult<String> {
         let leaf = self.get_leaf(index)?;
-        Ok(hex::encode(blake3::hash(leaf).as_bytes()))
+        Ok(hex::encode(self.leaf_hash(leaf)))
+    }
+
+    /// Generate a self-contained proof bundle for leaf at given index
+    ///
+    /// The bundle always carries the leaf hash; `include_data` adds the raw
+    /// leaf data so verifiers can recompute and cross-check it.
+    pub fn get_proof_bundle(&self, index: usize, include_data: bool) -> MerkleResult<ProofBundle> {
+        let proof = self.get_proof(index)?;
+        let leaf = self.get_leaf(index)?;
+
+        Ok(ProofBundle {
+            namespace: None,
+            data: include_data.then(|| String::from_utf8_lossy(leaf).into_owned()),
+            leaf_hash: Some(hex::encode(self.leaf_hash(leaf))),
+            proof,
+            root: self.get_root()?,
+        })
     }
 }
 
+/// Encode registration fields into leaf bytes: `dna|pointer|platform|timestamp`
+///
+/// Must stay byte-identical to the ProPy binding's
+/// `leaf_encoding::encode_leaf`; `tests/binding_parity.rs` checks it does.
+pub fn encode_leaf(
+    dna_hex: &str,
+    pointer: &str,
+    platform_id: &str,
+    timestamp: impl std::fmt::Display,
+) -> Vec<u8> {
+    format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp).into_bytes()
+}
+
 /// Standalone function to compute leaf hash
 pub fn compute_leaf_hash(dna_hex: &str, pointer: &str, platform_id: &str, timestamp: u64) -> String {
-    let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-    hex::encode(blake3::hash(leaf_data.as_bytes()).as_bytes())
+    hex::encode(blake3::hash(&encode_leaf(dna_hex, pointer, platform_id, timestamp)).as_bytes())
+}
+
+/// Largest leaf count whose proofs have at most `max_depth` steps (`2^max_depth`)
+///
+/// Every proof step costs one BLAKE3 hash of a 64-byte sibling pair (the
+/// on-chain `verify_merkle_proof` hashes in-program, without a syscall),
+/// so verification compute grows linearly with depth. Pick `max_depth` as
+/// the compute budget left for verification divided by the measured cost
+/// of one step. Saturates at `usize::MAX` for depths beyond the word size.
+pub fn max_leaves_for_depth(max_depth: usize) -> usize {
+    u32::try_from(max_depth)
+        .ok()
+        .and_then(|depth| 1usize.checked_shl(depth))
+        .unwrap_or(usize::MAX)
+}
+
+/// Compute the Merkle root of raw leaves without building a tree
+///
+/// Matches the root of a [`MerkleTree`] with the same leaves added via
+/// `add_raw_leaf` (32-byte hashes). Each level is hashed in place into a
+/// single buffer, so no nodes or proofs are retained.
+pub fn compute_root(leaves: &[Vec<u8>]) -> MerkleResult<[u8; 32]> {
+    if leaves.is_empty() {
+        return Err(MerkleError::EmptyTree);
+    }
+
+    let mut level: Vec<[u8; 32]> = leaves
+        .iter()
+        .map(|leaf| *blake3::hash(leaf).as_bytes())
+        .collect();
+
+    while level.len() > 1 {
+        let next_len = level.len().div_ceil(2);
+        for i in 0..next_len {
+            let left = level[2 * i];
+            // Duplicate last node if odd number
+            let right = level.get(2 * i + 1).copied().unwrap_or(left);
+
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&left);
+            hasher.update(&right);
+            level[i] = *hasher.finalize().as_bytes();
+        }
+        level.truncate(next_len);
+    }
+
+    Ok(level[0])
+}
+
+/// Compute the Merkle root of leaves arriving over a channel
+///
+/// Consumes leaves until every sender is dropped and returns the root and
+/// leaf count. Only one complete subtree per height is kept (an MMR-style
+/// accumulator), so memory is O(log n) rather than buffering every leaf.
+/// The root matches [`compute_root`] over the same leaves.
+pub fn build_tree_from_receiver(rx: Receiver<Vec<u8>>) -> MerkleResult<([u8; 32], usize)> {
+    let mut accumulator = RootAccumulator::default();
+    for leaf in rx {
+        accumulator.push(&leaf);
+    }
+    let count = accumulator.count;
+    Ok((accumulator.finish()?, count))
+}
+
+/// Streaming root builder holding one complete subtree root per height
+#[derive(Default)]
+struct RootAccumulator {
+    /// `peaks[h]` is the root of a pending complete subtree of 2^h leaves
+    peaks: Vec<Option<[u8; 32]>>,
+    count: usize,
+}
+
+impl RootAccumulator {
+    /// Add a leaf, merging equal-height subtrees like a binary counter
+    fn push(&mut self, leaf: &[u8]) {
+        let mut carry = *blake3::hash(leaf).as_bytes();
+        self.count += 1;
+
+        for peak in self.peaks.iter_mut() {
+            match peak.take() {
+                Some(left) => carry = hash_pair(&left, &carry),
+                None => {
+                    *peak = Some(carry);
+                    return;
+                }
+            }
+        }
+        self.peaks.push(Some(carry));
+    }
+
+    /// Fold the peaks into the root, duplicating the last node of odd levels
+    fn finish(self) -> MerkleResult<[u8; 32]> {
+        let mut carry: Option<[u8; 32]> = None;
+
+        for (height, peak) in self.peaks.iter().enumerate() {
+            let higher_peaks = self.peaks[height + 1..].iter().any(Option::is_some);
+            carry = match (*peak, carry) {
+                (Some(left), Some(right)) => Some(hash_pair(&left, &right)),
+                // A lone node with nothing above it is the root
+                (Some(node), None) | (None, Some(node)) if !higher_peaks => return Ok(node),
+                (Some(node), None) | (None, Some(node)) => Some(hash_pair(&node, &node)),
+                (None, None) => None,
+            };
+        }
+
+        carry.ok_or(MerkleError::EmptyTree)
+    }
+}
+
+/// Compare a computed (lowercase) hex root with a caller-supplied one
+///
+/// Roots from other tools may use upper or mixed case; the caller's root is
+/// normalized to lowercase before comparing.
+fn root_matches(computed: &str, expected: &str) -> bool {
+    computed == expected.to_ascii_lowercase()
+}
+
+/// `Cancelled` once `cancel` has been set
+fn check_cancelled(cancel: &AtomicBool) -> MerkleResult<()> {
+    if cancel.load(Ordering::Relaxed) {
+        return Err(MerkleError::Cancelled);
+    }
+    Ok(())
+}
+
+/// Unkeyed BLAKE3 of two concatenated child hashes
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(left);
+    hasher.update(right);
+    *hasher.finalize().as_bytes()
 }
 
 /// Standalone function to verify proof
+///
+/// The hash length is taken from `root_hash`, so proofs from trees built
+/// with [`MerkleTree::with_root_bytes`] verify as well.
 pub fn verify_proof_standalone(
     dna_hex: &str,
     pointer: &str,
@@ -357,15 +957,28 @@ pub fn verify_proof_standalone(
     proof: &[ProofElement],
     root_hash: &str,
 ) -> MerkleResult<bool> {
-    // Compute leaf hash
-    let leaf_data = format!("{}|{}|{}|{}", dna_hex, pointer, platform_id, timestamp);
-    let mut current = blake3::hash(leaf_data.as_bytes()).as_bytes().to_vec();
+    let leaf_hash = blake3::hash(&encode_leaf(dna_hex, pointer, platform_id, timestamp));
+    verify_proof_from_leaf_hash(leaf_hash.as_bytes(), proof, root_hash)
+}
 
-    // Apply proof elements
-    for (i, element) in proof.iter().enumerate() {
+/// Verify a proof starting from an already computed leaf hash
+///
+/// For trees built with [`MerkleTree::with_leaf_hasher`]: hash the leaf
+/// with the same function and pass the full 32-byte result. Like
+/// [`verify_proof_standalone`], the hash length is taken from `root_hash`.
+pub fn verify_proof_from_leaf_hash(
+    leaf_hash: &[u8; 32],
+    proof: &[ProofElement],
+    root_hash: &str,
+) -> MerkleResult<bool> {
+    let hash_len = (root_hash.len() / 2).clamp(MIN_ROOT_BYTES, 32);
+    let mut current = leaf_hash[..hash_len].to_vec();
+
+    // Apply proof elements; the recorded position alone decides the order
+    for element in proof {
         let sibling_bytes = hex::decode(&element.hash)?;
-        
-        let combined = if element.position == "right" || i % 2 == 0 {
+
+        let combined = if element.position == "right" {
             let mut combined = current.clone();
             combined.extend_from_slice(&sibling_bytes);
             combined
@@ -375,12 +988,82 @@ pub fn verify_proof_standalone(
             combined
         };
 
-        current = blake3::hash(&combined).as_bytes().to_vec();
+        current = blake3::hash(&combined).as_bytes()[..hash_len].to_vec();
     }
 
     // Compare with expected root
     let computed_root = hex::encode(&current);
-    Ok(computed_root == root_hash)
+    Ok(root_matches(&computed_root, root_hash))
+}
+
+/// Verify a [`NonMembershipProof`] against a sorted tree's root
+///
+/// Checks that each bracketing leaf is included under `root_hash`, that
+/// the two leaves are adjacent, and that their DNAs strictly bracket the
+/// absent one. A one-sided proof must bracket with the first leaf (index 0)
+/// or the last leaf (every right-hand sibling on its path is a duplicate of
+/// itself). DNA hex is compared as-is, so it must use the tree's casing.
+pub fn verify_non_membership(proof: &NonMembershipProof, root_hash: &str) -> MerkleResult<bool> {
+    let hash_len = (root_hash.len() / 2).clamp(MIN_ROOT_BYTES, 32);
+    let dna = proof.dna_hex.as_bytes();
+
+    let left = match &proof.left {
+        Some(bracket) => match fold_bracket(bracket, hash_len)? {
+            (root, index, is_last) if root_matches(&root, root_hash) => {
+                Some((bracket, index, is_last))
+            }
+            _ => return Ok(false),
+        },
+        None => None,
+    };
+    let right = match &proof.right {
+        Some(bracket) => match fold_bracket(bracket, hash_len)? {
+            (root, index, _) if root_matches(&root, root_hash) => Some((bracket, index)),
+            _ => return Ok(false),
+        },
+        None => None,
+    };
+
+    let below = |bracket: &BracketLeaf| leaf_dna(bracket.leaf.as_bytes()) < dna;
+    let above = |bracket: &BracketLeaf| leaf_dna(bracket.leaf.as_bytes()) > dna;
+
+    Ok(match (left, right) {
+        (Some((l, l_index, _)), Some((r, r_index))) => {
+            r_index == l_index + 1 && below(l) && above(r)
+        }
+        (None, Some((r, r_index))) => r_index == 0 && above(r),
+        (Some((l, _, is_last)), None) => is_last && below(l),
+        (None, None) => false,
+    })
+}
+
+/// Fold a bracket leaf's proof into `(root_hex, leaf_index, is_last_leaf)`
+///
+/// The index is read from the proof positions. The leaf is the last one
+/// when every right-hand sibling on its path is a duplicate of itself.
+fn fold_bracket(bracket: &BracketLeaf, hash_len: usize) -> MerkleResult<(String, usize, bool)> {
+    let mut current = blake3::hash(bracket.leaf.as_bytes()).as_bytes()[..hash_len].to_vec();
+    let mut index = 0;
+    let mut is_last = true;
+
+    for (level, element) in bracket.proof.iter().enumerate() {
+        let sibling = hex::decode(&element.hash)?;
+
+        let mut combined = Vec::with_capacity(current.len() + sibling.len());
+        if element.position == "right" {
+            is_last &= sibling == current;
+            combined.extend_from_slice(&current);
+            combined.extend_from_slice(&sibling);
+        } else {
+            index |= 1 << level;
+            combined.extend_from_slice(&sibling);
+            combined.extend_from_slice(&current);
+        }
+
+        current = blake3::hash(&combined).as_bytes()[..hash_len].to_vec();
+    }
+
+    Ok((hex::encode(current), index, is_last))
 }
 
 #[cfg(test)]
@@ -422,6 +1105,27 @@ mod tests {
         assert!(tree.verify_proof(0, &proof, &root).unwrap());
     }
 
+    #[test]
+    fn test_uppercase_root_verifies() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+        let root = tree.build_tree().unwrap();
+        let upper = root.to_uppercase();
+
+        // A raw string comparison would reject the same bytes
+        assert_ne!(upper, root);
+
+        let proof = tree.get_proof(2).unwrap();
+        assert!(tree.verify_proof(2, &proof, &upper).unwrap());
+        assert!(
+            verify_proof_standalone("dna_2", "ptr_2", "platform", 1234567890, &proof, &upper)
+                .unwrap()
+        );
+        tree.build_tree_expecting(&upper).unwrap();
+    }
+
     #[test]
     fn test_leaf_hash_computation() {
         let hash = compute_leaf_hash("abc123", "ptr1", "platform1", 1234567890);
@@ -440,4 +1144,477 @@ mod tests {
         assert_eq!(proof.len(), 0);
         assert!(tree.verify_proof(0, &proof, &root).unwrap());
     }
+
+    #[test]
+    fn test_is_built() {
+        let mut tree = MerkleTree::new();
+        assert!(tree.is_empty());
+        assert!(!tree.is_built());
+
+        tree.add_leaf("dna_0", "ptr_0", "platform", 1234567890);
+        assert!(!tree.is_empty());
+        assert_eq!(tree.leaf_count(), 1);
+        assert!(!tree.is_built());
+
+        tree.build_tree().unwrap();
+        assert!(tree.is_built());
+
+        tree.add_leaf("dna_1", "ptr_1", "platform", 1234567890);
+        assert!(!tree.is_built());
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+
+        tree.build_tree().unwrap();
+        assert!(tree.is_built());
+    }
+
+    #[test]
+    fn test_leaf_hashes_match_get_leaf_hash() {
+        let mut tree = MerkleTree::new();
+
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+
+        // Uncached and cached (after build) paths agree
+        let before = tree.leaf_hashes();
+        tree.build_tree().unwrap();
+        let hashes = tree.leaf_hashes();
+        assert_eq!(before, hashes);
+
+        assert_eq!(hashes.len(), 5);
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(hex::encode(hash), tree.get_leaf_hash(i).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_standalone_verification_odd_tree() {
+        let mut tree = MerkleTree::new();
+        for i in 0..7 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+        let root = tree.build_tree().unwrap();
+
+        for i in 0..7 {
+            let proof = tree.get_proof(i).unwrap();
+            assert!(
+                verify_proof_standalone(
+                    &format!("dna_{}", i),
+                    &format!("ptr_{}", i),
+                    "platform",
+                    1234567890,
+                    &proof,
+                    &root
+                )
+                .unwrap(),
+                "leaf {} failed standalone verification",
+                i
+            );
+            assert!(tree.verify_proof(i, &proof, &root).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_build_empty_tree() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.build_tree(), Err(MerkleError::EmptyTree)));
+        assert!(!tree.is_built());
+    }
+
+    #[test]
+    fn test_proof_bundle_verify() {
+        let mut tree = MerkleTree::new();
+        for i in 0..3 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+        tree.build_tree().unwrap();
+
+        let mut bundle = tree.get_proof_bundle(2, true).unwrap();
+        assert_eq!(bundle.data.as_deref(), Some("dna_2|ptr_2|platform|1234567890"));
+        assert!(bundle.verify().unwrap());
+
+        // Hash-only bundles verify from the leaf hash alone
+        let mut hash_only = bundle.clone();
+        hash_only.data = None;
+        assert!(hash_only.verify().unwrap());
+        hash_only.leaf_hash = Some("ab".repeat(16));
+        assert!(matches!(hash_only.verify(), Err(MerkleError::InvalidHashLength(16))));
+        hash_only.leaf_hash = None;
+        assert!(matches!(hash_only.verify(), Err(MerkleError::InvalidProof)));
+        hash_only.leaf_hash = Some(bundle.root.clone());
+        hash_only.proof.clear();
+        assert!(matches!(hash_only.verify(), Err(MerkleError::InvalidProof)));
+
+        bundle.data = Some("dna_2|ptr_tampered|platform|1234567890".to_string());
+        assert!(!bundle.verify().unwrap());
+        bundle.leaf_hash = None;
+        assert!(!bundle.verify().unwrap());
+    }
+
+    #[test]
+    fn test_truncated_root_bytes() {
+        assert!(matches!(
+            MerkleTree::with_root_bytes(15),
+            Err(MerkleError::InvalidHashLength(15))
+        ));
+        assert!(MerkleTree::with_root_bytes(33).is_err());
+
+        let mut tree = MerkleTree::with_root_bytes(20).unwrap();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+        let root = tree.build_tree().unwrap();
+        assert_eq!(root.len(), 40);
+
+        for i in 0..5 {
+            let proof = tree.get_proof(i).unwrap();
+            assert!(proof.iter().all(|element| element.hash.len() == 40));
+            assert!(tree.verify_proof(i, &proof, &root).unwrap());
+            assert!(verify_proof_standalone(
+                &format!("dna_{}", i),
+                &format!("ptr_{}", i),
+                "platform",
+                1234567890,
+                &proof,
+                &root
+            )
+            .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_iter_leaves() {
+        let mut tree = MerkleTree::new();
+        for i in 0..4 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+        }
+
+        let uncached: Vec<_> = tree.iter_leaves().map(|(_, _, hash)| hash).collect();
+        tree.build_tree().unwrap();
+
+        let leaves: Vec<_> = tree.iter_leaves().collect();
+        assert_eq!(leaves.len(), 4);
+
+        for (i, (index, bytes, hash)) in leaves.into_iter().enumerate() {
+            assert_eq!(index, i);
+            assert_eq!(bytes, tree.get_leaf(i).unwrap());
+            assert_eq!(hex::encode(hash), tree.get_leaf_hash(i).unwrap());
+            assert_eq!(hash, uncached[i]);
+        }
+    }
+
+    #[test]
+    fn test_verify_proof_length_checked() {
+        let mut single = MerkleTree::new();
+        single.add_leaf("dna_0", "ptr_0", "platform", 1000);
+        let root = single.build_tree().unwrap();
+        assert_eq!(single.depth(), 0);
+        assert!(single.verify_proof(0, &[], &root).unwrap());
+
+        let mut tree = MerkleTree::new();
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1000);
+        }
+        let root = tree.build_tree().unwrap();
+        assert_eq!(tree.depth(), 3);
+
+        // Empty proof against a multi-leaf root
+        assert!(matches!(tree.verify_proof(0, &[], &root), Err(MerkleError::InvalidProof)));
+
+        // Over-length proof
+        let mut proof = tree.get_proof(0).unwrap();
+        assert!(tree.verify_proof(0, &proof, &root).unwrap());
+        proof.push(proof[0].clone());
+        assert!(matches!(tree.verify_proof(0, &proof, &root), Err(MerkleError::InvalidProof)));
+    }
+
+    #[test]
+    fn test_max_leaves_for_depth() {
+        assert_eq!(max_leaves_for_depth(10), 1024);
+        assert_eq!(max_leaves_for_depth(20), 1_048_576);
+        assert_eq!(max_leaves_for_depth(0), 1);
+        assert_eq!(max_leaves_for_depth(usize::BITS as usize), usize::MAX);
+
+        // A tree at the limit still has proofs of exactly `max_depth` steps
+        let mut tree = MerkleTree::new();
+        for i in 0..max_leaves_for_depth(10) {
+            tree.add_raw_leaf(&(i as u64).to_le_bytes());
+        }
+        tree.build_tree().unwrap();
+        assert_eq!(tree.depth(), 10);
+        assert_eq!(tree.get_proof(0).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_compute_root_matches_build_tree() {
+        // 17 leaves: every level above the leaves has an odd node count
+        let leaves: Vec<Vec<u8>> = (0..17)
+            .map(|i| format!("dna{:02}|ptr{}|platform|{}", i, i, 1_700_000_000 + i).into_bytes())
+            .collect();
+
+        let mut tree = MerkleTree::new();
+        for leaf in &leaves {
+            tree.add_raw_leaf(leaf);
+        }
+        tree.build_tree().unwrap();
+
+        let root = compute_root(&leaves).unwrap();
+        assert_eq!(root.to_vec(), tree.get_root_bytes().unwrap());
+
+        assert_eq!(compute_root(&leaves[..1]).unwrap(), *blake3::hash(&leaves[0]).as_bytes());
+        assert!(matches!(compute_root(&[]), Err(MerkleError::EmptyTree)));
+    }
+
+    #[test]
+    fn test_build_tree_from_receiver() {
+        let leaves: Vec<Vec<u8>> = (0..100)
+            .map(|i| format!("leaf{}", i).into_bytes())
+            .collect();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sent = leaves.clone();
+        let producer = std::thread::spawn(move || {
+            for leaf in sent {
+                tx.send(leaf).unwrap();
+            }
+        });
+        let (root, count) = build_tree_from_receiver(rx).unwrap();
+        producer.join().unwrap();
+
+        assert_eq!(count, 100);
+        assert_eq!(root, compute_root(&leaves).unwrap());
+
+        // Every shape of odd levels agrees with the buffered build
+        for n in 1..40 {
+            let (tx, rx) = std::sync::mpsc::channel();
+            for leaf in &leaves[..n] {
+                tx.send(leaf.clone()).unwrap();
+            }
+            drop(tx);
+            let (root, _) = build_tree_from_receiver(rx).unwrap();
+            assert_eq!(root, compute_root(&leaves[..n]).unwrap(), "n = {}", n);
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel::<Vec<u8>>();
+        drop(tx);
+        assert!(matches!(
+            build_tree_from_receiver(rx),
+            Err(MerkleError::EmptyTree)
+        ));
+    }
+
+    #[test]
+    fn test_unified_error_variants() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.build_tree(), Err(MerkleError::EmptyTree)));
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+
+        for i in 0..3 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", 1000 + i);
+        }
+        assert!(matches!(tree.get_proof(0), Err(MerkleError::TreeNotBuilt)));
+        assert!(matches!(
+            tree.build_tree_expecting(&"00".repeat(32)),
+            Err(MerkleError::RootMismatch)
+        ));
+        let root = tree.get_root().unwrap();
+        tree.build_tree_expecting(&root).unwrap();
+
+        assert!(matches!(
+            tree.get_proof(3),
+            Err(MerkleError::LeafIndexOutOfRange(3))
+        ));
+        assert!(matches!(
+            tree.get_leaf(3),
+            Err(MerkleError::LeafIndexOutOfRange(3))
+        ));
+
+        let mut proof = tree.get_proof(1).unwrap();
+        assert!(matches!(
+            tree.verify_proof(1, &proof[1..], &root),
+            Err(MerkleError::InvalidProof)
+        ));
+        proof[0].hash = "zz".into();
+        assert!(matches!(
+            tree.verify_proof(1, &proof, &root),
+            Err(MerkleError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_keyed_hash() {
+        let build = |tree: &mut MerkleTree| {
+            for i in 0..5 {
+                tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", 1000 + i);
+            }
+            tree.build_tree().unwrap()
+        };
+
+        let mut plain = MerkleTree::new();
+        let mut tree_a = MerkleTree::with_keyed_hash([1u8; 32]);
+        let mut tree_b = MerkleTree::with_keyed_hash([2u8; 32]);
+        let plain_root = build(&mut plain);
+        let root_a = build(&mut tree_a);
+        let root_b = build(&mut tree_b);
+
+        assert_ne!(root_a, root_b);
+        assert_ne!(root_a, plain_root);
+
+        let proof_a = tree_a.get_proof(3).unwrap();
+        assert!(tree_a.verify_proof(3, &proof_a, &root_a).unwrap());
+        assert!(!tree_b.verify_proof(3, &proof_a, &root_a).unwrap());
+        assert!(!plain.verify_proof(3, &proof_a, &root_a).unwrap());
+    }
+
+    #[test]
+    fn test_build_tree_cancellable() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        // Hasher that raises the flag after the third leaf
+        let cancel = Arc::new(AtomicBool::new(false));
+        let hashed = Arc::new(AtomicUsize::new(0));
+        let (flag, count) = (Arc::clone(&cancel), Arc::clone(&hashed));
+        let mut tree = MerkleTree::with_leaf_hasher(move |leaf| {
+            if count.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                flag.store(true, Ordering::SeqCst);
+            }
+            *blake3::hash(leaf).as_bytes()
+        });
+        for i in 0..100u8 {
+            tree.add_raw_leaf(&[i]);
+        }
+
+        assert!(matches!(
+            tree.build_tree_cancellable(&cancel),
+            Err(MerkleError::Cancelled)
+        ));
+        assert_eq!(hashed.load(Ordering::SeqCst), 3);
+        assert!(matches!(tree.get_root(), Err(MerkleError::TreeNotBuilt)));
+
+        cancel.store(false, Ordering::SeqCst);
+        let root = tree.build_tree_cancellable(&cancel).unwrap();
+        assert_eq!(root, tree.build_tree().unwrap());
+    }
+
+    #[test]
+    fn test_custom_leaf_hasher() {
+        // Identity-ish: the leaf bytes themselves, zero-padded to 32
+        fn pad(leaf: &[u8]) -> [u8; 32] {
+            let mut hash = [0u8; 32];
+            let len = leaf.len().min(32);
+            hash[..len].copy_from_slice(&leaf[..len]);
+            hash
+        }
+
+        let leaves: Vec<Vec<u8>> = (0..5u8).map(|i| vec![b'L', i]).collect();
+        let mut tree = MerkleTree::with_leaf_hasher(pad);
+        let mut plain = MerkleTree::new();
+        for leaf in &leaves {
+            tree.add_raw_leaf(leaf);
+            plain.add_raw_leaf(leaf);
+        }
+        let root = tree.build_tree().unwrap();
+        assert_ne!(root, plain.build_tree().unwrap());
+
+        assert_eq!(tree.leaf_hashes()[2], pad(&leaves[2]));
+        assert_eq!(tree.get_leaf_hash(2).unwrap(), hex::encode(pad(&leaves[2])));
+        tree.validate().unwrap();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.get_proof(i).unwrap();
+            assert!(tree.verify_proof(i, &proof, &root).unwrap());
+            assert!(verify_proof_from_leaf_hash(&pad(leaf), &proof, &root).unwrap());
+            // BLAKE3 of the same leaf doesn't match the custom tree
+            let blake3_leaf = *blake3::hash(leaf).as_bytes();
+            assert!(!verify_proof_from_leaf_hash(&blake3_leaf, &proof, &root).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_validate_detects_corruption() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.validate(), Err(MerkleError::TreeNotBuilt)));
+
+        for i in 0..5 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", 1000 + i);
+        }
+        tree.build_tree().unwrap();
+        assert!(tree.validate().is_ok());
+
+        // Flip a bit in an internal node below the root
+        let root = tree.root.as_mut().unwrap();
+        root.left.as_mut().unwrap().hash[0] ^= 1;
+        assert!(matches!(tree.validate(), Err(MerkleError::InvalidProof)));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut tree = MerkleTree::new();
+        assert!(matches!(tree.to_dot(), Err(MerkleError::TreeNotBuilt)));
+
+        for i in 0..4 {
+            tree.add_leaf(&format!("dna{}", i), &format!("ptr{}", i), "platform", 1000 + i);
+        }
+        let root = tree.build_tree().unwrap();
+        let dot = tree.to_dot().unwrap();
+
+        assert!(dot.starts_with("digraph merkle {"));
+        assert_eq!(dot.matches("[label=").count(), 7);
+        assert_eq!(dot.matches(" -> ").count(), 6);
+        assert!(dot.contains(&format!("n0 [label=\"{}\"]", &root[..8])));
+    }
+
+    #[test]
+    fn test_non_membership() {
+        let mut tree = MerkleTree::with_sorted_leaves();
+        // Inserted out of order; build_tree sorts them
+        for dna in ["40", "10", "30", "20", "50"] {
+            tree.add_leaf(&dna.repeat(32), "ptr", "platform", 1000);
+        }
+        let root = tree.build_tree().unwrap();
+        assert!(tree.get_leaf(0).unwrap().starts_with(&b"10".repeat(32)));
+
+        // Between two registered DNAs
+        let absent = "25".repeat(32);
+        let proof = tree.non_membership_proof(&absent).unwrap();
+        assert!(proof.left.as_ref().unwrap().leaf.starts_with(&"20".repeat(32)));
+        assert!(proof.right.as_ref().unwrap().leaf.starts_with(&"30".repeat(32)));
+        assert!(verify_non_membership(&proof, &root).unwrap());
+
+        // Before the first and after the last leaf
+        for absent in ["00".repeat(32), "ff".repeat(32)] {
+            let proof = tree.non_membership_proof(&absent).unwrap();
+            assert!(verify_non_membership(&proof, &root).unwrap());
+        }
+
+        // A registered DNA can't be proven absent, even with a forged proof
+        let present = "20".repeat(32);
+        assert!(matches!(
+            tree.non_membership_proof(&present),
+            Err(MerkleError::DnaRegistered(_))
+        ));
+        let mut forged = tree.non_membership_proof(&absent).unwrap();
+        forged.dna_hex = present;
+        assert!(!verify_non_membership(&forged, &root).unwrap());
+
+        // Non-adjacent brackets are rejected
+        let mut gapped = tree.non_membership_proof(&"25".repeat(32)).unwrap();
+        gapped.right = tree.non_membership_proof(&"45".repeat(32)).unwrap().right;
+        gapped.dna_hex = "35".repeat(32);
+        assert!(!verify_non_membership(&gapped, &root).unwrap());
+
+        // Dropping a bracket doesn't turn an inner gap into an edge
+        let mut one_sided = tree.non_membership_proof(&"25".repeat(32)).unwrap();
+        one_sided.right = None;
+        assert!(!verify_non_membership(&one_sided, &root).unwrap());
+        let mut one_sided = tree.non_membership_proof(&"25".repeat(32)).unwrap();
+        one_sided.left = None;
+        assert!(!verify_non_membership(&one_sided, &root).unwrap());
+
+        assert!(matches!(
+            MerkleTree::new().non_membership_proof(&absent),
+            Err(MerkleError::NotSorted)
+        ));
+    }
 }
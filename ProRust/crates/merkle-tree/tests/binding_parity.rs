@@ -0,0 +1,32 @@
+//! Leaf-encoding parity with the ProPy binding (`protrace-merkle-tree`)
+
+use protrace_merkle::MerkleTree;
+
+#[test]
+fn test_registration_leaf_hashes_match_binding() {
+    let registrations = [
+        ("ab".repeat(32), "ipfs://QmFirst", "opensea", 1_698_765_432i64),
+        ("cd".repeat(32), "uuid-0002", "foundation", 0),
+        ("ef".repeat(32), "ipfs://QmThird", "devnet-test", -1),
+    ];
+
+    let mut native = MerkleTree::new();
+    let mut binding = protrace_merkle_tree::MerkleTree::new();
+    for (dna, pointer, platform, timestamp) in &registrations {
+        native.add_registration(dna, pointer, platform, *timestamp);
+        binding.add_leaf(dna, pointer, platform, Some(*timestamp));
+    }
+
+    for (i, (dna, pointer, platform, timestamp)) in registrations.iter().enumerate() {
+        assert_eq!(
+            protrace_merkle::encode_leaf(dna, pointer, platform, *timestamp),
+            protrace_merkle_tree::leaf_encoding::encode_leaf(dna, pointer, platform, *timestamp)
+        );
+        assert_eq!(
+            native.get_leaf_hash(i).unwrap(),
+            protrace_merkle_tree::compute_leaf_hash(dna, pointer, platform, Some(*timestamp))
+        );
+    }
+
+    assert_eq!(native.build_tree().unwrap(), binding.build_tree().unwrap());
+}
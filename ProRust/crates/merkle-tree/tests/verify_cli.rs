@@ -0,0 +1,82 @@
+//! Integration tests for the `verify` binary
+
+#![cfg(feature = "cli")]
+
+use protrace_merkle::{MerkleTree, ProofBundle};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn write_bundle(name: &str, bundle: &ProofBundle) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("protrace_{}_{}.json", name, std::process::id()));
+    std::fs::write(&path, serde_json::to_string(bundle).unwrap()).unwrap();
+    path
+}
+
+fn run_verify(path: &PathBuf) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_verify"))
+        .arg("--bundle")
+        .arg(path)
+        .output()
+        .unwrap();
+    (output.status.code(), String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[test]
+fn test_verify_valid_and_tampered_bundles() {
+    let mut tree = MerkleTree::new();
+    for i in 0..5 {
+        tree.add_leaf(&format!("dna_{}", i), &format!("ptr_{}", i), "platform", 1234567890);
+    }
+    tree.build_tree().unwrap();
+
+    let bundle = tree.get_proof_bundle(3, true).unwrap();
+
+    let valid = write_bundle("valid_bundle", &bundle);
+    assert_eq!(run_verify(&valid), (Some(0), "VALID".to_string()));
+
+    let mut tampered = bundle.clone();
+    tampered.data = Some("dna_tampered|ptr_3|platform|1234567890".to_string());
+    let tampered = write_bundle("tampered_bundle", &tampered);
+    assert_eq!(run_verify(&tampered), (Some(1), "INVALID".to_string()));
+
+    std::fs::remove_file(valid).unwrap();
+    std::fs::remove_file(tampered).unwrap();
+}
+
+/// Bundles written by `protrace merkle export-proofs` verify end to end
+#[test]
+fn test_verify_exported_binding_bundles() {
+    for namespace in [&b""[..], b"tenant-a"] {
+        let mut tree = protrace_merkle_tree::MerkleTree::with_namespace(namespace);
+        for i in 0..5 {
+            let (dna, pointer) = (format!("dna_{}", i), format!("ptr_{}", i));
+            tree.add_leaf(&dna, &pointer, "platform", Some(1234567890));
+        }
+        tree.build_tree().unwrap();
+
+        // Same path as export-proofs: manifest round trip, then one bundle per leaf
+        let manifest = tree.export_manifest().unwrap();
+        let mut imported = protrace_merkle_tree::MerkleTree::new();
+        imported.import_manifest(&manifest).unwrap();
+
+        for index in 0..imported.leaf_count() {
+            let exported = imported.get_proof_bundle(index, true).unwrap();
+            let path = std::env::temp_dir().join(format!(
+                "protrace_exported_{}_{}_{}.json",
+                namespace.len(),
+                index,
+                std::process::id()
+            ));
+            std::fs::write(&path, serde_json::to_string_pretty(&exported).unwrap()).unwrap();
+            assert_eq!(run_verify(&path), (Some(0), "VALID".to_string()));
+
+            let mut tampered: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            tampered["data"] = "dna_9|ptr_9|platform|1234567890".into();
+            std::fs::write(&path, tampered.to_string()).unwrap();
+            assert_eq!(run_verify(&path), (Some(1), "INVALID".to_string()));
+
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
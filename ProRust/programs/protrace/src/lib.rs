@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::ed25519_program;
 
 #[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
@@ -20,42 +23,106 @@ security_txt! {
 
 declare_id!("7hWMQqQiPsuwB41yWbUTs15ETAvjLGDbN2B3jqh87Dzh");
 
+/// Maximum byte length of `HashData::platform_id`
+pub const MAX_PLATFORM_ID_LEN: usize = 32;
+/// Maximum byte length of `HashData::pointer`
+pub const MAX_POINTER_LEN: usize = 64;
+/// Maximum byte length of `AnchorAccount::manifest_cid`
+pub const MAX_MANIFEST_CID_LEN: usize = 64;
+/// Number of distinct chains `EditionRegistryAccount` keeps per-chain totals for
+pub const MAX_TRACKED_CHAINS: usize = 8;
+/// Layout version written first in `AnchorAccount` and `EditionRegistryAccount`
+///
+/// Bump whenever either layout changes so clients and migrations can tell
+/// which layout an account was written with before decoding the rest.
+pub const ACCOUNT_SCHEMA_VERSION: u8 = 1;
+/// Domain tag that starts every message an oracle signs for relayed anchoring
+pub const ANCHOR_INTENT_DOMAIN: &[u8] = b"protrace:anchor-intent:v1";
+
 #[program]
 pub mod protrace {
     use super::*;
 
     // MVP Oracle Pattern: Authority-signed anchoring
+    //
+    // Each `registry_id` seeds its own anchor account, so one deployment can
+    // host independent roots for several platforms. The oracle's key is part
+    // of the seed too, so no one can claim a registry ID for another oracle.
     pub fn anchor_merkle_root_oracle(
         ctx: Context<AnchorMerkleRootOracle>,
+        registry_id: [u8; 16],
         merkle_root: [u8; 32],
         manifest_cid: String,
         asset_count: u64,
         timestamp: i64,
     ) -> Result<()> {
-        let anchor_account = &mut ctx.accounts.anchor_account;
+        record_anchor(
+            &mut ctx.accounts.anchor_account,
+            registry_id,
+            ctx.accounts.oracle_authority.key(),
+            merkle_root,
+            manifest_cid.clone(),
+            asset_count,
+            timestamp,
+        )?;
 
-        // Initialize oracle_authority on first use
-        if anchor_account.version == 0 {
-            anchor_account.oracle_authority = ctx.accounts.oracle_authority.key();
-        }
+        msg!("Merkle root anchored by oracle: {}", hex::encode(merkle_root));
+        msg!("Registry: {}", hex::encode(registry_id));
+        msg!("Manifest CID: {}", manifest_cid);
+        msg!("Asset count: {}", asset_count);
 
-        // Only allow the designated oracle authority to anchor
-        require!(
-            ctx.accounts.oracle_authority.key() == anchor_account.oracle_authority,
-            ProTraceError::UnauthorizedOracle
+        Ok(())
+    }
+
+    // Relayed Oracle Pattern: the oracle signs `anchor_intent_message`
+    // off-chain and any relayer submits it, paying the fees. The Ed25519
+    // program checks the signature in the instruction just before this one;
+    // here we only confirm that instruction covers the oracle and payload.
+    // Intents must be newer than the current anchor so they can't be replayed.
+    // Relaying only updates a registry the oracle has already anchored
+    // directly; it never creates one.
+    pub fn anchor_merkle_root_relayed(
+        ctx: Context<AnchorMerkleRootRelayed>,
+        registry_id: [u8; 16],
+        merkle_root: [u8; 32],
+        manifest_cid: String,
+        asset_count: u64,
+        timestamp: i64,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+        require!(current > 0, ProTraceError::MissingOracleSignature);
+        let ed25519_ix = load_instruction_at_checked(current - 1, &instructions)?;
+
+        let message = anchor_intent_message(
+            &crate::ID,
+            registry_id,
+            merkle_root,
+            &manifest_cid,
+            asset_count,
+            timestamp,
         );
+        verify_oracle_intent(&ed25519_ix, &oracle, &message)?;
 
-        // Update the anchor record
-        anchor_account.merkle_root = merkle_root;
-        anchor_account.manifest_cid = manifest_cid.clone();
-        anchor_account.asset_count = asset_count;
-        anchor_account.timestamp = timestamp;
-        anchor_account.oracle_signature = ctx.accounts.oracle_authority.key();
-        anchor_account.version += 1;
+        let anchor_account = &mut ctx.accounts.anchor_account;
+        authorize_relayed_anchor(anchor_account, &oracle, timestamp)?;
+        record_anchor(
+            anchor_account,
+            registry_id,
+            oracle,
+            merkle_root,
+            manifest_cid,
+            asset_count,
+            timestamp,
+        )?;
 
-        msg!("Merkle root anchored by oracle: {}", hex::encode(merkle_root));
-        msg!("Manifest CID: {}", manifest_cid);
-        msg!("Asset count: {}", asset_count);
+        msg!(
+            "Merkle root anchored for oracle {} by relayer {}",
+            oracle,
+            ctx.accounts.relayer.key()
+        );
+        msg!("Root: {}", hex::encode(merkle_root));
 
         Ok(())
     }
@@ -65,10 +132,16 @@ pub mod protrace {
         ctx: Context<AnchorDnaHash>,
         dna_hash: String,
         edition_mode: u8,
+        platform_id: String,
+        pointer: String,
     ) -> Result<()> {
         let hash_data = &mut ctx.accounts.hash_data;
         let user = &ctx.accounts.user;
 
+        // The PDA is created on first use; a populated account means this
+        // user already anchored this DNA hash
+        ensure_unregistered(hash_data)?;
+
         // Validate DNA hash is exactly 64 hex characters (256 bits)
         require!(
             dna_hash.len() == 64,
@@ -87,11 +160,16 @@ pub mod protrace {
             ProTraceError::InvalidEditionMode
         );
 
+        // Validate metadata fits the account
+        validate_hash_metadata(&platform_id, &pointer)?;
+
         // Store the hash data
         hash_data.dna_hash = dna_hash.clone();
         hash_data.owner = user.key();
         hash_data.timestamp = Clock::get()?.unix_timestamp;
         hash_data.edition_mode = edition_mode;
+        hash_data.platform_id = platform_id;
+        hash_data.pointer = pointer;
 
         // Compute BLAKE3 hash for internal verification
         let blake3_hash = blake3::hash(dna_hash.as_bytes());
@@ -101,6 +179,8 @@ pub mod protrace {
         msg!("Owner: {}", user.key());
         msg!("Timestamp: {}", hash_data.timestamp);
         msg!("Edition mode: {}", edition_mode);
+        msg!("Platform: {}", hash_data.platform_id);
+        msg!("Pointer: {}", hash_data.pointer);
         msg!("BLAKE3 verification: {}", blake3_hex);
 
         Ok(())
@@ -158,6 +238,7 @@ pub mod protrace {
         }
 
         // Update registry state
+        record_chain_counts(&mut edition_registry.chain_counts, &edition_updates)?;
         edition_registry.merkle_root = new_merkle_root;
         edition_registry.ipfs_cid = ipfs_cid.clone();
         edition_registry.total_editions += total_editions;
@@ -179,22 +260,65 @@ pub mod protrace {
         ctx: Context<InitializeEditionRegistry>,
         oracle_authority: Pubkey,
     ) -> Result<()> {
-        let edition_registry = &mut ctx.accounts.edition_registry;
-
-        edition_registry.oracle_authority = oracle_authority;
-        edition_registry.merkle_root = [0u8; 32]; // Empty tree root
-        edition_registry.ipfs_cid = "".to_string();
-        edition_registry.total_editions = 0;
-        edition_registry.last_batch_id = "".to_string();
-        edition_registry.last_batch_timestamp = Clock::get()?.unix_timestamp;
-        edition_registry.last_oracle_signature = oracle_authority;
-        edition_registry.version = 0;
+        reset_edition_registry(
+            &mut ctx.accounts.edition_registry,
+            oracle_authority,
+            Clock::get()?.unix_timestamp,
+        );
 
         msg!("Edition registry initialized with oracle authority: {}", oracle_authority);
 
         Ok(())
     }
 
+    // Migrate Edition Registry: Rewrite a pre-schema (v0) registry in the current layout
+    //
+    // Registries created before `schema_version` and `chain_counts` existed
+    // can't be loaded as `EditionRegistryAccount`, and `init` can't recreate
+    // them. The registry's oracle grows the account, topping up its rent,
+    // and every v0 field carries over. Per-chain totals start at zero since
+    // v0 never recorded them; `total_editions` keeps the full count.
+    pub fn migrate_edition_registry(ctx: Context<MigrateEditionRegistry>) -> Result<()> {
+        let registry_info = ctx.accounts.edition_registry.to_account_info();
+        let migrated = {
+            let data = registry_info.try_borrow_data()?;
+            require!(
+                data.starts_with(EditionRegistryAccount::DISCRIMINATOR),
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            migrate_edition_registry_v0(&data)?
+        };
+        require!(
+            ctx.accounts.oracle_authority.key() == migrated.oracle_authority,
+            ProTraceError::UnauthorizedOracle
+        );
+
+        let space = 8 + EditionRegistryAccount::LEN;
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(registry_info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.oracle_authority.to_account_info(),
+                        to: registry_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        registry_info.resize(space)?;
+
+        let mut data = registry_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        msg!("Edition registry migrated to schema version {}", ACCOUNT_SCHEMA_VERSION);
+
+        Ok(())
+    }
+
     // Verify Edition Authorization: Check if an edition is authorized for minting
     pub fn verify_edition_authorization(
         ctx: Context<VerifyEditionAuthorization>,
@@ -221,6 +345,14 @@ pub mod protrace {
 
         Ok(())
     }
+
+    // Chain Count: Return the editions registered on one chain
+    pub fn chain_count(ctx: Context<GetChainCount>, chain: [u8; 10]) -> Result<u64> {
+        let count = ctx.accounts.edition_registry.chain_count(chain);
+        msg!("Editions on {}: {}", std::str::from_utf8(&chain).unwrap_or("unknown"), count);
+        Ok(count)
+    }
+
     pub fn initialize_merkle_root(ctx: Context<InitializeMerkleRoot>, root: [u8; 32]) -> Result<()> {
         let merkle_account = &mut ctx.accounts.merkle_account;
         merkle_account.root = root;
@@ -242,25 +374,15 @@ pub mod protrace {
         ctx: Context<VerifyMerkleProof>,
         leaf: [u8; 32],
         proof: Vec<[u8; 32]>,
+        tree_depth: u8,
     ) -> Result<()> {
         let merkle_account = &ctx.accounts.merkle_account;
 
+        // Reject truncated or padded proofs before hashing
+        validate_proof_length(&proof, tree_depth)?;
+
         // Reconstruct root from leaf and proof
-        let mut computed_hash = leaf;
-
-        for sibling in proof {
-            let mut combined = Vec::new();
-            if computed_hash <= sibling {
-                combined.extend_from_slice(&computed_hash);
-                combined.extend_from_slice(&sibling);
-            } else {
-                combined.extend_from_slice(&sibling);
-                combined.extend_from_slice(&computed_hash);
-            }
-            // Use blake3 for hashing (already in dependencies)
-            let hash_result = blake3::hash(&combined);
-            computed_hash = *hash_result.as_bytes();
-        }
+        let computed_hash = compute_proof_root(leaf, &proof);
 
         // Check if computed root matches stored root
         require!(computed_hash == merkle_account.root, ProTraceError::InvalidProof);
@@ -270,12 +392,13 @@ pub mod protrace {
 }
 
 #[derive(Accounts)]
+#[instruction(registry_id: [u8; 16])]
 pub struct AnchorMerkleRootOracle<'info> {
     #[account(
         init_if_needed,
         payer = oracle_authority,
         space = 8 + AnchorAccount::LEN,
-        seeds = [b"protrace_anchor"],
+        seeds = [b"protrace_anchor", oracle_authority.key().as_ref(), registry_id.as_ref()],
         bump
     )]
     pub anchor_account: Account<'info, AnchorAccount>,
@@ -284,6 +407,29 @@ pub struct AnchorMerkleRootOracle<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(
+    registry_id: [u8; 16],
+    merkle_root: [u8; 32],
+    manifest_cid: String,
+    asset_count: u64,
+    timestamp: i64,
+    oracle: Pubkey
+)]
+pub struct AnchorMerkleRootRelayed<'info> {
+    #[account(
+        mut,
+        seeds = [b"protrace_anchor", oracle.as_ref(), registry_id.as_ref()],
+        bump
+    )]
+    pub anchor_account: Account<'info, AnchorAccount>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: pinned to the instructions sysvar by address
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeMerkleRoot<'info> {
     #[account(
@@ -321,7 +467,7 @@ pub struct VerifyMerkleProof<'info> {
 #[instruction(dna_hash: String)]
 pub struct AnchorDnaHash<'info> {
     #[account(
-        init,
+        init_if_needed,
         payer = user,
         space = 8 + HashData::LEN,
         seeds = [b"protrace_dna", user.key().as_ref(), dna_hash.as_bytes()],
@@ -335,6 +481,7 @@ pub struct AnchorDnaHash<'info> {
 
 #[account]
 pub struct AnchorAccount {
+    pub schema_version: u8,            // Layout version (ACCOUNT_SCHEMA_VERSION)
     pub oracle_authority: Pubkey,      // Designated oracle that can anchor
     pub merkle_root: [u8; 32],         // Current anchored Merkle root
     pub manifest_cid: String,          // IPFS CID of manifest
@@ -342,12 +489,22 @@ pub struct AnchorAccount {
     pub timestamp: i64,                // When this was anchored
     pub oracle_signature: Pubkey,      // Oracle that performed anchoring
     pub version: u64,                  // Version counter
+    pub registry_id: [u8; 16],         // Registry this account anchors
 }
 
 impl AnchorAccount {
-    const LEN: usize = 32 + (4 + 64) + 8 + 8 + 32 + 8; // oracle_auth + cid + count + ts + sig + ver
+    // schema + oracle_auth + root + cid + count + ts + sig + ver + registry
+    const LEN: usize = 1 + 32 + 32 + (4 + MAX_MANIFEST_CID_LEN) + 8 + 8 + 32 + 8 + 16;
 }
 
+// Accounts are created with `init`, which allocates through a CPI and is
+// capped at 10 KiB; fail the build rather than the first transaction.
+const MAX_INIT_ACCOUNT_SPACE: usize = 10_240;
+const _: () = assert!(8 + AnchorAccount::LEN <= MAX_INIT_ACCOUNT_SPACE);
+const _: () = assert!(8 + MerkleAccount::LEN <= MAX_INIT_ACCOUNT_SPACE);
+const _: () = assert!(8 + HashData::LEN <= MAX_INIT_ACCOUNT_SPACE);
+const _: () = assert!(8 + EditionRegistryAccount::LEN <= MAX_INIT_ACCOUNT_SPACE);
+
 #[account]
 pub struct MerkleAccount {
     pub root: [u8; 32],
@@ -368,10 +525,280 @@ pub struct HashData {
     pub owner: Pubkey,         // Owner/creator of the asset
     pub timestamp: i64,        // When this hash was anchored
     pub edition_mode: u8,      // 0 = SERIAL, 1 = STRICT_1_1
+    pub platform_id: String,   // Registering platform (≤ 32 bytes)
+    pub pointer: String,       // Off-chain pointer, e.g. UUID or IPFS CID (≤ 64 bytes)
 }
 
 impl HashData {
-    const LEN: usize = 4 + 64 + 32 + 8 + 1; // string length + 64 chars + pubkey + timestamp + mode
+    // dna_hash + pubkey + timestamp + mode + platform_id + pointer
+    const LEN: usize = (4 + 64) + 32 + 8 + 1 + (4 + MAX_PLATFORM_ID_LEN) + (4 + MAX_POINTER_LEN);
+}
+
+/// Apply an oracle anchor to a registry's `AnchorAccount`
+///
+/// The account's PDA is seeded by the oracle, so the oracle that creates it
+/// becomes its designated authority; later anchors must come from the same
+/// oracle. CIDs longer than
+/// `MAX_MANIFEST_CID_LEN` are rejected before the account is touched.
+fn record_anchor(
+    anchor_account: &mut AnchorAccount,
+    registry_id: [u8; 16],
+    oracle: Pubkey,
+    merkle_root: [u8; 32],
+    manifest_cid: String,
+    asset_count: u64,
+    timestamp: i64,
+) -> Result<()> {
+    require!(
+        manifest_cid.len() <= MAX_MANIFEST_CID_LEN,
+        ProTraceError::CidTooLong
+    );
+
+    // Initialize oracle_authority on first use
+    if anchor_account.version == 0 {
+        anchor_account.schema_version = ACCOUNT_SCHEMA_VERSION;
+        anchor_account.oracle_authority = oracle;
+        anchor_account.registry_id = registry_id;
+    }
+
+    // Only allow the designated oracle authority to anchor
+    require!(
+        oracle == anchor_account.oracle_authority,
+        ProTraceError::UnauthorizedOracle
+    );
+
+    // Update the anchor record
+    anchor_account.merkle_root = merkle_root;
+    anchor_account.manifest_cid = manifest_cid;
+    anchor_account.asset_count = asset_count;
+    anchor_account.timestamp = timestamp;
+    anchor_account.oracle_signature = oracle;
+    anchor_account.version += 1;
+
+    Ok(())
+}
+
+/// Check a relayed anchor against the registry's existing `AnchorAccount`
+///
+/// The registry must already have been anchored directly by its oracle,
+/// `oracle` must be the authority stored there, and the intent must be newer
+/// than the current anchor.
+fn authorize_relayed_anchor(
+    anchor_account: &AnchorAccount,
+    oracle: &Pubkey,
+    timestamp: i64,
+) -> Result<()> {
+    require!(
+        anchor_account.version > 0,
+        ProTraceError::AnchorAccountNotInitialized
+    );
+    require_keys_eq!(
+        *oracle,
+        anchor_account.oracle_authority,
+        ProTraceError::UnauthorizedOracle
+    );
+    require!(
+        timestamp > anchor_account.timestamp,
+        ProTraceError::StaleOracleIntent
+    );
+
+    Ok(())
+}
+
+/// Message an oracle signs to authorize a relayed anchor
+///
+/// Layout: `ANCHOR_INTENT_DOMAIN`, program ID, registry ID, root,
+/// asset count and timestamp (little-endian), then the CID bytes. Binding
+/// the program ID keeps an intent from being replayed on another deployment.
+pub fn anchor_intent_message(
+    program_id: &Pubkey,
+    registry_id: [u8; 16],
+    merkle_root: [u8; 32],
+    manifest_cid: &str,
+    asset_count: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ANCHOR_INTENT_DOMAIN.len() + 96 + manifest_cid.len());
+    message.extend_from_slice(ANCHOR_INTENT_DOMAIN);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(&registry_id);
+    message.extend_from_slice(&merkle_root);
+    message.extend_from_slice(&asset_count.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message.extend_from_slice(manifest_cid.as_bytes());
+    message
+}
+
+/// Size of one per-signature offsets record in Ed25519 program data
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Check that an Ed25519 program instruction verifies `oracle` signing `message`
+///
+/// The runtime has already rejected the transaction if the signature is
+/// invalid, so only the key and message it was checked against matter.
+/// Exactly one signature is accepted, with all its data inside `ix` itself.
+fn verify_oracle_intent(ix: &Instruction, oracle: &Pubkey, message: &[u8]) -> Result<()> {
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        ProTraceError::MissingOracleSignature
+    );
+
+    // Header: signature count and a padding byte, then the offsets record
+    let data = &ix.data;
+    require!(
+        data.len() >= 2 + ED25519_OFFSETS_LEN && data[0] == 1,
+        ProTraceError::OracleIntentMismatch
+    );
+    let field = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]);
+    let [_, signature_ix, pubkey_offset, pubkey_ix, message_offset, message_size, message_ix] =
+        [0, 1, 2, 3, 4, 5, 6].map(field);
+
+    // u16::MAX means "this instruction"; anything else would let the
+    // signed bytes differ from the ones checked below
+    require!(
+        [signature_ix, pubkey_ix, message_ix]
+            .iter()
+            .all(|&i| i == u16::MAX),
+        ProTraceError::OracleIntentMismatch
+    );
+
+    let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len);
+    require!(
+        slice(pubkey_offset, 32) == Some(oracle.as_ref()),
+        ProTraceError::OracleIntentMismatch
+    );
+    require!(
+        slice(message_offset, message_size as usize) == Some(message),
+        ProTraceError::OracleIntentMismatch
+    );
+
+    Ok(())
+}
+
+/// Put a freshly created `EditionRegistryAccount` into its empty state
+fn reset_edition_registry(
+    edition_registry: &mut EditionRegistryAccount,
+    oracle_authority: Pubkey,
+    timestamp: i64,
+) {
+    edition_registry.schema_version = ACCOUNT_SCHEMA_VERSION;
+    edition_registry.oracle_authority = oracle_authority;
+    edition_registry.merkle_root = [0u8; 32]; // Empty tree root
+    edition_registry.ipfs_cid = "".to_string();
+    edition_registry.total_editions = 0;
+    edition_registry.chain_counts = [ChainTotal::default(); MAX_TRACKED_CHAINS];
+    edition_registry.last_batch_id = "".to_string();
+    edition_registry.last_batch_timestamp = timestamp;
+    edition_registry.last_oracle_signature = oracle_authority;
+    edition_registry.version = 0;
+}
+
+/// Decode a v0 edition registry account and convert it to the current layout
+///
+/// `data` is the whole account, discriminator included. v0 accounts were
+/// allocated with exactly `8 + EditionRegistryAccountV0::LEN` bytes, which
+/// no current-layout account has, so the size alone tells them apart.
+fn migrate_edition_registry_v0(data: &[u8]) -> Result<EditionRegistryAccount> {
+    require!(
+        data.len() == 8 + EditionRegistryAccountV0::LEN,
+        ProTraceError::RegistryAlreadyMigrated
+    );
+    let v0 = EditionRegistryAccountV0::deserialize(&mut &data[8..])?;
+
+    Ok(EditionRegistryAccount {
+        schema_version: ACCOUNT_SCHEMA_VERSION,
+        oracle_authority: v0.oracle_authority,
+        merkle_root: v0.merkle_root,
+        ipfs_cid: v0.ipfs_cid,
+        total_editions: v0.total_editions,
+        last_batch_id: v0.last_batch_id,
+        last_batch_timestamp: v0.last_batch_timestamp,
+        last_oracle_signature: v0.last_oracle_signature,
+        version: v0.version,
+        chain_counts: [ChainTotal::default(); MAX_TRACKED_CHAINS],
+    })
+}
+
+/// Require a proof to have exactly one sibling per tree level
+fn validate_proof_length(proof: &[[u8; 32]], tree_depth: u8) -> Result<()> {
+    require!(
+        proof.len() == tree_depth as usize,
+        ProTraceError::ProofLengthMismatch
+    );
+    Ok(())
+}
+
+/// Hash a leaf up through its proof using sorted-pair ordering
+fn compute_proof_root(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut computed_hash = leaf;
+
+    for sibling in proof {
+        let mut combined = Vec::new();
+        if computed_hash <= *sibling {
+            combined.extend_from_slice(&computed_hash);
+            combined.extend_from_slice(sibling);
+        } else {
+            combined.extend_from_slice(sibling);
+            combined.extend_from_slice(&computed_hash);
+        }
+        // Use blake3 for hashing (already in dependencies)
+        let hash_result = blake3::hash(&combined);
+        computed_hash = *hash_result.as_bytes();
+    }
+
+    computed_hash
+}
+
+/// Add each update's edition to its chain's total
+///
+/// A chain not yet tracked takes the first unused slot; a batch that would
+/// need more than `MAX_TRACKED_CHAINS` slots is rejected as a whole.
+fn record_chain_counts(
+    chain_counts: &mut [ChainTotal; MAX_TRACKED_CHAINS],
+    edition_updates: &[EditionUpdate],
+) -> Result<()> {
+    let mut updated = *chain_counts;
+
+    for edition_update in edition_updates {
+        let tracked = updated
+            .iter()
+            .position(|t| t.count > 0 && t.chain == edition_update.chain);
+        let slot = match tracked {
+            Some(slot) => slot,
+            None => updated
+                .iter()
+                .position(|t| t.count == 0)
+                .ok_or(ProTraceError::TooManyChains)?,
+        };
+        updated[slot].chain = edition_update.chain;
+        updated[slot].count += 1;
+    }
+
+    *chain_counts = updated;
+    Ok(())
+}
+
+/// Reject anchoring into a `HashData` account that already holds a DNA hash
+fn ensure_unregistered(hash_data: &HashData) -> Result<()> {
+    require!(
+        hash_data.dna_hash.is_empty(),
+        ProTraceError::DnaAlreadyRegistered
+    );
+    Ok(())
+}
+
+/// Check `anchor_dna_hash` metadata against the space reserved in `HashData`
+fn validate_hash_metadata(platform_id: &str, pointer: &str) -> Result<()> {
+    require!(
+        platform_id.len() <= MAX_PLATFORM_ID_LEN,
+        ProTraceError::PlatformIdTooLong
+    );
+    require!(
+        pointer.len() <= MAX_POINTER_LEN,
+        ProTraceError::PointerTooLong
+    );
+    Ok(())
 }
 
 // Edition Management Data Structures
@@ -393,9 +820,17 @@ pub struct EditionUpdate {
     pub max_editions: Option<u32>,
 }
 
+/// Editions registered on one chain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ChainTotal {
+    pub chain: [u8; 10],
+    pub count: u64,
+}
+
 // Edition Registry Account
 #[account]
 pub struct EditionRegistryAccount {
+    pub schema_version: u8,            // Layout version (ACCOUNT_SCHEMA_VERSION)
     pub oracle_authority: Pubkey,      // Designated oracle that can update
     pub merkle_root: [u8; 32],         // Current Merkle root of edition registry
     pub ipfs_cid: String,              // IPFS CID of current registry snapshot
@@ -404,11 +839,39 @@ pub struct EditionRegistryAccount {
     pub last_batch_timestamp: i64,     // Timestamp of last batch update
     pub last_oracle_signature: Pubkey, // Oracle that performed last update
     pub version: u64,                  // Version counter
+    pub chain_counts: [ChainTotal; MAX_TRACKED_CHAINS], // Per-chain totals (count 0 = unused)
 }
 
 impl EditionRegistryAccount {
+    const LEN: usize = 1 + 32 + 32 + (4 + 64) + 8 + (4 + 64) + 8 + 32 + 8
+        + (10 + 8) * MAX_TRACKED_CHAINS;
+    // schema + oracle_auth + merkle_root + ipfs_cid + total_editions + last_batch_id + last_timestamp + last_sig + version
+    // + chain_counts
+
+    /// Editions registered on `chain`, or 0 if it isn't tracked
+    pub fn chain_count(&self, chain: [u8; 10]) -> u64 {
+        self.chain_counts
+            .iter()
+            .find(|t| t.count > 0 && t.chain == chain)
+            .map_or(0, |t| t.count)
+    }
+}
+
+/// `EditionRegistryAccount` as laid out before `schema_version` and `chain_counts`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct EditionRegistryAccountV0 {
+    pub oracle_authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub ipfs_cid: String,
+    pub total_editions: u64,
+    pub last_batch_id: String,
+    pub last_batch_timestamp: i64,
+    pub last_oracle_signature: Pubkey,
+    pub version: u64,
+}
+
+impl EditionRegistryAccountV0 {
     const LEN: usize = 32 + 32 + (4 + 64) + 8 + (4 + 64) + 8 + 32 + 8;
-    // oracle_auth + merkle_root + ipfs_cid + total_editions + last_batch_id + last_timestamp + last_sig + version
 }
 
 // Instruction Account Contexts
@@ -423,6 +886,22 @@ pub struct BatchRegisterEditions<'info> {
     pub oracle_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateEditionRegistry<'info> {
+    /// CHECK: v0 data doesn't load as `EditionRegistryAccount`; the handler
+    /// checks the discriminator and decodes the v0 layout itself
+    #[account(
+        mut,
+        seeds = [b"edition_registry"],
+        bump,
+        owner = crate::ID
+    )]
+    pub edition_registry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub oracle_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct VerifyEditionAuthorization<'info> {
     #[account(
@@ -432,6 +911,15 @@ pub struct VerifyEditionAuthorization<'info> {
     pub edition_registry: Account<'info, EditionRegistryAccount>,
 }
 
+#[derive(Accounts)]
+pub struct GetChainCount<'info> {
+    #[account(
+        seeds = [b"edition_registry"],
+        bump
+    )]
+    pub edition_registry: Account<'info, EditionRegistryAccount>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeEditionRegistry<'info> {
     #[account(
@@ -463,4 +951,472 @@ pub enum ProTraceError {
     InvalidDnaHashLength,
     #[msg("DNA hash must contain only valid hexadecimal characters")]
     InvalidDnaHashFormat,
+    #[msg("Platform ID exceeds 32 bytes")]
+    PlatformIdTooLong,
+    #[msg("Pointer exceeds 64 bytes")]
+    PointerTooLong,
+    #[msg("DNA hash already registered by this user")]
+    DnaAlreadyRegistered,
+    #[msg("Proof length does not match the expected tree depth")]
+    ProofLengthMismatch,
+    #[msg("Edition registry already tracks the maximum number of chains")]
+    TooManyChains,
+    #[msg("Manifest CID exceeds 64 bytes")]
+    CidTooLong,
+    #[msg("Relayed anchor must follow an Ed25519 signature instruction")]
+    MissingOracleSignature,
+    #[msg("Ed25519 instruction does not cover the oracle's anchor intent")]
+    OracleIntentMismatch,
+    #[msg("Oracle intent is not newer than the current anchor")]
+    StaleOracleIntent,
+    #[msg("Anchor account must be created by a direct oracle anchor before relaying")]
+    AnchorAccountNotInitialized,
+    #[msg("Edition registry is not in the pre-schema (v0) layout")]
+    RegistryAlreadyMigrated,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_data_metadata_roundtrip() {
+        let platform_id = "p".repeat(MAX_PLATFORM_ID_LEN);
+        let pointer = "q".repeat(MAX_POINTER_LEN);
+        validate_hash_metadata(&platform_id, &pointer).unwrap();
+
+        let hash_data = HashData {
+            dna_hash: "ab".repeat(32),
+            owner: Pubkey::new_unique(),
+            timestamp: 1698765432,
+            edition_mode: 1,
+            platform_id,
+            pointer,
+        };
+
+        // Largest allowed metadata fits the allocated account space
+        let mut data = Vec::new();
+        hash_data.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + HashData::LEN);
+
+        let read_back = HashData::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(read_back.dna_hash, hash_data.dna_hash);
+        assert_eq!(read_back.owner, hash_data.owner);
+        assert_eq!(read_back.edition_mode, 1);
+        assert_eq!(read_back.platform_id, hash_data.platform_id);
+        assert_eq!(read_back.pointer, hash_data.pointer);
+    }
+
+    #[test]
+    fn test_hash_metadata_bounds() {
+        let too_long_platform = "p".repeat(MAX_PLATFORM_ID_LEN + 1);
+        let too_long_pointer = "q".repeat(MAX_POINTER_LEN + 1);
+
+        assert_eq!(
+            validate_hash_metadata(&too_long_platform, "ipfs://Qm"),
+            Err(ProTraceError::PlatformIdTooLong.into())
+        );
+        assert_eq!(
+            validate_hash_metadata("opensea", &too_long_pointer),
+            Err(ProTraceError::PointerTooLong.into())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_dna_registration_rejected() {
+        let mut hash_data = HashData {
+            dna_hash: String::new(),
+            owner: Pubkey::default(),
+            timestamp: 0,
+            edition_mode: 0,
+            platform_id: String::new(),
+            pointer: String::new(),
+        };
+
+        // Freshly created PDA accepts the first registration
+        ensure_unregistered(&hash_data).unwrap();
+
+        hash_data.dna_hash = "ab".repeat(32);
+        hash_data.owner = Pubkey::new_unique();
+        assert_eq!(
+            ensure_unregistered(&hash_data),
+            Err(ProTraceError::DnaAlreadyRegistered.into())
+        );
+    }
+
+    #[test]
+    fn test_proof_length_must_match_depth() {
+        let leaf = [1u8; 32];
+        let proof = vec![[2u8; 32], [3u8; 32]];
+        let root = compute_proof_root(leaf, &proof);
+
+        validate_proof_length(&proof, 2).unwrap();
+        assert_ne!(root, leaf);
+
+        // Too short
+        assert_eq!(
+            validate_proof_length(&proof[..1], 2),
+            Err(ProTraceError::ProofLengthMismatch.into())
+        );
+
+        // Too long (padded)
+        let mut padded = proof.clone();
+        padded.push([0u8; 32]);
+        assert_eq!(
+            validate_proof_length(&padded, 2),
+            Err(ProTraceError::ProofLengthMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_registries_anchor_independently() {
+        let registry_a = [0xaa; 16];
+        let registry_b = [0xbb; 16];
+
+        let (oracle_a, oracle_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Each oracle and registry ID derives its own anchor PDA, so an
+        // oracle can't take over a registry ID another oracle uses
+        let pda = |oracle: &Pubkey, id: &[u8; 16]| {
+            Pubkey::find_program_address(&[b"protrace_anchor", oracle.as_ref(), id], &crate::ID).0
+        };
+        assert_ne!(pda(&oracle_a, &registry_a), pda(&oracle_a, &registry_b));
+        assert_ne!(pda(&oracle_a, &registry_a), pda(&oracle_b, &registry_a));
+
+        let empty = || AnchorAccount {
+            schema_version: 0,
+            oracle_authority: Pubkey::default(),
+            merkle_root: [0u8; 32],
+            manifest_cid: String::new(),
+            asset_count: 0,
+            timestamp: 0,
+            oracle_signature: Pubkey::default(),
+            version: 0,
+            registry_id: [0u8; 16],
+        };
+        let (mut account_a, mut account_b) = (empty(), empty());
+
+        record_anchor(&mut account_a, registry_a, oracle_a, [1u8; 32], "a".into(), 3, 100).unwrap();
+        record_anchor(&mut account_b, registry_b, oracle_b, [2u8; 32], "b".into(), 5, 200).unwrap();
+        record_anchor(&mut account_a, registry_a, oracle_a, [3u8; 32], "c".into(), 4, 300).unwrap();
+
+        assert_eq!(account_a.registry_id, registry_a);
+        assert_eq!(account_a.merkle_root, [3u8; 32]);
+        assert_eq!(account_a.version, 2);
+        assert_eq!(account_b.registry_id, registry_b);
+        assert_eq!(account_b.merkle_root, [2u8; 32]);
+        assert_eq!(account_b.version, 1);
+
+        // Each registry keeps its own oracle
+        assert_eq!(
+            record_anchor(&mut account_b, registry_b, oracle_a, [4u8; 32], String::new(), 1, 400),
+            Err(ProTraceError::UnauthorizedOracle.into())
+        );
+
+        // Largest CID still fits the allocated account space
+        account_a.manifest_cid = "c".repeat(MAX_MANIFEST_CID_LEN);
+        let mut data = Vec::new();
+        account_a.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + AnchorAccount::LEN);
+    }
+
+    /// Ed25519 program instruction with all data inline, as clients build it
+    fn ed25519_instruction(pubkey: &Pubkey, message: &[u8]) -> Instruction {
+        let (pubkey_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+        let mut data = vec![1u8, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey.as_ref());
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(message);
+
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: Vec::new(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_oracle_intent_verification() {
+        let oracle = Pubkey::new_unique();
+        let intent = |root: [u8; 32]| {
+            anchor_intent_message(&crate::ID, [1u8; 16], root, "bafyintent", 3, 1_700_000_000)
+        };
+        let message = intent([9u8; 32]);
+
+        verify_oracle_intent(&ed25519_instruction(&oracle, &message), &oracle, &message).unwrap();
+
+        // Signed by someone else
+        let forger = Pubkey::new_unique();
+        assert_eq!(
+            verify_oracle_intent(&ed25519_instruction(&forger, &message), &oracle, &message),
+            Err(ProTraceError::OracleIntentMismatch.into())
+        );
+
+        // Oracle signed a different root than the one submitted
+        let signed = ed25519_instruction(&oracle, &intent([8u8; 32]));
+        assert_eq!(
+            verify_oracle_intent(&signed, &oracle, &message),
+            Err(ProTraceError::OracleIntentMismatch.into())
+        );
+
+        // Key read from another instruction
+        let mut elsewhere = ed25519_instruction(&oracle, &message);
+        elsewhere.data[8..10].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(
+            verify_oracle_intent(&elsewhere, &oracle, &message),
+            Err(ProTraceError::OracleIntentMismatch.into())
+        );
+
+        // Not an Ed25519 program instruction at all
+        let mut other_program = ed25519_instruction(&oracle, &message);
+        other_program.program_id = crate::ID;
+        assert!(verify_oracle_intent(&other_program, &oracle, &message).is_err());
+    }
+
+    #[test]
+    fn test_relayed_anchor_needs_existing_registry() {
+        let oracle = Pubkey::new_unique();
+        let mut account = AnchorAccount {
+            schema_version: 0,
+            oracle_authority: Pubkey::default(),
+            merkle_root: [0u8; 32],
+            manifest_cid: String::new(),
+            asset_count: 0,
+            timestamp: 0,
+            oracle_signature: Pubkey::default(),
+            version: 0,
+            registry_id: [0u8; 16],
+        };
+
+        // A relayer can't create the registry
+        assert_eq!(
+            authorize_relayed_anchor(&account, &oracle, 100),
+            Err(ProTraceError::AnchorAccountNotInitialized.into())
+        );
+
+        record_anchor(&mut account, [1u8; 16], oracle, [1u8; 32], String::new(), 1, 100).unwrap();
+        authorize_relayed_anchor(&account, &oracle, 101).unwrap();
+
+        // Only the stored authority's intents are accepted
+        assert!(authorize_relayed_anchor(&account, &Pubkey::new_unique(), 101).is_err());
+
+        // Replays of the current or older intents are rejected
+        assert_eq!(
+            authorize_relayed_anchor(&account, &oracle, 100),
+            Err(ProTraceError::StaleOracleIntent.into())
+        );
+    }
+
+    #[test]
+    fn test_manifest_cid_too_long() {
+        let mut account = AnchorAccount {
+            schema_version: 0,
+            oracle_authority: Pubkey::default(),
+            merkle_root: [0u8; 32],
+            manifest_cid: String::new(),
+            asset_count: 0,
+            timestamp: 0,
+            oracle_signature: Pubkey::default(),
+            version: 0,
+            registry_id: [0u8; 16],
+        };
+        let oracle = Pubkey::new_unique();
+
+        assert_eq!(
+            record_anchor(&mut account, [1u8; 16], oracle, [1u8; 32], "c".repeat(100), 1, 100),
+            Err(ProTraceError::CidTooLong.into())
+        );
+        // Rejected before anything was written
+        assert_eq!(account.version, 0);
+        assert_eq!(account.oracle_authority, Pubkey::default());
+
+        let cid = "c".repeat(MAX_MANIFEST_CID_LEN);
+        record_anchor(&mut account, [1u8; 16], oracle, [1u8; 32], cid, 1, 100).unwrap();
+        assert_eq!(account.version, 1);
+    }
+
+    #[test]
+    fn test_new_accounts_carry_schema_version() {
+        let mut anchor = AnchorAccount {
+            schema_version: 0,
+            oracle_authority: Pubkey::default(),
+            merkle_root: [0u8; 32],
+            manifest_cid: String::new(),
+            asset_count: 0,
+            timestamp: 0,
+            oracle_signature: Pubkey::default(),
+            version: 0,
+            registry_id: [0u8; 16],
+        };
+        let oracle = Pubkey::new_unique();
+        record_anchor(&mut anchor, [1u8; 16], oracle, [1u8; 32], "c".into(), 1, 100).unwrap();
+        assert_eq!(anchor.schema_version, ACCOUNT_SCHEMA_VERSION);
+
+        let mut registry = EditionRegistryAccount {
+            schema_version: 0,
+            oracle_authority: Pubkey::default(),
+            merkle_root: [0xff; 32],
+            ipfs_cid: "stale".into(),
+            total_editions: 9,
+            last_batch_id: "stale".into(),
+            last_batch_timestamp: 0,
+            last_oracle_signature: Pubkey::default(),
+            version: 4,
+            chain_counts: [ChainTotal::default(); MAX_TRACKED_CHAINS],
+        };
+        reset_edition_registry(&mut registry, oracle, 1698765432);
+        assert_eq!(registry.schema_version, ACCOUNT_SCHEMA_VERSION);
+        assert_eq!(registry.oracle_authority, oracle);
+        assert_eq!(registry.version, 0);
+
+        // The schema byte is the first thing after the discriminator
+        let mut data = Vec::new();
+        anchor.try_serialize(&mut data).unwrap();
+        assert_eq!(data[8], ACCOUNT_SCHEMA_VERSION);
+        data.clear();
+        registry.try_serialize(&mut data).unwrap();
+        assert_eq!(data[8], ACCOUNT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_edition_registry_v0() {
+        let v0 = EditionRegistryAccountV0 {
+            oracle_authority: Pubkey::new_from_array([1u8; 32]),
+            merkle_root: [2u8; 32],
+            ipfs_cid: "bafyregistry".into(),
+            total_editions: 5,
+            last_batch_id: "batch-7".into(),
+            last_batch_timestamp: 1698765432,
+            last_oracle_signature: Pubkey::new_from_array([3u8; 32]),
+            version: 4,
+        };
+        // As allocated on chain: discriminator, fields, zero padding
+        let mut data = EditionRegistryAccount::DISCRIMINATOR.to_vec();
+        v0.serialize(&mut data).unwrap();
+        data.resize(8 + EditionRegistryAccountV0::LEN, 0);
+
+        let migrated = migrate_edition_registry_v0(&data).unwrap();
+        assert_eq!(migrated.schema_version, ACCOUNT_SCHEMA_VERSION);
+        assert_eq!(migrated.oracle_authority, v0.oracle_authority);
+        assert_eq!(migrated.merkle_root, v0.merkle_root);
+        assert_eq!(migrated.ipfs_cid, "bafyregistry");
+        assert_eq!(migrated.total_editions, 5);
+        assert_eq!(migrated.last_batch_id, "batch-7");
+        assert_eq!(migrated.last_batch_timestamp, 1698765432);
+        assert_eq!(migrated.last_oracle_signature, v0.last_oracle_signature);
+        assert_eq!(migrated.version, 4);
+        assert_eq!(migrated.chain_count(*b"ethereum\0\0"), 0);
+
+        // The migrated account fits the space it is resized to
+        let mut resized = vec![0u8; 8 + EditionRegistryAccount::LEN];
+        migrated.try_serialize(&mut &mut resized[..]).unwrap();
+        let reloaded = EditionRegistryAccount::try_deserialize(&mut &resized[..]).unwrap();
+        assert_eq!(reloaded.version, 4);
+
+        // Current-layout accounts are not migrated again
+        assert_eq!(
+            migrate_edition_registry_v0(&resized).err(),
+            Some(ProTraceError::RegistryAlreadyMigrated.into())
+        );
+    }
+
+    /// Serialized `EditionRegistryAccount` the bindings client decodes in its tests
+    const EDITION_REGISTRY_FIXTURE: &str = include_str!(
+        "../../../../ProPy/modules/protrace_rust_bindings/crates/blockchain/tests/fixtures/edition_registry_v1.hex"
+    );
+
+    #[test]
+    fn test_edition_registry_matches_client_fixture() {
+        let chain = |name: &str| {
+            let mut id = [0u8; 10];
+            id[..name.len()].copy_from_slice(name.as_bytes());
+            id
+        };
+        let mut chain_counts = [ChainTotal::default(); MAX_TRACKED_CHAINS];
+        chain_counts[0] = ChainTotal { chain: chain("ethereum"), count: 2 };
+        chain_counts[1] = ChainTotal { chain: chain("solana"), count: 3 };
+
+        let registry = EditionRegistryAccount {
+            schema_version: ACCOUNT_SCHEMA_VERSION,
+            oracle_authority: Pubkey::new_from_array([1u8; 32]),
+            merkle_root: [2u8; 32],
+            ipfs_cid: "bafyregistry".into(),
+            total_editions: 5,
+            last_batch_id: "batch-7".into(),
+            last_batch_timestamp: 1698765432,
+            last_oracle_signature: Pubkey::new_from_array([3u8; 32]),
+            version: 4,
+            chain_counts,
+        };
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+
+        assert_eq!(hex::encode(&data), EDITION_REGISTRY_FIXTURE.trim());
+    }
+
+    #[test]
+    fn test_chain_counts() {
+        let chain = |name: &str| {
+            let mut id = [0u8; 10];
+            id[..name.len()].copy_from_slice(name.as_bytes());
+            id
+        };
+        let update = |name: &str| EditionUpdate {
+            dna_hash: [7u8; 32],
+            chain: chain(name),
+            contract: [0u8; 32],
+            token_id: [0u8; 32],
+            edition_no: 1,
+            edition_mode: EditionMode::Serial,
+            max_editions: None,
+        };
+
+        let mut registry = EditionRegistryAccount {
+            schema_version: ACCOUNT_SCHEMA_VERSION,
+            oracle_authority: Pubkey::new_unique(),
+            merkle_root: [0u8; 32],
+            ipfs_cid: "c".repeat(64),
+            total_editions: 0,
+            last_batch_id: "b".repeat(64),
+            last_batch_timestamp: 0,
+            last_oracle_signature: Pubkey::default(),
+            version: 0,
+            chain_counts: [ChainTotal::default(); MAX_TRACKED_CHAINS],
+        };
+
+        let first = [update("ethereum"), update("solana"), update("ethereum")];
+        record_chain_counts(&mut registry.chain_counts, &first).unwrap();
+        record_chain_counts(&mut registry.chain_counts, &[update("solana")]).unwrap();
+
+        assert_eq!(registry.chain_count(chain("ethereum")), 2);
+        assert_eq!(registry.chain_count(chain("solana")), 2);
+        assert_eq!(registry.chain_count(chain("polygon")), 0);
+
+        // A batch needing a ninth slot is rejected without changing any count
+        let names = ["c2", "c3", "c4", "c5", "c6", "c7", "c8"];
+        let overflow: Vec<_> = names.iter().map(|name| update(name)).collect();
+        assert_eq!(
+            record_chain_counts(&mut registry.chain_counts, &overflow),
+            Err(ProTraceError::TooManyChains.into())
+        );
+        assert_eq!(registry.chain_count(chain("c2")), 0);
+        record_chain_counts(&mut registry.chain_counts, &overflow[..6]).unwrap();
+        assert_eq!(registry.chain_count(chain("c7")), 1);
+
+        // Full per-chain table still fits the allocated account space
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + EditionRegistryAccount::LEN);
+    }
 }